@@ -1,14 +1,17 @@
 use std::collections::HashMap;
 use std::env;
 
-use clap::{Arg, Command};
-use tokio::runtime::Builder;
+use clap::{Arg, ArgAction, Command};
 use lumni::EnvironmentConfig;
+use tokio::runtime::Builder;
 
 use crate::subcommands::cp::*;
 use crate::subcommands::ls::*;
+use crate::subcommands::presign::*;
 use crate::subcommands::query::*;
 use crate::subcommands::request::*;
+use crate::subcommands::rm::*;
+use crate::subcommands::sync::*;
 
 const PROGRAM_NAME: &str = "lumni";
 
@@ -28,10 +31,81 @@ pub fn run_cli(args: Vec<String>) {
                 .short('r')
                 .help("Region to use"),
         )
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .help("Profile from the AWS config and credentials files"),
+        )
+        .arg(
+            Arg::new("provider")
+                .long("provider")
+                .value_parser([
+                    "aws", "minio", "ceph-rgw", "r2", "b2", "wasabi",
+                ])
+                .help(
+                    "S3 compatible service, sets the default endpoint, region \
+                     and addressing style (signing is the same for all)",
+                ),
+        )
+        .arg(
+            Arg::new("addressing-style")
+                .long("addressing-style")
+                .value_parser(["auto", "path", "virtual"])
+                .help("Address the bucket in the host name or in the path"),
+        )
+        .arg(
+            Arg::new("no-sign-request")
+                .long("no-sign-request")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Send requests without credentials, e.g. to public buckets",
+                ),
+        )
+        .arg(
+            Arg::new("checksum-algorithm")
+                .long("checksum-algorithm")
+                .value_parser(["md5", "sha256", "crc32c"])
+                .help("Checksum sent with uploads, verified by the server"),
+        )
+        .arg(
+            Arg::new("verify-checksum")
+                .long("verify-checksum")
+                .action(ArgAction::SetTrue)
+                .help("Verify downloads against the ETag of the object"),
+        )
+        .arg(
+            Arg::new("ca-bundle")
+                .long("ca-bundle")
+                .value_name("PATH")
+                .help("PEM file with CA certificates to trust"),
+        )
+        .arg(
+            Arg::new("client-cert")
+                .long("client-cert")
+                .value_name("PATH")
+                .requires("client-key")
+                .help("PEM client certificate for mutual TLS"),
+        )
+        .arg(
+            Arg::new("client-key")
+                .long("client-key")
+                .value_name("PATH")
+                .requires("client-cert")
+                .help("PEM (PKCS#8) private key of the client certificate"),
+        )
+        .arg(
+            Arg::new("insecure")
+                .long("insecure")
+                .action(ArgAction::SetTrue)
+                .help("Skip verification of TLS certificates"),
+        )
         .subcommand(request_subcommand()) // "-X/--request [GET,PUT]"
         .subcommand(query_subcommand()) // "-Q/--query [SELECT,DESCRIBE]"
         .subcommand(ls_subcommand()) // "ls [URI]"
-        .subcommand(cp_subcommand()); // "cp" [SOURCE] [TARGET]
+        .subcommand(cp_subcommand()) // "cp" [SOURCE] [TARGET]
+        .subcommand(rm_subcommand()) // "rm" [URI]
+        .subcommand(presign_subcommand()) // "presign" [URI]
+        .subcommand(sync_subcommand()); // "sync" [SOURCE] [TARGET]
 
     let matches = app.try_get_matches_from(args).unwrap_or_else(|e| {
         e.exit();
@@ -53,6 +127,15 @@ pub fn run_cli(args: Vec<String>) {
         Some(("cp", matches)) => {
             rt.block_on(handle_cp(matches, &mut config));
         }
+        Some(("rm", matches)) => {
+            rt.block_on(handle_rm(matches, &mut config));
+        }
+        Some(("presign", matches)) => {
+            rt.block_on(handle_presign(matches, &mut config));
+        }
+        Some(("sync", matches)) => {
+            rt.block_on(handle_sync(matches, &mut config));
+        }
         _ => {
             eprintln!("No valid subcommand provided");
         }
@@ -64,6 +147,43 @@ fn create_initial_config(matches: &clap::ArgMatches) -> EnvironmentConfig {
     if let Some(region) = matches.get_one::<String>("region") {
         config_hashmap.insert("region".to_string(), region.to_string());
     }
+    if let Some(profile) = matches.get_one::<String>("profile") {
+        config_hashmap.insert("AWS_PROFILE".to_string(), profile.to_string());
+    }
+    if let Some(provider) = matches.get_one::<String>("provider") {
+        config_hashmap.insert("S3_PROVIDER".to_string(), provider.to_string());
+    }
+    if let Some(style) = matches.get_one::<String>("addressing-style") {
+        config_hashmap
+            .insert("S3_ADDRESSING_STYLE".to_string(), style.to_string());
+    }
+    if let Some(algorithm) = matches.get_one::<String>("checksum-algorithm") {
+        config_hashmap
+            .insert("S3_CHECKSUM_ALGORITHM".to_string(), algorithm.to_string());
+    }
+    if matches.get_flag("verify-checksum") {
+        config_hashmap
+            .insert("S3_VERIFY_CHECKSUM".to_string(), "true".to_string());
+    }
+
+    // TLS settings, these take precedence over the environment
+    let tls_settings = [
+        ("ca-bundle", "AWS_CA_BUNDLE"),
+        ("client-cert", "HTTP_TLS_CLIENT_CERT"),
+        ("client-key", "HTTP_TLS_CLIENT_KEY"),
+    ];
+    for (arg, key) in tls_settings {
+        if let Some(value) = matches.get_one::<String>(arg) {
+            config_hashmap.insert(key.to_string(), value.to_string());
+        }
+    }
+    if matches.get_flag("no-sign-request") {
+        config_hashmap
+            .insert("S3_NO_SIGN_REQUEST".to_string(), "true".to_string());
+    }
+    if matches.get_flag("insecure") {
+        config_hashmap.insert("HTTP_TLS_INSECURE".to_string(), "true".into());
+    }
 
     // Create a Config instance
     EnvironmentConfig::new(config_hashmap)
@@ -1,6 +1,7 @@
-use clap::{Arg, Command};
+use clap::{Arg, ArgAction, Command};
 
 pub use super::cp_handler::handle_cp;
+use super::filters::filter_args;
 
 pub fn cp_subcommand() -> Command {
     Command::new("cp")
@@ -17,4 +18,12 @@ pub fn cp_subcommand() -> Command {
                 .required(true)
                 .help("Target URI to copy objects to"),
         )
+        .arg(
+            Arg::new("recursive")
+                .long("recursive")
+                .short('r')
+                .action(ArgAction::SetTrue)
+                .help("Copy all objects under the source prefix recursively"),
+        )
+        .args(filter_args())
 }
@@ -1,10 +1,251 @@
-use lumni::EnvironmentConfig;
+use std::cell::RefCell;
+use std::path::Path;
+
+use bytes::Bytes;
+use futures::stream;
+use log::error;
+use lumni::{
+    BinaryCallbackWrapper, EnvironmentConfig, FileObjectFilter,
+    LakestreamError, ObjectStoreHandler,
+};
+use tokio::sync::mpsc;
+
+use super::filters::parse_filter_args;
+use super::listing::{
+    join_uri, list_source_objects, local_path, object_matches,
+};
+
+// chunks of the source that are buffered while the target is written,
+// the source is read no faster than the target is written
+const COPY_CHANNEL_CAPACITY: usize = 16;
+
+// objects copied and objects that failed to copy
+#[derive(Debug, Default, PartialEq, Eq)]
+struct CopySummary {
+    copied: usize,
+    failed: usize,
+}
 
 pub async fn handle_cp(
     matches: &clap::ArgMatches,
-    _config: &mut EnvironmentConfig,
+    config: &mut EnvironmentConfig,
 ) {
     let source = matches.get_one::<String>("source").unwrap();
     let target = matches.get_one::<String>("target").unwrap();
-    println!("Copying from {} to {}", source, target);
+    let recursive = *matches.get_one::<bool>("recursive").unwrap_or(&false);
+
+    let filter = match parse_filter_args(matches) {
+        Ok(filter) => filter,
+        Err(err) => {
+            error!("Error creating filter: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let handler = ObjectStoreHandler::new(None);
+
+    let result = if recursive {
+        copy_prefix(&handler, source, target, config, &filter).await
+    } else {
+        copy_single(&handler, source, target, config, &filter).await
+    };
+
+    match result {
+        Ok(summary) => {
+            println!("Copied {} object(s)", summary.copied);
+            if summary.failed > 0 {
+                eprintln!("{} object(s) failed to copy", summary.failed);
+                std::process::exit(1);
+            }
+        }
+        Err(err) => {
+            error!("Error copying objects: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+// without --recursive the filter applies to the source object itself
+async fn copy_single(
+    handler: &ObjectStoreHandler,
+    source: &str,
+    target: &str,
+    config: &EnvironmentConfig,
+    filter: &Option<FileObjectFilter>,
+) -> Result<CopySummary, LakestreamError> {
+    if filter.is_some()
+        && !object_matches(handler, source, config, filter).await?
+    {
+        return Ok(CopySummary::default());
+    }
+    let target = target_object_uri(source, target);
+    copy_object(handler, source, &target, config).await?;
+    Ok(CopySummary {
+        copied: 1,
+        failed: 0,
+    })
+}
+
+async fn copy_prefix(
+    handler: &ObjectStoreHandler,
+    source: &str,
+    target: &str,
+    config: &EnvironmentConfig,
+    filter: &Option<FileObjectFilter>,
+) -> Result<CopySummary, LakestreamError> {
+    let objects =
+        list_source_objects(handler, source, config, true, filter, false)
+            .await?;
+
+    // a failed object does not stop the copy of the others
+    let mut summary = CopySummary::default();
+    for object in &objects {
+        let target_uri = join_uri(target, &object.relative_key);
+        match copy_object(handler, &object.uri, &target_uri, config).await {
+            Ok(()) => summary.copied += 1,
+            Err(err) => {
+                eprintln!("Error copying {}: {}", object.uri, err);
+                summary.failed += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+// the source is streamed into the upload of the target, so an object does
+// not have to fit in memory. If reading the source fails (e.g. on checksum
+// verification), the upload fails and no partial object is left behind
+pub async fn copy_object(
+    handler: &ObjectStoreHandler,
+    source_uri: &str,
+    target_uri: &str,
+    config: &EnvironmentConfig,
+) -> Result<(), LakestreamError> {
+    let size = handler.head_object(source_uri, config).await?.size();
+
+    let (sender, receiver) = mpsc::channel::<Bytes>(COPY_CHANNEL_CAPACITY);
+    let callback = BinaryCallbackWrapper::create_async(move |data: Vec<u8>| {
+        let sender = sender.clone();
+        async move {
+            // fails only when the upload has stopped, its error is returned
+            let _ = sender.send(Bytes::from(data)).await;
+        }
+    });
+
+    // an error reading the source is passed on as the last chunk
+    let read_error = RefCell::new(None);
+    let read = async {
+        let result = handler
+            .get_object(source_uri, config, None, Some(callback))
+            .await;
+        if let Err(err) = result {
+            *read_error.borrow_mut() = Some(err);
+        }
+        // the callback, and with it the sender, is dropped at this point,
+        // which ends the stream of chunks
+    };
+    let chunks = stream::unfold(Some(receiver), |receiver| async {
+        let mut receiver = receiver?;
+        match receiver.recv().await {
+            Some(chunk) => Some((Ok(chunk), Some(receiver))),
+            None => read_error.borrow_mut().take().map(|err| (Err(err), None)),
+        }
+    });
+    let write = handler.put_object_stream(
+        target_uri,
+        config,
+        Box::pin(chunks),
+        Some(size),
+        None,
+    );
+
+    let ((), result) = tokio::join!(read, write);
+    result?;
+    println!("copy: {} to {}", source_uri, target_uri);
+    Ok(())
+}
+
+// copying into a directory (a URI ending with "/", or an existing local
+// directory) keeps the name of the source object
+fn target_object_uri(source: &str, target: &str) -> String {
+    let is_local_dir =
+        local_path(target).is_some_and(|path| Path::new(path).is_dir());
+
+    if target.ends_with('/') || is_local_dir {
+        let file_name = source
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or(source);
+        join_uri(target, file_name)
+    } else {
+        target.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_copy_prefix_with_filter() {
+        let source_dir = tempdir().unwrap();
+        let target_dir = tempdir().unwrap();
+        fs::create_dir(source_dir.path().join("sub")).unwrap();
+        fs::write(source_dir.path().join("a.log"), b"a").unwrap();
+        fs::write(source_dir.path().join("sub/b.log"), b"bb").unwrap();
+        fs::write(source_dir.path().join("c.txt"), b"ccc").unwrap();
+
+        let source = format!("localfs://{}", source_dir.path().display());
+        let target = format!("localfs://{}", target_dir.path().display());
+        let filter =
+            Some(FileObjectFilter::new(Some(".*\\.log"), None, None).unwrap());
+
+        let handler = ObjectStoreHandler::new(None);
+        let config = EnvironmentConfig::default();
+        let summary = copy_prefix(&handler, &source, &target, &config, &filter)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            summary,
+            CopySummary {
+                copied: 2,
+                failed: 0
+            }
+        );
+        assert_eq!(fs::read(target_dir.path().join("a.log")).unwrap(), b"a");
+        assert_eq!(
+            fs::read(target_dir.path().join("sub/b.log")).unwrap(),
+            b"bb"
+        );
+        assert!(!target_dir.path().join("c.txt").exists());
+
+        // the filter also applies to a single object
+        let c_txt = format!("{}/c.txt", source);
+        let summary = copy_single(&handler, &c_txt, &target, &config, &filter)
+            .await
+            .unwrap();
+        assert_eq!(summary, CopySummary::default());
+        assert!(!target_dir.path().join("c.txt").exists());
+
+        // a failed object does not stop the others
+        fs::write(target_dir.path().join("file"), b"").unwrap();
+        let blocked = format!("{}/file", target);
+        let summary =
+            copy_prefix(&handler, &source, &blocked, &config, &filter)
+                .await
+                .unwrap();
+        assert_eq!(
+            summary,
+            CopySummary {
+                copied: 0,
+                failed: 2
+            }
+        );
+    }
 }
@@ -0,0 +1,51 @@
+use clap::Arg;
+use lumni::FileObjectFilter;
+
+// filter arguments shared by subcommands that operate on a listing
+pub fn filter_args() -> Vec<Arg> {
+    vec![
+        Arg::new("name")
+            .long("name")
+            .short('n')
+            .help("Filter objects based on name. E.g. 'foo', 'foo.*', '.*bar'"),
+        Arg::new("size")
+            .long("size")
+            .short('s')
+            .num_args(1)
+            .allow_hyphen_values(true)
+            .help(
+                "Filter objects based on size. E.g. '-1K', '+4M', '+1G', \
+                 '-1G', '5G', '1G-2G'",
+            ),
+        Arg::new("mtime")
+            .long("mtime")
+            .short('t')
+            .num_args(1)
+            .allow_hyphen_values(true)
+            .help(
+                "Filter objects based on the time offset. E.g. '-60s', \
+                 '+5m', '-1h', '+2D', '-3W', '+1M', '-1Y'",
+            ),
+    ]
+}
+
+pub fn parse_filter_args(
+    matches: &clap::ArgMatches,
+) -> Result<Option<FileObjectFilter>, String> {
+    let filter_name =
+        matches.get_one::<String>("name").map(ToString::to_string);
+    let filter_size =
+        matches.get_one::<String>("size").map(ToString::to_string);
+    let filter_mtime =
+        matches.get_one::<String>("mtime").map(ToString::to_string);
+
+    match (&filter_name, &filter_size, &filter_mtime) {
+        (None, None, None) => Ok(None),
+        _ => FileObjectFilter::new(
+            filter_name.as_deref(),
+            filter_size.as_deref(),
+            filter_mtime.as_deref(),
+        )
+        .map(Some),
+    }
+}
@@ -0,0 +1,173 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use lumni::{
+    EnvironmentConfig, FileObject, FileObjectFilter, LakestreamError,
+    ObjectStoreHandler, ParsedUri, TableCallback, TableColumnValue, TableRow,
+};
+
+// An object found while listing a source URI
+#[derive(Debug, Clone)]
+pub struct ListedObject {
+    pub uri: String,          // full URI of the object
+    pub relative_key: String, // key relative to the listed URI
+    pub size: u64,
+    pub modified: Option<u64>,
+    pub checksum: Option<String>, // ETag on S3, MD5 digest on localfs
+}
+
+// a listed file, with its checksum when selected
+type CollectedObject = (FileObject, Option<String>);
+
+// Collects the rows of a listing, skipping (virtual) directories
+struct CollectCallback {
+    file_objects: Arc<Mutex<Vec<CollectedObject>>>,
+}
+
+impl TableCallback for CollectCallback {
+    fn on_row_add(&self, row: &mut TableRow) {
+        let mut name = None;
+        let mut size = 0;
+        let mut modified = None;
+        let mut checksum = None;
+
+        for (column, value) in row.data() {
+            match (column.as_str(), value) {
+                ("name", TableColumnValue::StringColumn(val)) => {
+                    name = Some(val.clone())
+                }
+                ("size", TableColumnValue::Uint64Column(val)) => size = *val,
+                ("modified", TableColumnValue::OptionalUint64Column(val)) => {
+                    modified = *val
+                }
+                (
+                    "etag" | "md5",
+                    TableColumnValue::OptionalStringColumn(Some(val)),
+                ) => checksum = Some(val.clone()),
+                _ => {}
+            }
+        }
+
+        // directories and common prefixes are listed without a mtime
+        if let (Some(name), Some(_)) = (&name, modified) {
+            if !name.ends_with('/') {
+                self.file_objects.lock().unwrap().push((
+                    FileObject::new(name.clone(), size, modified, None),
+                    checksum,
+                ));
+            }
+        }
+    }
+}
+
+pub async fn list_source_objects(
+    handler: &ObjectStoreHandler,
+    uri: &str,
+    config: &EnvironmentConfig,
+    recursive: bool,
+    filter: &Option<FileObjectFilter>,
+    with_checksum: bool,
+) -> Result<Vec<ListedObject>, LakestreamError> {
+    let file_objects = Arc::new(Mutex::new(Vec::new()));
+    let callback = Arc::new(CollectCallback {
+        file_objects: file_objects.clone(),
+    });
+
+    let parsed_uri = ParsedUri::from_uri(uri, true);
+    let is_s3 = parsed_uri.scheme.as_deref() == Some("s3");
+
+    // local files are only read to compute the digest when asked for
+    let selected_columns = match (with_checksum, is_s3) {
+        (false, _) => None,
+        (true, true) => Some(vec!["name", "size", "modified", "etag"]),
+        (true, false) => Some(vec!["name", "size", "modified", "md5"]),
+    };
+
+    handler
+        .list_objects(
+            uri,
+            config,
+            selected_columns,
+            recursive,
+            None,
+            filter,
+            Some(callback),
+        )
+        .await?;
+
+    let bucket = parsed_uri.bucket.unwrap_or_else(|| ".".to_string());
+    let prefix = parsed_uri.path.unwrap_or_default();
+
+    let file_objects =
+        file_objects.lock().unwrap().drain(..).collect::<Vec<_>>();
+    let objects = file_objects
+        .into_iter()
+        .map(|(file_object, checksum)| {
+            let name = file_object.name().to_string();
+            let size = file_object.size();
+            let modified = file_object.modified();
+            if is_s3 {
+                // S3 lists keys relative to the bucket
+                ListedObject {
+                    uri: format!("s3://{}/{}", bucket, name),
+                    relative_key: name
+                        .strip_prefix(&prefix)
+                        .unwrap_or(&name)
+                        .to_string(),
+                    size,
+                    modified,
+                    checksum,
+                }
+            } else {
+                // localfs lists paths, which are valid URIs as-is
+                let base_path = Path::new(&bucket).join(&prefix);
+                let relative_key = Path::new(&name)
+                    .strip_prefix(&base_path)
+                    .map(|path| path.to_string_lossy().to_string())
+                    .unwrap_or_else(|_| name.clone());
+                ListedObject {
+                    uri: name,
+                    relative_key,
+                    size,
+                    modified,
+                    checksum,
+                }
+            }
+        })
+        .collect();
+    Ok(objects)
+}
+
+// a single object (given without --recursive) is checked against the
+// filter by its metadata
+pub async fn object_matches(
+    handler: &ObjectStoreHandler,
+    uri: &str,
+    config: &EnvironmentConfig,
+    filter: &Option<FileObjectFilter>,
+) -> Result<bool, LakestreamError> {
+    let metadata = handler.head_object(uri, config).await?;
+    let file_object = FileObject::new(
+        uri.to_string(),
+        metadata.size(),
+        metadata.last_modified(),
+        None,
+    );
+    Ok(filter
+        .as_ref()
+        .is_none_or(|filter| filter.matches(&file_object)))
+}
+
+// join a (directory) URI and a relative key
+pub fn join_uri(base_uri: &str, relative_key: &str) -> String {
+    format!("{}/{}", base_uri.trim_end_matches('/'), relative_key)
+}
+
+// path of a local URI, which is given either with or without scheme
+pub fn local_path(uri: &str) -> Option<&str> {
+    match uri.strip_prefix("localfs://") {
+        Some(path) => Some(path),
+        None if !uri.contains("://") => Some(uri),
+        None => None,
+    }
+}
@@ -1,5 +1,6 @@
 use clap::{Arg, ArgAction, Command};
 
+use super::filters::filter_args;
 pub use super::ls_handler::handle_ls;
 
 pub fn ls_subcommand() -> Command {
@@ -11,33 +12,7 @@ pub fn ls_subcommand() -> Command {
                 .default_value(".") // default to current directory
                 .help("URI to list objects from. E.g. s3://bucket-name/"),
         )
-        .arg(
-            Arg::new("name").long("name").short('n').help(
-                "Filter objects based on name. E.g. 'foo', 'foo.*', '.*bar'",
-            ),
-        )
-        .arg(
-            Arg::new("size")
-                .long("size")
-                .short('s')
-                .num_args(1)
-                .allow_hyphen_values(true)
-                .help(
-                    "Filter objects based on size. E.g. '-1K', '+4M', '+1G', \
-                     '-1G', '5G', '1G-2G'",
-                ),
-        )
-        .arg(
-            Arg::new("mtime")
-                .long("mtime")
-                .short('t')
-                .num_args(1)
-                .allow_hyphen_values(true)
-                .help(
-                    "Filter objects based on the time offset. E.g. '-60s', \
-                     '+5m', '-1h', '+2D', '-3W', '+1M', '-1Y'",
-                ),
-        )
+        .args(filter_args())
         .arg(
             Arg::new("recursive")
                 .long("recursive")
@@ -50,6 +25,44 @@ pub fn ls_subcommand() -> Command {
                 .long("max-files")
                 .short('m')
                 .default_value("1000")
-                .help("Maximum number of files to list"),
+                .help("Maximum number of files to list, 0 for no limit"),
+        )
+        .arg(
+            Arg::new("all")
+                .long("all")
+                .short('a')
+                .action(ArgAction::SetTrue)
+                .conflicts_with("max_files")
+                .help("List all files, same as --max-files 0"),
+        )
+        .arg(
+            Arg::new("concurrency")
+                .long("concurrency")
+                .short('c')
+                .value_parser(clap::value_parser!(u32).range(1..))
+                .help(
+                    "Number of (virtual) subdirectories to list in parallel. \
+                     A recursive S3 listing then walks the subdirectories \
+                     instead of listing all keys in one pass",
+                ),
+        )
+        .arg(Arg::new("start_after").long("start-after").help(
+            "List keys after this key. On Local Filesystem keys are \
+                     relative to the URI",
+        ))
+        .arg(
+            Arg::new("start_token")
+                .long("start-token")
+                .conflicts_with("start_after")
+                .help("Continue a listing from a printed next-token"),
+        )
+        .arg(
+            Arg::new("page_size")
+                .long("page-size")
+                .value_parser(clap::value_parser!(u32).range(1..=1000))
+                .help(
+                    "List in pages of this size, printing a next-token to \
+                     stderr after each page to resume from",
+                ),
         )
 }
@@ -2,10 +2,13 @@ use std::sync::Arc;
 
 use log::{debug, error};
 use lumni::{
-    EnvironmentConfig, FileObjectFilter, LakestreamError, ObjectStoreHandler,
-    TableCallback, TableRow,
+    EnvironmentConfig, FileObjectFilter, FileObjectTable, LakestreamError,
+    ListStart, ObjectStoreHandler, Table, TableCallback, TableRow,
+    AWS_MAX_LIST_OBJECTS,
 };
 
+use super::filters::parse_filter_args;
+
 pub async fn handle_ls(
     ls_matches: &clap::ArgMatches,
     config: &mut EnvironmentConfig,
@@ -13,8 +16,34 @@ pub async fn handle_ls(
     let (uri, recursive, max_files, filter) =
         prepare_handle_ls_arguments(ls_matches);
 
-    let handler = ObjectStoreHandler::new(None);
+    if let Some(concurrency) = ls_matches.get_one::<u32>("concurrency") {
+        config.set("S3_LIST_CONCURRENCY".to_string(), concurrency.to_string());
+    }
+
+    let start = if let Some(token) = ls_matches.get_one::<String>("start_token")
+    {
+        Some(ListStart::Token(token.to_string()))
+    } else {
+        ls_matches
+            .get_one::<String>("start_after")
+            .map(|key| ListStart::After(key.to_string()))
+    };
+    let page_size = ls_matches.get_one::<u32>("page_size").copied();
+
+    if start.is_some() || page_size.is_some() {
+        let page_size = page_size.unwrap_or(AWS_MAX_LIST_OBJECTS);
+        if let Err(err) = list_pages(
+            &uri, config, recursive, max_files, page_size, start, &filter,
+        )
+        .await
+        {
+            error!("Error listing objects: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
 
+    let handler = ObjectStoreHandler::new(None);
     let callback = Arc::new(PrintCallback);
 
     match handler
@@ -23,7 +52,7 @@ pub async fn handle_ls(
             config,
             None, // functions as "*", prints all columns
             recursive,
-            Some(max_files),
+            max_files,
             &filter,
             Some(callback),
         )
@@ -43,45 +72,80 @@ pub async fn handle_ls(
     }
 }
 
+// list page by page, so that an interrupted listing can be resumed from
+// the last printed next-token
+async fn list_pages(
+    uri: &str,
+    config: &EnvironmentConfig,
+    recursive: bool,
+    max_files: Option<u32>,
+    page_size: u32,
+    mut start: Option<ListStart>,
+    filter: &Option<FileObjectFilter>,
+) -> Result<(), LakestreamError> {
+    let handler = ObjectStoreHandler::new(None);
+    let mut table = FileObjectTable::new(&None);
+    table.set_callback(Arc::new(PrintCallback));
+    let mut remaining = max_files;
+
+    loop {
+        let page_max = remaining.map_or(page_size, |max| max.min(page_size));
+        let page = handler
+            .list_page(
+                uri,
+                config,
+                recursive,
+                Some(page_max),
+                start.as_ref(),
+                filter,
+            )
+            .await?;
+        let (file_objects, next_token) = page.into_parts();
+        remaining =
+            remaining.map(|max| max.saturating_sub(file_objects.len() as u32));
+        table
+            .add_file_objects(file_objects)
+            .await
+            .map_err(LakestreamError::InternalError)?;
+
+        match next_token {
+            Some(token) => {
+                eprintln!("next-token: {}", token);
+                if remaining == Some(0) {
+                    return Ok(());
+                }
+                start = Some(ListStart::Token(token));
+            }
+            None => return Ok(()),
+        }
+    }
+}
+
 fn prepare_handle_ls_arguments(
     ls_matches: &clap::ArgMatches,
-) -> (String, bool, u32, Option<FileObjectFilter>) {
+) -> (String, bool, Option<u32>, Option<FileObjectFilter>) {
     let recursive = *ls_matches.get_one::<bool>("recursive").unwrap_or(&false);
     let uri = ls_matches.get_one::<String>("uri").unwrap().to_string();
 
-    let filter_name = ls_matches
-        .get_one::<String>("name")
-        .map(ToString::to_string);
-    let filter_size = ls_matches
-        .get_one::<String>("size")
-        .map(ToString::to_string);
-    let filter_mtime = ls_matches
-        .get_one::<String>("mtime")
-        .map(ToString::to_string);
-
-    let filter = match (&filter_name, &filter_size, &filter_mtime) {
-        (None, None, None) => None,
-        _ => {
-            let filter_result = FileObjectFilter::new(
-                filter_name.as_deref(),
-                filter_size.as_deref(),
-                filter_mtime.as_deref(),
-            );
-            match filter_result {
-                Ok(filter) => Some(filter),
-                Err(err) => {
-                    error!("Error creating filter: {}", err);
-                    std::process::exit(1);
-                }
-            }
+    let filter = match parse_filter_args(ls_matches) {
+        Ok(filter) => filter,
+        Err(err) => {
+            error!("Error creating filter: {}", err);
+            std::process::exit(1);
         }
     };
 
-    let max_files = ls_matches
-        .get_one::<String>("max_files")
-        .unwrap()
-        .parse::<u32>()
-        .expect("Invalid value for max_files");
+    let max_files = if *ls_matches.get_one::<bool>("all").unwrap_or(&false) {
+        None
+    } else {
+        let max_files = ls_matches
+            .get_one::<String>("max_files")
+            .unwrap()
+            .parse::<u32>()
+            .expect("Invalid value for max_files");
+        // 0 means no limit
+        Some(max_files).filter(|max_files| *max_files > 0)
+    };
 
     (uri, recursive, max_files, filter)
 }
@@ -93,3 +157,24 @@ impl TableCallback for PrintCallback {
         row.print();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::ls::ls_subcommand;
+    use super::*;
+
+    #[test]
+    fn test_max_files() {
+        let max_files = |args: &[&str]| {
+            let matches = ls_subcommand()
+                .try_get_matches_from([&["ls", "s3://bucket/"], args].concat())
+                .unwrap();
+            prepare_handle_ls_arguments(&matches).2
+        };
+        assert_eq!(max_files(&[]), Some(1000));
+        assert_eq!(max_files(&["--max-files", "5"]), Some(5));
+        // 0 means no limit, same as --all
+        assert_eq!(max_files(&["--max-files", "0"]), None);
+        assert_eq!(max_files(&["--all"]), None);
+    }
+}
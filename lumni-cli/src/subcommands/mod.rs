@@ -1,8 +1,16 @@
 pub mod cp;
 mod cp_handler;
+mod filters;
+mod listing;
 pub mod ls;
 mod ls_handler;
+pub mod presign;
+mod presign_handler;
 pub mod query;
 mod query_handler;
 pub mod request;
 mod request_handler;
+pub mod rm;
+mod rm_handler;
+pub mod sync;
+mod sync_handler;
@@ -0,0 +1,37 @@
+use clap::{Arg, Command};
+
+pub use super::presign_handler::handle_presign;
+use super::presign_handler::parse_expires;
+
+pub fn presign_subcommand() -> Command {
+    Command::new("presign")
+        .about(
+            "Create a presigned URL, to download or upload an object \
+             without credentials",
+        )
+        .arg(
+            Arg::new("uri")
+                .index(1)
+                .required(true)
+                .help("URI of the object, e.g. s3://bucket/key"),
+        )
+        .arg(
+            Arg::new("expires")
+                .long("expires")
+                .short('e')
+                .default_value("1h")
+                .value_parser(parse_expires)
+                .help(
+                    "Time until the URL expires, at most 7 days. E.g. '900', \
+                     '15m', '1h', '7D'",
+                ),
+        )
+        .arg(
+            Arg::new("method")
+                .long("method")
+                .short('m')
+                .default_value("GET")
+                .value_parser(["GET", "PUT"])
+                .help("Download (GET) or upload (PUT) the object"),
+        )
+}
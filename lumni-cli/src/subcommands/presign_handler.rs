@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use log::error;
+use lumni::{
+    parse_duration, EnvironmentConfig, ObjectStoreHandler,
+    S3_PRESIGN_MAX_EXPIRES_SECS,
+};
+
+pub async fn handle_presign(
+    matches: &clap::ArgMatches,
+    config: &mut EnvironmentConfig,
+) {
+    let uri = matches.get_one::<String>("uri").unwrap();
+    let method = matches.get_one::<String>("method").unwrap();
+    let expires_in = *matches.get_one::<Duration>("expires").unwrap();
+
+    let handler = ObjectStoreHandler::new(None);
+    match handler.presign_url(uri, config, method, expires_in).await {
+        Ok(url) => println!("{}", url),
+        Err(err) => {
+            error!("Error creating presigned URL: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+// parser of --expires, in the syntax of the --mtime filter
+pub fn parse_expires(expires: &str) -> Result<Duration, String> {
+    let expires_in = parse_duration(expires)?;
+    if expires_in.as_secs() > S3_PRESIGN_MAX_EXPIRES_SECS {
+        return Err(format!(
+            "Expiration of a presigned url can be at most 7 days, got {}",
+            expires
+        ));
+    }
+    Ok(expires_in)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::presign::presign_subcommand;
+    use super::*;
+
+    fn expires(args: &[&str]) -> Result<Duration, clap::Error> {
+        presign_subcommand()
+            .try_get_matches_from(
+                ["presign", "s3://bucket/key"].iter().chain(args),
+            )
+            .map(|matches| *matches.get_one::<Duration>("expires").unwrap())
+    }
+
+    #[test]
+    fn test_expires() {
+        assert_eq!(expires(&[]).unwrap(), Duration::from_secs(3600));
+        let cases = [
+            ("900", 900),
+            ("15m", 900),
+            ("1D8h", 32 * 3600),
+            ("7D", S3_PRESIGN_MAX_EXPIRES_SECS),
+        ];
+        for (input, seconds) in cases {
+            assert_eq!(
+                expires(&["--expires", input]).unwrap(),
+                Duration::from_secs(seconds)
+            );
+        }
+
+        // invalid, or units of the --mtime syntax only
+        for input in ["", "0", "1.5h", "h", "-1h", "1x", "7d", "2H"] {
+            let arg = format!("--expires={}", input);
+            let err = expires(&[&arg]).unwrap_err();
+            assert_eq!(err.kind(), clap::error::ErrorKind::ValueValidation);
+        }
+        // out of range
+        for input in ["8D", "604801", "1Y", "10050Y", "99999999999999999999"] {
+            let err = expires(&["-e", input]).unwrap_err();
+            assert_eq!(err.kind(), clap::error::ErrorKind::ValueValidation);
+        }
+    }
+}
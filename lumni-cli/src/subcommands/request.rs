@@ -1,4 +1,4 @@
-use clap::{Arg, Command};
+use clap::{Arg, ArgAction, Command};
 
 pub use super::request_handler::handle_request;
 
@@ -9,9 +9,9 @@ pub fn request_subcommand() -> Command {
         .arg(
             Arg::new("method")
                 .index(1)
-                .value_parser(["GET"])
+                .value_parser(["GET", "HEAD", "DELETE"])
                 .required(true)
-                .help("HTTP verb for the request (GET)"),
+                .help("HTTP verb for the request (GET, HEAD, DELETE)"),
         )
         .arg(
             Arg::new("uri")
@@ -19,4 +19,27 @@ pub fn request_subcommand() -> Command {
                 .required(true)
                 .help("File for the HTTP request"),
         )
+        .arg(
+            Arg::new("range")
+                .long("range")
+                .allow_hyphen_values(true)
+                .help(
+                    "Byte range to get (inclusive). E.g. '0-1023', '1024-', \
+                     '-512' (last 512 bytes)",
+                ),
+        )
+        .arg(
+            Arg::new("remove-empty-dirs")
+                .long("remove-empty-dirs")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Local filesystem only: remove the parent directory of \
+                     the object when a DELETE leaves it empty",
+                ),
+        )
+        .arg(Arg::new("output").long("output").short('o').help(
+            "Write the object of a GET request to this file instead \
+                     of stdout. The file is only written when the request \
+                     succeeds, including checksum verification",
+        ))
 }
@@ -1,8 +1,11 @@
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{self, Write};
 use std::sync::{Arc, Mutex};
 
-use lumni::{BinaryCallbackWrapper, EnvironmentConfig, ObjectStoreHandler};
+use lumni::formatters::time_human_readable;
+use lumni::{
+    BinaryCallbackWrapper, ByteRange, EnvironmentConfig, ObjectStoreHandler,
+};
 
 pub async fn handle_request(
     matches: &clap::ArgMatches,
@@ -10,24 +13,37 @@ pub async fn handle_request(
 ) {
     let method = matches.get_one::<String>("method").unwrap();
     let uri = matches.get_one::<String>("uri").unwrap();
+    let range = match matches.get_one::<String>("range") {
+        Some(range) => match range.parse::<ByteRange>() {
+            Ok(range) => Some(range),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
 
-    // TODO: implement output file option vs default stdout
-    // writing to an output file works internally, but need
-    // -o output_file as a main cli option applicable to all commands
-    let output_file = None;
+    let output_file = matches.get_one::<String>("output").map(String::as_str);
+    if *matches
+        .get_one::<bool>("remove-empty-dirs")
+        .unwrap_or(&false)
+    {
+        config.set("LOCALFS_REMOVE_EMPTY_DIRS".to_string(), "true".to_string());
+    }
 
     match method.as_str() {
         "GET" => {
-            handle_get_request(uri, config, output_file).await;
+            handle_get_request(uri, config, range.as_ref(), output_file).await;
         }
         "PUT" => {
             println!("PUT request not yet implemented");
         }
         "DELETE" => {
-            println!("DELETE request not yet implemented");
+            handle_delete_request(uri, config).await;
         }
         "HEAD" => {
-            println!("HEAD request not yet implemented");
+            handle_head_request(uri, config).await;
         }
         "LIST" => {
             println!("LIST request not yet implemented");
@@ -41,32 +57,105 @@ pub async fn handle_request(
 async fn handle_get_request(
     uri: &str,
     config: &EnvironmentConfig,
+    range: Option<&ByteRange>,
     output_path: Option<&str>,
 ) {
     let handler = ObjectStoreHandler::new(None);
 
-    let callback = if let Some(output_path) = output_path {
-        // write to file
-        let file = Arc::new(Mutex::new(File::create(output_path).unwrap()));
-        Some(BinaryCallbackWrapper::create_async(move |data: Vec<u8>| {
-            let mut file = file.lock().unwrap();
-            if let Err(e) = file.write_all(&data) {
-                eprintln!("Error writing to file: {:?}", e);
+    let output_path = match output_path {
+        Some(output_path) => output_path,
+        None => {
+            // written as it arrives, a failed checksum verification is
+            // only reported after all data was written
+            let callback =
+                BinaryCallbackWrapper::create_async(move |data: Vec<u8>| {
+                    let mut stdout = io::stdout();
+                    if let Err(e) = stdout.write_all(&data) {
+                        eprintln!("Error writing to stdout: {:?}", e);
+                    }
+                    async {}
+                });
+            if let Err(err) =
+                handler.get_object(uri, config, range, Some(callback)).await
+            {
+                eprintln!("Error: {}", err);
             }
-            async {}
-        }))
-    } else {
-        // write to stdout
-        Some(BinaryCallbackWrapper::create_async(move |data: Vec<u8>| {
-            let mut stdout = io::stdout();
-            if let Err(e) = stdout.write_all(&data) {
-                eprintln!("Error writing to stdout: {:?}", e);
+            return;
+        }
+    };
+
+    // data is written to a temporary file, which replaces the output file
+    // only after the object was received (and verified) completely
+    let partial_path = format!("{}.partial", output_path);
+    let file = match File::create(&partial_path) {
+        Ok(file) => Arc::new(Mutex::new(file)),
+        Err(err) => {
+            eprintln!("Error creating {}: {}", partial_path, err);
+            std::process::exit(1);
+        }
+    };
+    let write_error = Arc::new(Mutex::new(None));
+    let callback = {
+        let write_error = write_error.clone();
+        BinaryCallbackWrapper::create_async(move |data: Vec<u8>| {
+            if let Err(err) = file.lock().unwrap().write_all(&data) {
+                write_error.lock().unwrap().get_or_insert(err);
             }
             async {}
-        }))
+        })
+    };
+
+    let result = handler.get_object(uri, config, range, Some(callback)).await;
+    let write_error = write_error.lock().unwrap().take();
+    let result = match (result, write_error) {
+        (Ok(_), None) => fs::rename(&partial_path, output_path)
+            .map_err(|err| format!("renaming {}: {}", partial_path, err)),
+        (Ok(_), Some(err)) => Err(format!("writing {}: {}", partial_path, err)),
+        (Err(err), _) => Err(err.to_string()),
     };
+    if let Err(err) = result {
+        let _ = fs::remove_file(&partial_path);
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+async fn handle_head_request(uri: &str, config: &EnvironmentConfig) {
+    let handler = ObjectStoreHandler::new(None);
+
+    match handler.head_object(uri, config).await {
+        Ok(metadata) => {
+            println!("size: {}", metadata.size());
+            if let Some(last_modified) = metadata.last_modified() {
+                println!(
+                    "last-modified: {}",
+                    time_human_readable(last_modified)
+                );
+            }
+            if let Some(etag) = metadata.etag() {
+                println!("etag: {}", etag);
+            }
+            if let Some(content_type) = metadata.content_type() {
+                println!("content-type: {}", content_type);
+            }
+            let mut user_metadata =
+                metadata.user_metadata().iter().collect::<Vec<_>>();
+            user_metadata.sort();
+            for (key, value) in user_metadata {
+                println!("x-amz-meta-{}: {}", key, value);
+            }
+        }
+        Err(err) => {
+            eprintln!("Error: {}", err);
+        }
+    }
+}
+
+async fn handle_delete_request(uri: &str, config: &EnvironmentConfig) {
+    let handler = ObjectStoreHandler::new(None);
 
-    if let Err(err) = handler.get_object(uri, config, callback).await {
-        eprintln!("Error: {:?}", err);
+    match handler.delete_object(uri, config).await {
+        Ok(_) => println!("delete: {}", uri),
+        Err(err) => eprintln!("Error: {}", err),
     }
 }
@@ -0,0 +1,53 @@
+use clap::{Arg, ArgAction, Command};
+
+use super::filters::filter_args;
+pub use super::rm_handler::handle_rm;
+
+pub fn rm_subcommand() -> Command {
+    Command::new("rm")
+        .about(
+            "Remove objects. Lists what would be removed, unless --force is \
+             given",
+        )
+        .arg(
+            Arg::new("uri")
+                .index(1)
+                .required(true)
+                .help("URI of the object, or prefix with --recursive"),
+        )
+        .arg(
+            Arg::new("recursive")
+                .long("recursive")
+                .short('r')
+                .action(ArgAction::SetTrue)
+                .help("Remove all objects under the prefix recursively"),
+        )
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .short('f')
+                .action(ArgAction::SetTrue)
+                .help("Remove the objects instead of listing them"),
+        )
+        .arg(
+            Arg::new("yes")
+                .long("yes")
+                .short('y')
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Do not ask for confirmation when removing more objects \
+                     than RM_CONFIRM_THRESHOLD (default 100)",
+                ),
+        )
+        .arg(
+            Arg::new("remove-empty-dirs")
+                .long("remove-empty-dirs")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Local filesystem only: remove the directories left \
+                     empty, below the given URI with --recursive, else \
+                     the parent directory of the object",
+                ),
+        )
+        .args(filter_args())
+}
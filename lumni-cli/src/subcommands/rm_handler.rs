@@ -0,0 +1,272 @@
+use std::env;
+use std::io::{self, BufRead, Write};
+
+use log::error;
+use lumni::{
+    EnvironmentConfig, FileObjectFilter, LakestreamError, ObjectStoreHandler,
+};
+
+use super::filters::parse_filter_args;
+use super::listing::{join_uri, list_source_objects, object_matches};
+
+// ask for confirmation when removing more objects than this, can be
+// overridden via RM_CONFIRM_THRESHOLD
+const CONFIRM_THRESHOLD: usize = 100;
+
+#[derive(Debug, PartialEq, Eq)]
+enum RmAction {
+    // list what would be removed (dry run)
+    List,
+    Confirm,
+    Remove,
+}
+
+pub async fn handle_rm(
+    matches: &clap::ArgMatches,
+    config: &mut EnvironmentConfig,
+) {
+    let uri = matches.get_one::<String>("uri").unwrap();
+    let recursive = *matches.get_one::<bool>("recursive").unwrap_or(&false);
+    let force = *matches.get_one::<bool>("force").unwrap_or(&false);
+    let yes = *matches.get_one::<bool>("yes").unwrap_or(&false);
+    let remove_empty_dirs = *matches
+        .get_one::<bool>("remove-empty-dirs")
+        .unwrap_or(&false);
+    if remove_empty_dirs {
+        config.set("LOCALFS_REMOVE_EMPTY_DIRS".to_string(), "true".to_string());
+    }
+
+    let threshold = match confirm_threshold(config) {
+        Ok(threshold) => threshold,
+        Err(err) => {
+            error!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    let filter = match parse_filter_args(matches) {
+        Ok(filter) => filter,
+        Err(err) => {
+            error!("Error creating filter: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let handler = ObjectStoreHandler::new(None);
+
+    let (base_uri, keys) =
+        match select_keys(&handler, uri, config, recursive, &filter).await {
+            Ok(selected) => selected,
+            Err(err) => {
+                error!("Error selecting objects to remove: {}", err);
+                std::process::exit(1);
+            }
+        };
+
+    match rm_action(keys.len(), force, yes, threshold) {
+        RmAction::List => {
+            for key in &keys {
+                println!("would remove: {}", join_uri(&base_uri, key));
+            }
+            println!(
+                "{} object(s) would be removed, use --force to remove",
+                keys.len()
+            );
+            return;
+        }
+        RmAction::Confirm if !confirm(keys.len()) => {
+            println!("Aborted");
+            return;
+        }
+        RmAction::Confirm | RmAction::Remove => {}
+    }
+
+    if remove_empty_dirs && !recursive {
+        // a single object is removed via its full URI, so that its parent
+        // directory is removed once empty (delete_objects keeps it)
+        for key in &keys {
+            let object_uri = join_uri(&base_uri, key);
+            if let Err(err) = handler.delete_object(&object_uri, config).await {
+                error!("Error removing {}: {}", object_uri, err);
+                std::process::exit(1);
+            }
+            println!("remove: {}", object_uri);
+        }
+        println!("Removed {} object(s)", keys.len());
+        return;
+    }
+
+    match handler.delete_objects(&base_uri, config, &keys).await {
+        Ok(results) => {
+            let mut failed = 0;
+            for result in &results {
+                let object_uri = join_uri(&base_uri, result.key());
+                match result.error() {
+                    None => println!("remove: {}", object_uri),
+                    Some(err) => {
+                        failed += 1;
+                        eprintln!("Error removing {}: {}", object_uri, err);
+                    }
+                }
+            }
+            println!("Removed {} object(s)", results.len() - failed);
+            if failed > 0 {
+                std::process::exit(1);
+            }
+        }
+        Err(err) => {
+            error!("Error removing objects: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+// objects are only removed with --force, and removing more than the
+// threshold needs confirmation unless --yes is given
+fn rm_action(
+    count: usize,
+    force: bool,
+    yes: bool,
+    threshold: usize,
+) -> RmAction {
+    if !force {
+        RmAction::List
+    } else if count > threshold && !yes {
+        RmAction::Confirm
+    } else {
+        RmAction::Remove
+    }
+}
+
+fn confirm_threshold(config: &EnvironmentConfig) -> Result<usize, String> {
+    let value = config
+        .get("RM_CONFIRM_THRESHOLD")
+        .cloned()
+        .or_else(|| env::var("RM_CONFIRM_THRESHOLD").ok());
+    match value {
+        Some(value) => value.trim().parse::<usize>().map_err(|_| {
+            format!(
+                "Invalid RM_CONFIRM_THRESHOLD: {}, expected a number",
+                value
+            )
+        }),
+        None => Ok(CONFIRM_THRESHOLD),
+    }
+}
+
+// the objects to remove, as a base URI and keys relative to it. A single
+// object (without --recursive) is only selected when it matches the filter
+async fn select_keys(
+    handler: &ObjectStoreHandler,
+    uri: &str,
+    config: &EnvironmentConfig,
+    recursive: bool,
+    filter: &Option<FileObjectFilter>,
+) -> Result<(String, Vec<String>), LakestreamError> {
+    if recursive {
+        let objects =
+            list_source_objects(handler, uri, config, true, filter, false)
+                .await?;
+        let keys = objects
+            .into_iter()
+            .map(|object| object.relative_key)
+            .collect::<Vec<_>>();
+        return Ok((uri.to_string(), keys));
+    }
+
+    let is_match = object_matches(handler, uri, config, filter).await?;
+    let (base_uri, key) = match uri.rsplit_once('/') {
+        Some(("", key)) => ("/", key),
+        Some((base_uri, key)) => (base_uri, key),
+        None => (".", uri),
+    };
+    let keys = if is_match {
+        vec![key.to_string()]
+    } else {
+        Vec::new()
+    };
+    Ok((base_uri.to_string(), keys))
+}
+
+fn confirm(count: usize) -> bool {
+    print!("Remove {} objects? [y/N] ", count);
+    io::stdout().flush().ok();
+
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_rm_action() {
+        // a dry run unless --force is given, regardless of --yes
+        assert_eq!(rm_action(1, false, false, 100), RmAction::List);
+        assert_eq!(rm_action(500, false, true, 100), RmAction::List);
+
+        assert_eq!(rm_action(100, true, false, 100), RmAction::Remove);
+        assert_eq!(rm_action(101, true, false, 100), RmAction::Confirm);
+        assert_eq!(rm_action(101, true, true, 100), RmAction::Remove);
+        assert_eq!(rm_action(1, true, false, 0), RmAction::Confirm);
+    }
+
+    #[test]
+    fn test_confirm_threshold() {
+        let config = |value: &str| {
+            EnvironmentConfig::new(HashMap::from([(
+                "RM_CONFIRM_THRESHOLD".to_string(),
+                value.to_string(),
+            )]))
+        };
+        assert_eq!(confirm_threshold(&config("5")), Ok(5));
+        assert!(confirm_threshold(&config("many")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_select_keys() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let base = temp_dir.path().to_string_lossy().to_string();
+        fs::create_dir(temp_dir.path().join("logs")).unwrap();
+        for key in ["a.txt", "logs/b.log", "logs/c.txt"] {
+            fs::write(temp_dir.path().join(key), b"data").unwrap();
+        }
+        let handler = ObjectStoreHandler::new(None);
+        let config = EnvironmentConfig::new(HashMap::new());
+        let uri = format!("localfs://{}", base);
+
+        let (base_uri, mut keys) =
+            select_keys(&handler, &uri, &config, true, &None)
+                .await
+                .unwrap();
+        keys.sort();
+        assert_eq!(base_uri, uri);
+        assert_eq!(keys, ["a.txt", "logs/b.log", "logs/c.txt"]);
+
+        let filter = FileObjectFilter::new(Some(r"\.log$"), None, None).ok();
+        let (_, keys) = select_keys(&handler, &uri, &config, true, &filter)
+            .await
+            .unwrap();
+        assert_eq!(keys, ["logs/b.log"]);
+
+        // a single object, relative to its directory
+        let object_uri = format!("{}/logs/c.txt", uri);
+        let (base_uri, keys) =
+            select_keys(&handler, &object_uri, &config, false, &None)
+                .await
+                .unwrap();
+        assert_eq!(base_uri, format!("{}/logs", uri));
+        assert_eq!(keys, ["c.txt"]);
+        let (_, keys) =
+            select_keys(&handler, &object_uri, &config, false, &filter)
+                .await
+                .unwrap();
+        assert!(keys.is_empty());
+    }
+}
@@ -0,0 +1,47 @@
+use clap::{Arg, ArgAction, Command};
+
+use super::filters::filter_args;
+pub use super::sync_handler::handle_sync;
+
+pub fn sync_subcommand() -> Command {
+    Command::new("sync")
+        .about(
+            "Copy new and changed objects from source to target, objects \
+             that are up to date are skipped",
+        )
+        .arg(
+            Arg::new("source")
+                .index(1)
+                .required(true)
+                .help("Source URI to sync objects from"),
+        )
+        .arg(
+            Arg::new("target")
+                .index(2)
+                .required(true)
+                .help("Target URI to sync objects to"),
+        )
+        .arg(
+            Arg::new("delete")
+                .long("delete")
+                .action(ArgAction::SetTrue)
+                .help("Remove objects from the target that are not in source"),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .action(ArgAction::SetTrue)
+                .help("List what would be copied and removed, without changes"),
+        )
+        .arg(
+            Arg::new("checksum")
+                .long("checksum")
+                .short('c')
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Compare objects of the same size by checksum instead of \
+                     modification time",
+                ),
+        )
+        .args(filter_args())
+}
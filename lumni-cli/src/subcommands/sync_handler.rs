@@ -0,0 +1,307 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use log::error;
+use lumni::{
+    EnvironmentConfig, FileObjectFilter, LakestreamError, ObjectStoreHandler,
+};
+
+use super::cp_handler::copy_object;
+use super::filters::parse_filter_args;
+use super::listing::{join_uri, list_source_objects, local_path, ListedObject};
+
+struct SyncOptions {
+    delete: bool,
+    dry_run: bool,
+    checksum: bool,
+}
+
+#[derive(Default)]
+struct SyncSummary {
+    copied: usize,
+    up_to_date: usize,
+    removed: usize,
+    failed: usize,
+}
+
+pub async fn handle_sync(
+    matches: &clap::ArgMatches,
+    config: &mut EnvironmentConfig,
+) {
+    let source = matches.get_one::<String>("source").unwrap();
+    let target = matches.get_one::<String>("target").unwrap();
+    let options = SyncOptions {
+        delete: *matches.get_one::<bool>("delete").unwrap_or(&false),
+        dry_run: *matches.get_one::<bool>("dry-run").unwrap_or(&false),
+        checksum: *matches.get_one::<bool>("checksum").unwrap_or(&false),
+    };
+
+    let filter = match parse_filter_args(matches) {
+        Ok(filter) => filter,
+        Err(err) => {
+            error!("Error creating filter: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let handler = ObjectStoreHandler::new(None);
+
+    match sync(&handler, source, target, config, &filter, &options).await {
+        Ok(summary) => {
+            if options.dry_run {
+                println!(
+                    "{} object(s) would be copied, {} up to date, {} would \
+                     be removed",
+                    summary.copied, summary.up_to_date, summary.removed
+                );
+            } else {
+                println!(
+                    "Copied {} object(s), {} up to date, removed {} object(s)",
+                    summary.copied, summary.up_to_date, summary.removed
+                );
+            }
+            if summary.failed > 0 {
+                eprintln!("{} object(s) failed", summary.failed);
+                std::process::exit(1);
+            }
+        }
+        Err(err) => {
+            error!("Error syncing objects: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+async fn sync(
+    handler: &ObjectStoreHandler,
+    source: &str,
+    target: &str,
+    config: &EnvironmentConfig,
+    filter: &Option<FileObjectFilter>,
+    options: &SyncOptions,
+) -> Result<SyncSummary, LakestreamError> {
+    let source_objects = list_source_objects(
+        handler,
+        source,
+        config,
+        true,
+        filter,
+        options.checksum,
+    )
+    .await?;
+
+    // a local target directory is created by the first copy
+    let target_exists =
+        local_path(target).is_none_or(|path| Path::new(path).exists());
+    let target_objects = if target_exists {
+        list_source_objects(
+            handler,
+            target,
+            config,
+            true,
+            filter,
+            options.checksum,
+        )
+        .await?
+    } else {
+        Vec::new()
+    };
+    let target_objects = target_objects
+        .into_iter()
+        .map(|object| (object.relative_key.clone(), object))
+        .collect::<HashMap<_, _>>();
+
+    let mut summary = SyncSummary::default();
+    for object in &source_objects {
+        let needs_copy = match target_objects.get(&object.relative_key) {
+            Some(existing) => is_changed(object, existing, options.checksum),
+            None => true,
+        };
+        if !needs_copy {
+            summary.up_to_date += 1;
+            continue;
+        }
+
+        let target_uri = join_uri(target, &object.relative_key);
+        if options.dry_run {
+            println!("would copy: {} to {}", object.uri, target_uri);
+        } else if let Err(err) =
+            copy_object(handler, &object.uri, &target_uri, config).await
+        {
+            // continue with the other objects, the failure is reported
+            // in the summary
+            eprintln!("Error copying {}: {}", object.uri, err);
+            summary.failed += 1;
+            continue;
+        }
+        summary.copied += 1;
+    }
+
+    // objects are not removed from a target that is not fully in sync
+    if options.delete && summary.failed > 0 {
+        eprintln!(
+            "Skipping --delete, {} object(s) failed to copy",
+            summary.failed
+        );
+    } else if options.delete {
+        let source_keys = source_objects
+            .iter()
+            .map(|object| object.relative_key.as_str())
+            .collect::<HashSet<_>>();
+        let mut keys = target_objects
+            .into_keys()
+            .filter(|key| !source_keys.contains(key.as_str()))
+            .collect::<Vec<_>>();
+        keys.sort();
+        remove_objects(handler, target, config, &keys, options, &mut summary)
+            .await?;
+    }
+    Ok(summary)
+}
+
+// remove objects from the target that are no longer in the source
+async fn remove_objects(
+    handler: &ObjectStoreHandler,
+    target: &str,
+    config: &EnvironmentConfig,
+    keys: &[String],
+    options: &SyncOptions,
+    summary: &mut SyncSummary,
+) -> Result<(), LakestreamError> {
+    if options.dry_run {
+        for key in keys {
+            println!("would remove: {}", join_uri(target, key));
+        }
+        summary.removed += keys.len();
+        return Ok(());
+    }
+    if keys.is_empty() {
+        return Ok(());
+    }
+
+    let results = handler.delete_objects(target, config, keys).await?;
+    for result in &results {
+        let object_uri = join_uri(target, result.key());
+        match result.error() {
+            None => {
+                println!("remove: {}", object_uri);
+                summary.removed += 1;
+            }
+            Some(err) => {
+                summary.failed += 1;
+                eprintln!("Error removing {}: {}", object_uri, err);
+            }
+        }
+    }
+    Ok(())
+}
+
+// objects of a different size are always copied. Otherwise the checksum
+// decides when it can be compared, and else the modification time
+fn is_changed(
+    source: &ListedObject,
+    target: &ListedObject,
+    compare_checksum: bool,
+) -> bool {
+    if source.size != target.size {
+        return true;
+    }
+    if compare_checksum {
+        if let (Some(source_sum), Some(target_sum)) =
+            (&source.checksum, &target.checksum)
+        {
+            if source_sum.eq_ignore_ascii_case(target_sum) {
+                return false;
+            }
+            // the ETag of a multipart upload is not a digest of the data,
+            // and depends on the part size
+            if !source_sum.contains('-') && !target_sum.contains('-') {
+                return true;
+            }
+        }
+    }
+    // a copy is written after the source was last modified
+    match (source.modified, target.modified) {
+        (Some(source_mtime), Some(target_mtime)) => source_mtime > target_mtime,
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(
+        size: u64,
+        modified: u64,
+        checksum: Option<&str>,
+    ) -> ListedObject {
+        ListedObject {
+            uri: "localfs:///tmp/a".to_string(),
+            relative_key: "a".to_string(),
+            size,
+            modified: Some(modified),
+            checksum: checksum.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_is_changed() {
+        // size differs, even when the target is newer
+        assert!(is_changed(
+            &object(10, 100, None),
+            &object(11, 200, None),
+            false
+        ));
+
+        // same size, decided by the modification time
+        assert!(is_changed(
+            &object(10, 200, None),
+            &object(10, 100, None),
+            false
+        ));
+        assert!(!is_changed(
+            &object(10, 100, None),
+            &object(10, 200, None),
+            false
+        ));
+        assert!(!is_changed(
+            &object(10, 100, None),
+            &object(10, 100, None),
+            false
+        ));
+
+        // equal checksums, a newer source is up to date
+        let md5 = "d41d8cd98f00b204e9800998ecf8427e";
+        assert!(!is_changed(
+            &object(10, 200, Some(md5)),
+            &object(10, 100, Some(&md5.to_uppercase())),
+            true
+        ));
+        // different checksums, an older source is copied
+        assert!(is_changed(
+            &object(10, 100, Some(md5)),
+            &object(10, 200, Some("0cc175b9c0f1b6a831c399e269772661")),
+            true
+        ));
+        // checksums are ignored unless asked for
+        assert!(is_changed(
+            &object(10, 200, Some(md5)),
+            &object(10, 100, Some(md5)),
+            false
+        ));
+
+        // a multipart ETag can not be compared, falls back to mtime
+        let etag = "9b2cf535f27731c974343645a3985328-2";
+        assert!(is_changed(
+            &object(10, 200, Some(md5)),
+            &object(10, 100, Some(etag)),
+            true
+        ));
+        assert!(!is_changed(
+            &object(10, 100, Some(md5)),
+            &object(10, 200, Some(etag)),
+            true
+        ));
+    }
+}
@@ -1,4 +1,7 @@
+use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use log::debug;
@@ -6,11 +9,15 @@ use sqlparser::ast::{Expr, Query, SelectItem, SetExpr, Statement};
 use sqlparser::dialect::GenericDialect;
 use sqlparser::parser::Parser;
 
+use crate::localfs::backend::remove_empty_dirs;
+use crate::s3::backend::{presign_url, S3Bucket};
 use crate::table::object_store::table_from_list_bucket;
 use crate::utils::uri_parse::ParsedUri;
 use crate::{
-    BinaryCallbackWrapper, EnvironmentConfig, FileObjectFilter,
-    LakestreamError, ObjectStore, ObjectStoreTable, Table, TableCallback,
+    BinaryCallbackWrapper, ByteRange, ByteStream, DeleteResult,
+    EnvironmentConfig, FileObjectFilter, FileObjectStream, LakestreamError,
+    ListPage, ListStart, ObjectMetadata, ObjectStore, ObjectStoreTable, Table,
+    TableCallback,
 };
 
 #[derive(Clone)]
@@ -69,6 +76,74 @@ impl ObjectStoreHandler {
         }
     }
 
+    // list a single page of objects, pass the next_token of the returned
+    // page as ListStart::Token to continue with the next page
+    pub async fn list_page(
+        &self,
+        uri: &str,
+        config: &EnvironmentConfig,
+        recursive: bool,
+        max_files: Option<u32>,
+        start: Option<&ListStart>,
+        filter: &Option<FileObjectFilter>,
+    ) -> Result<ListPage, LakestreamError> {
+        let parsed_uri = ParsedUri::from_uri(uri, true);
+
+        if let Some(bucket) = &parsed_uri.bucket {
+            let bucket_uri = if let Some(scheme) = &parsed_uri.scheme {
+                format!("{}://{}", scheme, bucket)
+            } else {
+                format!("localfs://{}", bucket)
+            };
+
+            let object_store = ObjectStore::new(&bucket_uri, config.clone())
+                .map_err(LakestreamError::ConfigError)?;
+            object_store
+                .list_page(
+                    parsed_uri.path.as_deref(),
+                    recursive,
+                    max_files,
+                    start,
+                    filter,
+                )
+                .await
+        } else {
+            Err(LakestreamError::NoBucketInUri(uri.to_string()))
+        }
+    }
+
+    // list objects as a stream, objects are produced while pages are
+    // listed instead of being collected in a table first
+    pub fn list_stream(
+        &self,
+        uri: &str,
+        config: &EnvironmentConfig,
+        recursive: bool,
+        max_files: Option<u32>,
+        filter: &Option<FileObjectFilter>,
+    ) -> Result<FileObjectStream<'static>, LakestreamError> {
+        let parsed_uri = ParsedUri::from_uri(uri, true);
+
+        if let Some(bucket) = &parsed_uri.bucket {
+            let bucket_uri = if let Some(scheme) = &parsed_uri.scheme {
+                format!("{}://{}", scheme, bucket)
+            } else {
+                format!("localfs://{}", bucket)
+            };
+
+            let object_store = ObjectStore::new(&bucket_uri, config.clone())
+                .map_err(LakestreamError::ConfigError)?;
+            Ok(object_store.list_stream(
+                parsed_uri.path.as_deref(),
+                recursive,
+                max_files,
+                filter,
+            ))
+        } else {
+            Err(LakestreamError::NoBucketInUri(uri.to_string()))
+        }
+    }
+
     pub async fn list_buckets(
         &self,
         uri: &str,
@@ -101,6 +176,7 @@ impl ObjectStoreHandler {
         &self,
         uri: &str,
         config: &EnvironmentConfig,
+        range: Option<&ByteRange>,
         callback: Option<BinaryCallbackWrapper>,
     ) -> Result<Option<Vec<u8>>, LakestreamError> {
         let parsed_uri = ParsedUri::from_uri(uri, false);
@@ -117,18 +193,14 @@ impl ObjectStoreHandler {
             let object_store =
                 ObjectStore::new(&bucket_uri, config.clone()).unwrap();
 
-            // NOTE: initial callback implementation for get_object. In future updates the callback
-            // mechanism will be pushed to the underlying object store methods, so we can add
-            // chunking as well for increased performance and ability to handle big files that not
-            // fit in memory
+            // with a callback the object is streamed in chunks, so it does
+            // not have to fit in memory
             if let Some(callback) = callback {
-                let mut data = Vec::new();
-                object_store.get_object(key, &mut data).await?;
-                callback.call(data).await?;
+                object_store.stream_object(key, range, &callback).await?;
                 Ok(None)
             } else {
                 let mut data = Vec::new();
-                object_store.get_object(key, &mut data).await?;
+                object_store.get_object(key, range, &mut data).await?;
                 Ok(Some(data))
             }
         } else {
@@ -136,6 +208,230 @@ impl ObjectStoreHandler {
         }
     }
 
+    pub async fn head_object(
+        &self,
+        uri: &str,
+        config: &EnvironmentConfig,
+    ) -> Result<ObjectMetadata, LakestreamError> {
+        let parsed_uri = ParsedUri::from_uri(uri, false);
+
+        if let Some(bucket) = &parsed_uri.bucket {
+            let bucket_uri = if let Some(scheme) = &parsed_uri.scheme {
+                format!("{}://{}", scheme, bucket)
+            } else {
+                format!("localfs://{}", bucket)
+            };
+
+            let key = parsed_uri.path.as_deref().ok_or_else(|| {
+                LakestreamError::String(format!(
+                    "No key specified in URI: {}",
+                    uri
+                ))
+            })?;
+            let object_store = ObjectStore::new(&bucket_uri, config.clone())
+                .map_err(LakestreamError::ConfigError)?;
+            object_store.head_object(key).await
+        } else {
+            Err(LakestreamError::NoBucketInUri(uri.to_string()))
+        }
+    }
+
+    pub async fn put_object(
+        &self,
+        uri: &str,
+        config: &EnvironmentConfig,
+        data: &[u8],
+        metadata: Option<&HashMap<String, String>>,
+    ) -> Result<(), LakestreamError> {
+        let parsed_uri = ParsedUri::from_uri(uri, false);
+
+        if let Some(bucket) = &parsed_uri.bucket {
+            let bucket_uri = if let Some(scheme) = &parsed_uri.scheme {
+                format!("{}://{}", scheme, bucket)
+            } else {
+                format!("localfs://{}", bucket)
+            };
+
+            let key = parsed_uri.path.as_deref().ok_or_else(|| {
+                LakestreamError::String(format!(
+                    "No key specified in URI: {}",
+                    uri
+                ))
+            })?;
+            let object_store = ObjectStore::new(&bucket_uri, config.clone())
+                .map_err(LakestreamError::ConfigError)?;
+            object_store.put_object(key, data, metadata).await
+        } else {
+            Err(LakestreamError::NoBucketInUri(uri.to_string()))
+        }
+    }
+
+    // upload data as it is read from a stream, size is the total size if
+    // known, see ObjectStoreTrait::put_object_stream
+    pub async fn put_object_stream(
+        &self,
+        uri: &str,
+        config: &EnvironmentConfig,
+        data: ByteStream<'_>,
+        size: Option<u64>,
+        metadata: Option<&HashMap<String, String>>,
+    ) -> Result<(), LakestreamError> {
+        let parsed_uri = ParsedUri::from_uri(uri, false);
+
+        if let Some(bucket) = &parsed_uri.bucket {
+            let bucket_uri = if let Some(scheme) = &parsed_uri.scheme {
+                format!("{}://{}", scheme, bucket)
+            } else {
+                format!("localfs://{}", bucket)
+            };
+
+            let key = parsed_uri.path.as_deref().ok_or_else(|| {
+                LakestreamError::String(format!(
+                    "No key specified in URI: {}",
+                    uri
+                ))
+            })?;
+            let object_store = ObjectStore::new(&bucket_uri, config.clone())
+                .map_err(LakestreamError::ConfigError)?;
+            object_store
+                .put_object_stream(key, data, size, metadata)
+                .await
+        } else {
+            Err(LakestreamError::NoBucketInUri(uri.to_string()))
+        }
+    }
+
+    pub async fn delete_object(
+        &self,
+        uri: &str,
+        config: &EnvironmentConfig,
+    ) -> Result<(), LakestreamError> {
+        let parsed_uri = ParsedUri::from_uri(uri, false);
+
+        if let Some(bucket) = &parsed_uri.bucket {
+            let key = parsed_uri.path.as_deref().ok_or_else(|| {
+                LakestreamError::String(format!(
+                    "No key specified in URI: {}",
+                    uri
+                ))
+            })?;
+
+            let (bucket_uri, key) = match parsed_uri.scheme.as_deref() {
+                Some("s3") => (format!("s3://{}", bucket), key.to_string()),
+                _ => {
+                    // the parent directory acts as bucket, so that no
+                    // directories are removed. When asked for, the parent
+                    // itself is removed once empty, but never beyond it
+                    let object_path = Path::new(bucket).join(key);
+                    let parent =
+                        object_path.parent().unwrap_or(Path::new(bucket));
+                    let base = match parent.parent() {
+                        Some(base) if remove_empty_dirs(config) => base,
+                        _ => parent,
+                    };
+                    let key = object_path
+                        .strip_prefix(base)
+                        .map(|key| key.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    (format!("localfs://{}", base.display()), key)
+                }
+            };
+
+            let object_store = ObjectStore::new(&bucket_uri, config.clone())
+                .map_err(LakestreamError::ConfigError)?;
+            object_store.delete_object(&key).await
+        } else {
+            Err(LakestreamError::NoBucketInUri(uri.to_string()))
+        }
+    }
+
+    // presigned url to access an S3 object without credentials, e.g. to
+    // share a download (GET) or upload (PUT) link
+    pub async fn presign_url(
+        &self,
+        uri: &str,
+        config: &EnvironmentConfig,
+        method: &str,
+        expires_in: Duration,
+    ) -> Result<String, LakestreamError> {
+        let parsed_uri = ParsedUri::from_uri(uri, false);
+
+        let bucket = parsed_uri
+            .bucket
+            .as_ref()
+            .ok_or_else(|| LakestreamError::NoBucketInUri(uri.to_string()))?;
+        if parsed_uri.scheme.as_deref() != Some("s3") {
+            return Err(LakestreamError::String(format!(
+                "Presigned urls are only supported for S3: {}",
+                uri
+            )));
+        }
+        let key = parsed_uri.path.as_deref().ok_or_else(|| {
+            LakestreamError::String(format!("No key specified in URI: {}", uri))
+        })?;
+
+        let bucket = S3Bucket::new(bucket, config.clone())?;
+        presign_url(&bucket, key, method, expires_in).await
+    }
+
+    // delete multiple keys, relative to the given (directory) URI
+    pub async fn delete_objects(
+        &self,
+        uri: &str,
+        config: &EnvironmentConfig,
+        keys: &[String],
+    ) -> Result<Vec<DeleteResult>, LakestreamError> {
+        let parsed_uri = ParsedUri::from_uri(uri, false);
+
+        if let Some(bucket) = &parsed_uri.bucket {
+            let path = parsed_uri.path.as_deref().unwrap_or_default();
+            let path = path.trim_end_matches('/');
+
+            let (bucket_uri, prefix) = match parsed_uri.scheme.as_deref() {
+                Some("s3") if path.is_empty() => {
+                    (format!("s3://{}", bucket), String::new())
+                }
+                Some("s3") => {
+                    (format!("s3://{}", bucket), format!("{}/", path))
+                }
+                _ => {
+                    // on localfs the directory of the URI acts as bucket, so
+                    // that removing empty directories never goes beyond it
+                    let directory = Path::new(bucket).join(path);
+                    (
+                        format!("localfs://{}", directory.display()),
+                        String::new(),
+                    )
+                }
+            };
+            let object_keys = keys
+                .iter()
+                .map(|key| format!("{}{}", prefix, key))
+                .collect::<Vec<_>>();
+
+            let object_store = ObjectStore::new(&bucket_uri, config.clone())
+                .map_err(LakestreamError::ConfigError)?;
+            let results = object_store.delete_objects(&object_keys).await?;
+
+            // report keys relative to the URI, as they were passed
+            Ok(results
+                .into_iter()
+                .map(|result| {
+                    let key = result
+                        .key()
+                        .strip_prefix(&prefix)
+                        .unwrap_or(result.key());
+                    match result.error() {
+                        Some(error) => DeleteResult::failed(key, error),
+                        None => DeleteResult::deleted(key),
+                    }
+                })
+                .collect())
+        } else {
+            Err(LakestreamError::NoBucketInUri(uri.to_string()))
+        }
+    }
+
     async fn list_files_in_bucket(
         &self,
         parsed_uri: ParsedUri,
@@ -152,7 +448,8 @@ impl ObjectStoreHandler {
             format!("localfs://{}", parsed_uri.bucket.as_ref().unwrap())
         };
 
-        let object_store = ObjectStore::new(&bucket_uri, config).unwrap();
+        let object_store = ObjectStore::new(&bucket_uri, config)
+            .map_err(LakestreamError::ConfigError)?;
         object_store
             .list_files(
                 parsed_uri.path.as_deref(),
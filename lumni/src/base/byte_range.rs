@@ -0,0 +1,194 @@
+use std::fmt;
+use std::str::FromStr;
+
+// constructed via new, inclusive or suffix, which validate the range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    kind: RangeKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RangeKind {
+    // bytes starting at offset, up to the end when length is None
+    Range { offset: u64, length: Option<u64> },
+    // the last N bytes of an object
+    Suffix(u64),
+}
+
+impl ByteRange {
+    // a range of zero bytes can not be requested, HTTP ranges are inclusive
+    pub fn new(offset: u64, length: Option<u64>) -> Result<Self, String> {
+        if let Some(length) = length {
+            if length == 0 {
+                return Err(format!(
+                    "Invalid byte range: zero length at offset {}",
+                    offset
+                ));
+            }
+            // the last byte of the range must be addressable
+            if offset.checked_add(length - 1).is_none() {
+                return Err(format!(
+                    "Invalid byte range: length {} at offset {} exceeds \
+                     the maximum offset",
+                    length, offset
+                ));
+            }
+        }
+        Ok(ByteRange {
+            kind: RangeKind::Range { offset, length },
+        })
+    }
+
+    // range from start to end, both inclusive
+    pub fn inclusive(start: u64, end: u64) -> Result<Self, String> {
+        let length = end
+            .checked_sub(start)
+            .and_then(|length| length.checked_add(1))
+            .ok_or_else(|| format!("Invalid byte range: {}-{}", start, end))?;
+        ByteRange::new(start, Some(length))
+    }
+
+    pub fn suffix(length: u64) -> Result<Self, String> {
+        if length == 0 {
+            return Err("Invalid byte range: zero length suffix".to_string());
+        }
+        Ok(ByteRange {
+            kind: RangeKind::Suffix(length),
+        })
+    }
+
+    // value for the HTTP Range header, e.g. "bytes=0-1023"
+    pub fn to_header_value(&self) -> String {
+        format!("bytes={}", self)
+    }
+
+    // resolve to (start, length) for an object of the given size,
+    // returns None if the range can not be satisfied
+    pub fn resolve(&self, object_size: u64) -> Option<(u64, u64)> {
+        let (start, length) = match self.kind {
+            RangeKind::Range { offset, length } => {
+                if offset >= object_size {
+                    return None;
+                }
+                let available = object_size - offset;
+                (offset, length.map_or(available, |len| len.min(available)))
+            }
+            RangeKind::Suffix(length) => {
+                let start = object_size.saturating_sub(length);
+                (start, object_size - start)
+            }
+        };
+        if length == 0 {
+            None
+        } else {
+            Some((start, length))
+        }
+    }
+}
+
+impl fmt::Display for ByteRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            RangeKind::Range {
+                offset,
+                length: Some(length),
+            } => write!(f, "{}-{}", offset, offset + (length - 1)),
+            RangeKind::Range {
+                offset,
+                length: None,
+            } => write!(f, "{}-", offset),
+            RangeKind::Suffix(length) => write!(f, "-{}", length),
+        }
+    }
+}
+
+impl FromStr for ByteRange {
+    type Err = String;
+
+    // parse an (inclusive) HTTP style range: "100-199", "100-" or "-500"
+    fn from_str(range: &str) -> Result<Self, Self::Err> {
+        let range = range.trim().trim_start_matches("bytes=");
+        let invalid = || format!("Invalid byte range: {}", range);
+
+        let (start, end) = range.split_once('-').ok_or_else(invalid)?;
+        let parse = |value: &str| value.parse::<u64>().map_err(|_| invalid());
+
+        match (start.is_empty(), end.is_empty()) {
+            (true, false) => {
+                ByteRange::suffix(parse(end)?).map_err(|_| invalid())
+            }
+            (false, true) => ByteRange::new(parse(start)?, None),
+            (false, false) => ByteRange::inclusive(parse(start)?, parse(end)?)
+                .map_err(|_| invalid()),
+            (true, true) => Err(invalid()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_byte_range() {
+        let cases = vec![
+            ("0-99", ByteRange::new(0, Some(100)).unwrap()),
+            ("bytes=100-199", ByteRange::new(100, Some(100)).unwrap()),
+            ("1024-", ByteRange::new(1024, None).unwrap()),
+            ("-500", ByteRange::suffix(500).unwrap()),
+        ];
+        for (input, expected) in cases {
+            let range = input.parse::<ByteRange>().unwrap();
+            assert_eq!(range, expected);
+            assert_eq!(
+                range.to_header_value(),
+                format!("bytes={}", input.trim_start_matches("bytes="))
+            );
+        }
+
+        let invalid_cases =
+            vec!["", "-", "100", "200-100", "-0", "a-b", "1-2-3"];
+        for input in invalid_cases {
+            assert!(input.parse::<ByteRange>().is_err());
+        }
+    }
+
+    #[test]
+    fn test_zero_length_byte_range() {
+        assert!(ByteRange::new(10, Some(0)).is_err());
+        assert!(ByteRange::suffix(0).is_err());
+        assert!(ByteRange::new(0, Some(1)).is_ok());
+    }
+
+    #[test]
+    fn test_byte_range_bounds() {
+        let max = u64::MAX;
+        assert_eq!(
+            format!("0-{}", max).parse::<ByteRange>(),
+            Err(format!("Invalid byte range: 0-{}", max))
+        );
+        let range = format!("1-{}", max).parse::<ByteRange>().unwrap();
+        assert_eq!(range, ByteRange::new(1, Some(max)).unwrap());
+        assert_eq!(range.to_header_value(), format!("bytes=1-{}", max));
+        assert!(format!("{}-{}", max, max).parse::<ByteRange>().is_ok());
+
+        assert!(ByteRange::inclusive(5, 4).is_err());
+        assert_eq!(ByteRange::inclusive(5, 5), ByteRange::new(5, Some(1)));
+        assert!(ByteRange::new(2, Some(max)).is_err());
+        assert!(ByteRange::new(max, Some(2)).is_err());
+        assert!(ByteRange::new(max, None).is_ok());
+    }
+
+    #[test]
+    fn test_resolve_byte_range() {
+        let range = |offset, length| ByteRange::new(offset, length).unwrap();
+        assert_eq!(range(10, Some(5)).resolve(100), Some((10, 5)));
+        assert_eq!(range(90, Some(50)).resolve(100), Some((90, 10)));
+        assert_eq!(range(10, None).resolve(100), Some((10, 90)));
+        assert_eq!(range(100, None).resolve(100), None);
+        let suffix = |length| ByteRange::suffix(length).unwrap();
+        assert_eq!(suffix(30).resolve(100), Some((70, 30)));
+        assert_eq!(suffix(300).resolve(100), Some((0, 100)));
+        assert_eq!(suffix(30).resolve(0), None);
+    }
+}
@@ -0,0 +1,34 @@
+// Result of deleting a single object as part of a (batch) delete
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeleteResult {
+    key: String,
+    error: Option<String>,
+}
+
+impl DeleteResult {
+    pub fn deleted(key: &str) -> Self {
+        DeleteResult {
+            key: key.to_string(),
+            error: None,
+        }
+    }
+
+    pub fn failed(key: &str, error: &str) -> Self {
+        DeleteResult {
+            key: key.to_string(),
+            error: Some(error.to_string()),
+        }
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.error.is_none()
+    }
+}
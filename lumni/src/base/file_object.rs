@@ -41,6 +41,18 @@ impl FileObject {
         &self.tags
     }
 
+    // ETag of an S3 object, without quotes
+    pub fn etag(&self) -> Option<&str> {
+        self.tag("ETag")
+    }
+
+    fn tag(&self, key: &str) -> Option<&str> {
+        self.tags
+            .as_ref()
+            .and_then(|tags| tags.get(key))
+            .map(String::as_str)
+    }
+
     pub fn get_value_by_column_name(
         &self,
         column_name: &str,
@@ -48,9 +60,20 @@ impl FileObject {
         match column_name {
             "name" => Some(TableColumnValue::StringColumn(self.name.clone())),
             "size" => Some(TableColumnValue::Uint64Column(self.size)),
-            "modified" => self
-                .modified
-                .map(|val| TableColumnValue::OptionalUint64Column(Some(val))),
+            "modified" => {
+                Some(TableColumnValue::OptionalUint64Column(self.modified))
+            }
+            // content digests are only known for some object stores
+            "etag" | "md5" | "sha256" => {
+                let key = if column_name == "etag" {
+                    "ETag"
+                } else {
+                    column_name
+                };
+                Some(TableColumnValue::OptionalStringColumn(
+                    self.tag(key).map(String::from),
+                ))
+            }
             _ => None,
         }
     }
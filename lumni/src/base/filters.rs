@@ -216,7 +216,7 @@ mod tests {
 
         let valid_base_cases_inputs = &[
             "2M", "-2M", "3W", "-3W", "5D", "-5D", "48h", "-48h", "20m",
-            "-20m", "10s", "-10s", "1Y", "-1Y",
+            "-20m", "10s", "-10s", "1Y", "-1Y", "90", "-90",
         ];
         let valid_base_cases =
             generate_valid_cases(current_time, valid_base_cases_inputs)
@@ -229,6 +229,12 @@ mod tests {
             assert_eq!(max_time_result, max_time);
         }
 
+        // the units of a combined offset add up
+        assert_eq!(
+            parse_time("1D8h20m", current_time).unwrap(),
+            (None, Some(current_time - (32 * 3600 + 20 * 60)))
+        );
+
         let valid_combined_cases_inputs = &[
             "1Y2M",
             "-1Y2M",
@@ -263,7 +269,9 @@ mod tests {
             "3660001D",
             "-3660001D",
             "316224000001s",
-            "-316224000001s", // edge cases
+            "-316224000001s",
+            "316224000001",
+            "99999999999999999999s", // edge cases
             " 2M",
             "2M ",
             " 2M ",
@@ -0,0 +1,41 @@
+use crate::FileObject;
+
+// where a page listing starts, when not at the beginning
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListStart {
+    // token returned as next_token by a previous page
+    Token(String),
+    // list keys that sort after this key
+    After(String),
+}
+
+// a single page of a listing, next_token is set when there are more pages
+#[derive(Debug, Clone, Default)]
+pub struct ListPage {
+    file_objects: Vec<FileObject>,
+    next_token: Option<String>,
+}
+
+impl ListPage {
+    pub fn new(
+        file_objects: Vec<FileObject>,
+        next_token: Option<String>,
+    ) -> Self {
+        ListPage {
+            file_objects,
+            next_token,
+        }
+    }
+
+    pub fn file_objects(&self) -> &[FileObject] {
+        &self.file_objects
+    }
+
+    pub fn next_token(&self) -> Option<&str> {
+        self.next_token.as_deref()
+    }
+
+    pub fn into_parts(self) -> (Vec<FileObject>, Option<String>) {
+        (self.file_objects, self.next_token)
+    }
+}
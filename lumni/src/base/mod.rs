@@ -1,5 +1,9 @@
+pub mod byte_range;
 pub mod callback_wrapper;
 pub mod config;
+pub mod delete_result;
 pub mod file_object;
 pub mod filters;
+pub mod list_page;
+pub mod object_metadata;
 pub mod object_store;
@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+
+// Metadata of a single object, as returned by head_object
+#[derive(Debug, Clone, Default)]
+pub struct ObjectMetadata {
+    size: u64,
+    last_modified: Option<u64>,
+    etag: Option<String>,
+    content_type: Option<String>,
+    user_metadata: HashMap<String, String>,
+}
+
+impl ObjectMetadata {
+    pub fn new(
+        size: u64,
+        last_modified: Option<u64>,
+        etag: Option<String>,
+        content_type: Option<String>,
+        user_metadata: HashMap<String, String>,
+    ) -> Self {
+        ObjectMetadata {
+            size,
+            last_modified,
+            etag,
+            content_type,
+            user_metadata,
+        }
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn last_modified(&self) -> Option<u64> {
+        self.last_modified
+    }
+
+    pub fn etag(&self) -> Option<&str> {
+        self.etag.as_deref()
+    }
+
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    pub fn user_metadata(&self) -> &HashMap<String, String> {
+        &self.user_metadata
+    }
+}
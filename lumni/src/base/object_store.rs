@@ -1,16 +1,29 @@
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::pin::Pin;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use bytes::Bytes;
+use futures::Stream;
 
 use crate::localfs::backend::LocalFsBucket;
 use crate::s3::backend::S3Bucket;
 use crate::table::{FileObjectTable, Table};
 use crate::{
-    EnvironmentConfig, FileObjectFilter, LakestreamError, TableCallback,
+    BinaryCallbackWrapper, ByteRange, DeleteResult, EnvironmentConfig,
+    FileObject, FileObjectFilter, LakestreamError, ListPage, ListStart,
+    ObjectMetadata, TableCallback,
 };
 
+// objects are produced as they are listed, without collecting them first
+pub type FileObjectStream<'a> =
+    Pin<Box<dyn Stream<Item = Result<FileObject, LakestreamError>> + 'a>>;
+
+// data of an object in chunks, so it does not have to fit in memory
+pub type ByteStream<'a> =
+    Pin<Box<dyn Stream<Item = Result<Bytes, LakestreamError>> + 'a>>;
+
 #[derive(Debug, Clone)]
 pub enum ObjectStore {
     S3Bucket(S3Bucket),
@@ -105,15 +118,142 @@ impl ObjectStore {
         Ok(Box::new(table))
     }
 
+    pub async fn list_page(
+        &self,
+        prefix: Option<&str>,
+        recursive: bool,
+        max_files: Option<u32>,
+        start: Option<&ListStart>,
+        filter: &Option<FileObjectFilter>,
+    ) -> Result<ListPage, LakestreamError> {
+        match self {
+            ObjectStore::S3Bucket(bucket) => {
+                bucket
+                    .list_page(prefix, recursive, max_files, start, filter)
+                    .await
+            }
+            ObjectStore::LocalFsBucket(local_fs) => {
+                local_fs
+                    .list_page(prefix, recursive, max_files, start, filter)
+                    .await
+            }
+        }
+    }
+
+    pub fn list_stream(
+        &self,
+        prefix: Option<&str>,
+        recursive: bool,
+        max_files: Option<u32>,
+        filter: &Option<FileObjectFilter>,
+    ) -> FileObjectStream<'static> {
+        match self {
+            ObjectStore::S3Bucket(bucket) => {
+                bucket.list_stream(prefix, recursive, max_files, filter)
+            }
+            ObjectStore::LocalFsBucket(local_fs) => {
+                local_fs.list_stream(prefix, recursive, max_files, filter)
+            }
+        }
+    }
+
     pub async fn get_object(
         &self,
         key: &str,
+        range: Option<&ByteRange>,
         data: &mut Vec<u8>,
     ) -> Result<(), LakestreamError> {
         match self {
-            ObjectStore::S3Bucket(bucket) => bucket.get_object(key, data).await,
+            ObjectStore::S3Bucket(bucket) => {
+                bucket.get_object(key, range, data).await
+            }
+            ObjectStore::LocalFsBucket(local_fs) => {
+                local_fs.get_object(key, range, data).await
+            }
+        }
+    }
+
+    pub async fn stream_object(
+        &self,
+        key: &str,
+        range: Option<&ByteRange>,
+        callback: &BinaryCallbackWrapper,
+    ) -> Result<(), LakestreamError> {
+        match self {
+            ObjectStore::S3Bucket(bucket) => {
+                bucket.stream_object(key, range, callback).await
+            }
+            ObjectStore::LocalFsBucket(local_fs) => {
+                local_fs.stream_object(key, range, callback).await
+            }
+        }
+    }
+
+    pub async fn head_object(
+        &self,
+        key: &str,
+    ) -> Result<ObjectMetadata, LakestreamError> {
+        match self {
+            ObjectStore::S3Bucket(bucket) => bucket.head_object(key).await,
             ObjectStore::LocalFsBucket(local_fs) => {
-                local_fs.get_object(key, data).await
+                local_fs.head_object(key).await
+            }
+        }
+    }
+
+    pub async fn put_object(
+        &self,
+        key: &str,
+        data: &[u8],
+        metadata: Option<&HashMap<String, String>>,
+    ) -> Result<(), LakestreamError> {
+        match self {
+            ObjectStore::S3Bucket(bucket) => {
+                bucket.put_object(key, data, metadata).await
+            }
+            ObjectStore::LocalFsBucket(local_fs) => {
+                local_fs.put_object(key, data, metadata).await
+            }
+        }
+    }
+
+    pub async fn put_object_stream(
+        &self,
+        key: &str,
+        data: ByteStream<'_>,
+        size: Option<u64>,
+        metadata: Option<&HashMap<String, String>>,
+    ) -> Result<(), LakestreamError> {
+        match self {
+            ObjectStore::S3Bucket(bucket) => {
+                bucket.put_object_stream(key, data, size, metadata).await
+            }
+            ObjectStore::LocalFsBucket(local_fs) => {
+                local_fs.put_object_stream(key, data, size, metadata).await
+            }
+        }
+    }
+
+    pub async fn delete_object(
+        &self,
+        key: &str,
+    ) -> Result<(), LakestreamError> {
+        match self {
+            ObjectStore::S3Bucket(bucket) => bucket.delete_object(key).await,
+            ObjectStore::LocalFsBucket(local_fs) => {
+                local_fs.delete_object(key).await
+            }
+        }
+    }
+
+    pub async fn delete_objects(
+        &self,
+        keys: &[String],
+    ) -> Result<Vec<DeleteResult>, LakestreamError> {
+        match self {
+            ObjectStore::S3Bucket(bucket) => bucket.delete_objects(keys).await,
+            ObjectStore::LocalFsBucket(local_fs) => {
+                local_fs.delete_objects(keys).await
             }
         }
     }
@@ -132,13 +272,59 @@ pub trait ObjectStoreTrait: Send {
         filter: &Option<FileObjectFilter>,
         table: &mut FileObjectTable,
     ) -> Result<(), LakestreamError>;
+    async fn list_page(
+        &self,
+        prefix: Option<&str>,
+        recursive: bool,
+        max_keys: Option<u32>,
+        start: Option<&ListStart>,
+        filter: &Option<FileObjectFilter>,
+    ) -> Result<ListPage, LakestreamError>;
+    fn list_stream(
+        &self,
+        prefix: Option<&str>,
+        recursive: bool,
+        max_keys: Option<u32>,
+        filter: &Option<FileObjectFilter>,
+    ) -> FileObjectStream<'static>;
     async fn get_object(
         &self,
         key: &str,
+        range: Option<&ByteRange>,
         data: &mut Vec<u8>,
     ) -> Result<(), LakestreamError>;
+    async fn stream_object(
+        &self,
+        key: &str,
+        range: Option<&ByteRange>,
+        callback: &BinaryCallbackWrapper,
+    ) -> Result<(), LakestreamError>;
+    // returns LakestreamError::NotFound if the object does not exist
     async fn head_object(
         &self,
         key: &str,
-    ) -> Result<(u16, HashMap<String, String>), LakestreamError>;
+    ) -> Result<ObjectMetadata, LakestreamError>;
+    async fn put_object(
+        &self,
+        key: &str,
+        data: &[u8],
+        metadata: Option<&HashMap<String, String>>,
+    ) -> Result<(), LakestreamError>;
+    // size is the total size of the data if known, used to choose the part
+    // size of a multipart upload
+    async fn put_object_stream(
+        &self,
+        key: &str,
+        data: ByteStream<'_>,
+        size: Option<u64>,
+        metadata: Option<&HashMap<String, String>>,
+    ) -> Result<(), LakestreamError>;
+    // deleting a non-existing object is not an error
+    async fn delete_object(&self, key: &str) -> Result<(), LakestreamError>;
+    // returns a result per key, Err is only returned if the request fails
+    // as a whole
+    async fn delete_objects(
+        &self,
+        keys: &[String],
+    ) -> Result<Vec<DeleteResult>, LakestreamError>;
 }
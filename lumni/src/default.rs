@@ -1,2 +1,29 @@
 pub const AWS_DEFAULT_REGION: &str = "us-east-1";
 pub const AWS_MAX_LIST_OBJECTS: u32 = 1000;
+pub const AWS_MAX_DELETE_OBJECTS: usize = 1000;
+pub const AWS_MIN_PART_SIZE: u64 = 5 * 1024 * 1024;
+pub const AWS_MAX_PARTS: u64 = 10_000;
+
+// multipart upload defaults, can be overridden via the S3_MULTIPART_*
+// configuration settings
+pub const S3_MULTIPART_THRESHOLD: u64 = 16 * 1024 * 1024;
+pub const S3_MULTIPART_PART_SIZE: u64 = 8 * 1024 * 1024;
+pub const S3_MULTIPART_CONCURRENCY: usize = 4;
+
+// retries of transient HTTP errors, can be overridden via AWS_MAX_ATTEMPTS
+// and the S3_RETRY_* configuration settings
+pub const RETRY_MAX_ATTEMPTS: u32 = 5;
+pub const RETRY_BASE_DELAY_MS: u64 = 100;
+pub const RETRY_MAX_DELAY_MS: u64 = 10_000;
+pub const RETRY_MAX_ELAPSED_MS: u64 = 60_000;
+
+// pooled HTTP connections, can be overridden via the HTTP_POOL_*
+// configuration settings
+pub const HTTP_POOL_MAX_IDLE_PER_HOST: usize = 32;
+pub const HTTP_POOL_IDLE_TIMEOUT_SECS: u64 = 90;
+
+// longest expiration of a presigned url (7 days)
+pub const S3_PRESIGN_MAX_EXPIRES_SECS: u64 = 7 * 24 * 3600;
+
+// number of prefixes listed in parallel when walking virtual directories
+pub const S3_LIST_CONCURRENCY: usize = 8;
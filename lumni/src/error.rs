@@ -13,6 +13,13 @@ pub enum LakestreamError {
     AccessDenied(String),
     InternalError(String),
     NotFound(String),
+    NoSuchKey(S3ErrorResponse),
+    NoSuchBucket(S3ErrorResponse),
+    InvalidAccessKeyId(S3ErrorResponse),
+    SignatureDoesNotMatch(S3ErrorResponse),
+    RequestTimeTooSkewed(S3ErrorResponse),
+    // any other error response returned by S3
+    S3Error(S3ErrorResponse),
     Anyhow(anyhow::Error),
     #[cfg(target_arch = "wasm32")]
     Js(wasm_bindgen::JsValue),
@@ -38,6 +45,12 @@ impl fmt::Display for LakestreamError {
             }
             LakestreamError::Anyhow(e) => write!(f, "Anyhow error: {}", e),
             LakestreamError::NotFound(s) => write!(f, "Not found: {}", s),
+            LakestreamError::NoSuchKey(e)
+            | LakestreamError::NoSuchBucket(e)
+            | LakestreamError::InvalidAccessKeyId(e)
+            | LakestreamError::SignatureDoesNotMatch(e)
+            | LakestreamError::RequestTimeTooSkewed(e)
+            | LakestreamError::S3Error(e) => write!(f, "{}", e),
             #[cfg(target_arch = "wasm32")]
             LakestreamError::Js(e) => write!(
                 f,
@@ -50,6 +63,100 @@ impl fmt::Display for LakestreamError {
 
 impl Error for LakestreamError {}
 
+// Error document returned by S3 for a failed request
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct S3ErrorResponse {
+    status: u16,
+    code: String,
+    message: Option<String>,
+    request_id: Option<String>,
+    resource: Option<String>,
+}
+
+impl S3ErrorResponse {
+    pub fn new(
+        status: u16,
+        code: String,
+        message: Option<String>,
+        request_id: Option<String>,
+        resource: Option<String>,
+    ) -> Self {
+        S3ErrorResponse {
+            status,
+            code,
+            message,
+            request_id,
+            resource,
+        }
+    }
+
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    pub fn resource(&self) -> Option<&str> {
+        self.resource.as_deref()
+    }
+
+    // codes that S3 documents as safe to retry
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self.code.as_str(),
+            "SlowDown"
+                | "RequestTimeout"
+                | "InternalError"
+                | "ServiceUnavailable"
+        )
+    }
+}
+
+impl fmt::Display for S3ErrorResponse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.code)?;
+        if let Some(message) = &self.message {
+            write!(f, ": {}", message)?;
+        }
+        write!(f, " (status: {}", self.status)?;
+        if let Some(resource) = &self.resource {
+            write!(f, ", resource: {}", resource)?;
+        }
+        if let Some(request_id) = &self.request_id {
+            write!(f, ", request id: {}", request_id)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl From<S3ErrorResponse> for LakestreamError {
+    fn from(error: S3ErrorResponse) -> Self {
+        match error.code() {
+            "NoSuchKey" => LakestreamError::NoSuchKey(error),
+            "NoSuchBucket" => LakestreamError::NoSuchBucket(error),
+            "InvalidAccessKeyId" => LakestreamError::InvalidAccessKeyId(error),
+            "SignatureDoesNotMatch" => {
+                LakestreamError::SignatureDoesNotMatch(error)
+            }
+            "RequestTimeTooSkewed" => {
+                LakestreamError::RequestTimeTooSkewed(error)
+            }
+            "AccessDenied" => LakestreamError::AccessDenied(error.to_string()),
+            _ => LakestreamError::S3Error(error),
+        }
+    }
+}
+
 impl From<Box<dyn Error>> for LakestreamError {
     fn from(error: Box<dyn Error>) -> Self {
         LakestreamError::Wrapped(error)
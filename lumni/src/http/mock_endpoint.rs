@@ -0,0 +1,153 @@
+// HTTP endpoint for tests, serving requests with the responses of a handler
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+pub struct MockRequest {
+    pub method: String,
+    pub path: String, // including the query string
+    pub headers: HashMap<String, String>, // lowercase names
+    pub body: Vec<u8>,
+}
+
+pub struct MockResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    delay: Option<Duration>,
+}
+
+impl MockResponse {
+    pub fn new(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        MockResponse {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+            delay: None,
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    // respond after a delay, e.g. to complete requests out of order
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+}
+
+// serves each request with the response of the handler, connections are
+// served concurrently. Returns the endpoint and a counter of the requests
+pub async fn mock_endpoint<F>(handler: F) -> (String, Arc<AtomicUsize>)
+where
+    F: Fn(&MockRequest) -> MockResponse + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+    let handler = Arc::new(handler);
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let handler = handler.clone();
+            tokio::spawn(async move {
+                serve(stream, handler.as_ref()).await;
+            });
+        }
+    });
+    (endpoint, requests)
+}
+
+async fn serve<F>(mut stream: TcpStream, handler: &F)
+where
+    F: Fn(&MockRequest) -> MockResponse,
+{
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        match stream.read_u8().await {
+            Ok(byte) => head.push(byte),
+            Err(_) => return,
+        }
+    }
+    let head = String::from_utf8_lossy(&head).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+    let method = request_line.next().unwrap_or_default().to_string();
+    let path = request_line.next().unwrap_or_default().to_string();
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| {
+            (key.to_ascii_lowercase(), value.trim().to_string())
+        })
+        .collect::<HashMap<_, _>>();
+
+    let length = headers
+        .get("content-length")
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = vec![0u8; length];
+    if stream.read_exact(&mut body).await.is_err() {
+        return;
+    }
+
+    let request = MockRequest {
+        method,
+        path,
+        headers,
+        body,
+    };
+    let response = handler(&request);
+    if let Some(delay) = response.delay {
+        tokio::time::sleep(delay).await;
+    }
+
+    let mut head = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    let _ = stream.write_all(head.as_bytes()).await;
+    // a HEAD response has the length of the body, but no body
+    if request.method != "HEAD" {
+        let _ = stream.write_all(&response.body).await;
+    }
+}
+
+// configuration of an S3 bucket served by a mock endpoint
+pub fn mock_s3_config(endpoint: &str) -> crate::EnvironmentConfig {
+    let mut config = crate::EnvironmentConfig::default();
+    for (key, value) in [
+        ("AWS_ACCESS_KEY_ID", "AKIDMOCK"),
+        ("AWS_SECRET_ACCESS_KEY", "mock-secret"),
+        ("AWS_REGION", "us-east-1"),
+        ("S3_ENDPOINT_URL", endpoint),
+    ] {
+        config.set(key.to_string(), value.to_string());
+    }
+    config
+}
+
+// value of a query parameter of the request path, percent-decoded
+pub fn query_param(path: &str, name: &str) -> Option<String> {
+    let (_, query) = path.split_once('?')?;
+    query.split('&').find_map(|param| {
+        let (key, value) = param.split_once('=').unwrap_or((param, ""));
+        (key == name).then(|| {
+            percent_encoding::percent_decode_str(value)
+                .decode_utf8_lossy()
+                .to_string()
+        })
+    })
+}
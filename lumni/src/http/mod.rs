@@ -1,6 +1,18 @@
+use std::collections::HashMap;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod client;
+pub mod client_config;
+#[cfg(all(test, not(target_arch = "wasm32")))]
+pub mod mock_endpoint;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod requests;
 
 #[cfg(target_arch = "wasm32")]
 #[path = "requests_wasm32.rs"]
 pub mod requests;
+
+pub mod retry;
+
+// called with the headers of a streamed response, before its body
+pub type ResponseHeadersHook = dyn Fn(&HashMap<String, String>);
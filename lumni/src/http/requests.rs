@@ -2,25 +2,29 @@ use std::collections::HashMap;
 use std::str::FromStr;
 
 use bytes::Bytes;
-use hyper::client::HttpConnector;
+use hyper::body::HttpBody;
 use hyper::header::{HeaderName, HeaderValue};
-use hyper::{Body, Client, Request};
-use hyper_tls::HttpsConnector;
+use hyper::{Body, Request, Response};
 use log::info;
-use native_tls::TlsConnector as NativeTlsConnector;
-use tokio_native_tls::TlsConnector;
 use url::Url;
 
+use super::client::http_client;
+use super::client_config::HttpClientConfig;
+use super::ResponseHeadersHook;
+use crate::{BinaryCallbackWrapper, LakestreamError};
+
 type HttpResult = Result<(Bytes, u16, HashMap<String, String>), anyhow::Error>;
 type HttpResultWithoutHeaders = Result<(Bytes, u16), anyhow::Error>;
 
 pub async fn http_get_request(
     url: &str,
     headers: &HashMap<String, String>,
+    client_config: &HttpClientConfig,
 ) -> HttpResultWithoutHeaders {
     let method = "GET";
     let (body, status, _) =
-        http_request_with_headers(url, headers, method).await?;
+        http_request_with_headers(url, headers, method, None, client_config)
+            .await?;
     Ok((body, status))
 }
 
@@ -28,30 +32,75 @@ pub async fn http_request_with_headers(
     url: &str,
     headers: &HashMap<String, String>,
     method: &str,
+    body: Option<Bytes>,
+    client_config: &HttpClientConfig,
 ) -> HttpResult {
+    let response =
+        send_request(url, headers, method, body, client_config).await?;
+
+    let status = response.status().as_u16();
+    let headers_map = parse_response_headers(&response);
+
+    // the body of an error response describes the error
+    let body_bytes = hyper::body::to_bytes(response.into_body()).await?;
+    Ok((body_bytes, status, headers_map))
+}
+
+// forward the response body to the callback chunk by chunk as it arrives,
+// instead of collecting it in memory first. The body of an error response
+// is not forwarded but returned. For a successful response, on_headers
+// is called with the response headers before the body is forwarded
+pub async fn http_request_with_callback(
+    url: &str,
+    headers: &HashMap<String, String>,
+    method: &str,
+    callback: &BinaryCallbackWrapper,
+    on_headers: Option<&ResponseHeadersHook>,
+    client_config: &HttpClientConfig,
+) -> Result<(Bytes, u16, HashMap<String, String>), LakestreamError> {
+    let response =
+        send_request(url, headers, method, None, client_config).await?;
+
+    let status = response.status().as_u16();
+    let headers_map = parse_response_headers(&response);
+
+    if !(200..300).contains(&(status as isize)) {
+        let body_bytes = hyper::body::to_bytes(response.into_body())
+            .await
+            .map_err(anyhow::Error::from)?;
+        return Ok((body_bytes, status, headers_map));
+    }
+    if let Some(on_headers) = on_headers {
+        on_headers(&headers_map);
+    }
+
+    let mut body = response.into_body();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(anyhow::Error::from)?;
+        callback.call(chunk.to_vec()).await?;
+    }
+    Ok((Bytes::new(), status, headers_map))
+}
+
+async fn send_request(
+    url: &str,
+    headers: &HashMap<String, String>,
+    method: &str,
+    body: Option<Bytes>,
+    client_config: &HttpClientConfig,
+) -> Result<Response<Body>, anyhow::Error> {
     info!("http_request_with_headers {}: {}", method, url);
     let url_u = Url::parse(url)?;
     let accept_invalid_certs = url_u.scheme() == "https"
         && url_u.host_str() == Some("localhost")
         && url_u.port().map_or(true, |port| port > 0);
 
-    let mut native_tls_connector_builder = NativeTlsConnector::builder();
-    native_tls_connector_builder
-        .danger_accept_invalid_certs(accept_invalid_certs);
-    let native_tls_connector = native_tls_connector_builder.build().unwrap();
-
-    let tls_connector = TlsConnector::from(native_tls_connector);
-
-    let mut http_connector = HttpConnector::new();
-    http_connector.enforce_http(false);
-
-    let https = HttpsConnector::from((http_connector, tls_connector));
-    let client = Client::builder().build::<_, Body>(https);
+    let client = http_client(client_config, accept_invalid_certs)?;
 
     let mut request = Request::builder()
         .method(method)
         .uri(url)
-        .body(Body::empty())?;
+        .body(body.map_or_else(Body::empty, Body::from))?;
 
     for (key, value) in headers.iter() {
         if let (Ok(header_name), Ok(header_value)) =
@@ -61,16 +110,26 @@ pub async fn http_request_with_headers(
         }
     }
 
-    let response = client.request(request).await?;
-
-    let status = response.status().as_u16();
-    let headers_map = parse_response_headers(&response);
-
-    if !(200..300).contains(&(status as isize)) {
-        return Ok((Bytes::new(), status, headers_map));
+    // plain HTTP is forwarded by a proxy, which needs the credentials on
+    // every request (for HTTPS these are sent when opening the tunnel)
+    if url_u.scheme() == "http" {
+        let proxy = client_config.proxy().proxy_for(
+            "http",
+            url_u.host_str().unwrap_or_default(),
+            url_u.port_or_known_default().unwrap_or(80),
+        );
+        if let Some(authorization) =
+            proxy.and_then(|proxy| proxy.authorization())
+        {
+            request.headers_mut().insert(
+                hyper::header::PROXY_AUTHORIZATION,
+                HeaderValue::from_str(authorization)?,
+            );
+        }
     }
-    let body_bytes = hyper::body::to_bytes(response.into_body()).await?;
-    Ok((body_bytes, status, headers_map))
+
+    let response = client.request(request).await?;
+    Ok(response)
 }
 
 fn parse_response_headers(
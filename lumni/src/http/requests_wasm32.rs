@@ -6,29 +6,57 @@ use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::JsFuture;
 use web_sys::{Headers, Request, RequestInit, RequestMode, Response};
 
-use crate::LakestreamError;
+use super::client_config::HttpClientConfig;
+use super::ResponseHeadersHook;
+use crate::{BinaryCallbackWrapper, LakestreamError};
 
 pub async fn http_request_with_headers(
     url: &str,
     headers: &HashMap<String, String>,
     method: &str,
+    body: Option<Bytes>,
+    _client_config: &HttpClientConfig, // connections are managed by fetch
 ) -> Result<(Bytes, u16, HashMap<String, String>), LakestreamError> {
     info!("http_get_request_with_headers: {}", url);
     // TODO: implement response headers -- for now forward to http_get_request
     // Call the http_get_request function
     let (response_body, response_status) =
-        http_request(url, headers, method).await?;
+        http_request(url, headers, method, body).await?;
 
     // Add the headers to the returned result
     Ok((response_body, response_status, HashMap::new()))
 }
 
+// fetch does not expose a chunked body to us (yet), so the full
+// response is forwarded to the callback in a single call
+pub async fn http_request_with_callback(
+    url: &str,
+    headers: &HashMap<String, String>,
+    method: &str,
+    callback: &BinaryCallbackWrapper,
+    on_headers: Option<&ResponseHeadersHook>,
+    client_config: &HttpClientConfig,
+) -> Result<(Bytes, u16, HashMap<String, String>), LakestreamError> {
+    let (response_body, response_status, response_headers) =
+        http_request_with_headers(url, headers, method, None, client_config)
+            .await?;
+    if !(200..300).contains(&response_status) {
+        return Ok((response_body, response_status, response_headers));
+    }
+    if let Some(on_headers) = on_headers {
+        on_headers(&response_headers);
+    }
+    callback.call(response_body.to_vec()).await?;
+    Ok((Bytes::new(), response_status, response_headers))
+}
+
 pub async fn http_get_request(
     url: &str,
     headers: &HashMap<String, String>,
+    _client_config: &HttpClientConfig,
 ) -> Result<(Bytes, u16), LakestreamError> {
     let method = "GET";
-    let (body, status) = http_request(url, headers, method).await?;
+    let (body, status) = http_request(url, headers, method, None).await?;
     Ok((body, status))
 }
 
@@ -36,6 +64,7 @@ pub async fn http_request(
     url: &str,
     headers: &HashMap<String, String>,
     method: &str,
+    body: Option<Bytes>,
 ) -> Result<(Bytes, u16), LakestreamError> {
     info!("http_request: {}", url);
     let window = web_sys::window()
@@ -44,6 +73,10 @@ pub async fn http_request(
     let mut request_init = RequestInit::new();
     request_init.method(method);
     request_init.mode(RequestMode::Cors);
+    if let Some(body) = body {
+        let body_array = js_sys::Uint8Array::from(&body[..]);
+        request_init.body(Some(&body_array));
+    }
 
     let headers_map =
         Headers::new().map_err(|e| LakestreamError::Js(e.into()))?;
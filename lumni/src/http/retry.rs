@@ -0,0 +1,216 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+use super::client::ProxyError;
+use crate::{
+    LakestreamError, RETRY_BASE_DELAY_MS, RETRY_MAX_ATTEMPTS,
+    RETRY_MAX_DELAY_MS, RETRY_MAX_ELAPSED_MS,
+};
+
+// Retry policy for transient errors, delays grow exponentially from
+// base_delay up to max_delay, with (full) jitter applied to each delay
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    max_elapsed: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::new(
+            RETRY_MAX_ATTEMPTS,
+            Duration::from_millis(RETRY_BASE_DELAY_MS),
+            Duration::from_millis(RETRY_MAX_DELAY_MS),
+            Duration::from_millis(RETRY_MAX_ELAPSED_MS),
+        )
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(
+        max_attempts: u32,
+        base_delay: Duration,
+        max_delay: Duration,
+        max_elapsed: Duration,
+    ) -> Self {
+        RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            base_delay,
+            max_delay,
+            max_elapsed,
+        }
+    }
+
+    // upper bound of the delay before the given retry (starting at 1)
+    pub fn delay_cap(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.base_delay
+            .checked_mul(factor)
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay))
+    }
+
+    fn delay(&self, retry: u32) -> Duration {
+        let cap = self.delay_cap(retry).as_millis() as u64;
+        Duration::from_millis(random_u64() % (cap + 1))
+    }
+}
+
+// tracks the attempts of a single request
+pub struct Backoff<'a> {
+    policy: &'a RetryPolicy,
+    attempt: u32,
+    started_ms: u64,
+}
+
+impl<'a> Backoff<'a> {
+    pub fn new(policy: &'a RetryPolicy) -> Self {
+        Backoff {
+            policy,
+            attempt: 1,
+            started_ms: now_ms(),
+        }
+    }
+
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    // wait before the next attempt, returns false when the policy does not
+    // allow another attempt
+    pub async fn wait(&mut self) -> bool {
+        if self.attempt >= self.policy.max_attempts {
+            return false;
+        }
+        let delay = self.policy.delay(self.attempt);
+        let elapsed = now_ms().saturating_sub(self.started_ms);
+        if Duration::from_millis(elapsed) + delay > self.policy.max_elapsed {
+            return false;
+        }
+        sleep(delay).await;
+        self.attempt += 1;
+        true
+    }
+}
+
+// server errors and throttling (S3 SlowDown is returned as a 503)
+pub fn is_retryable_status(status: u16) -> bool {
+    matches!(status, 429 | 500 | 502 | 503 | 504)
+}
+
+// connection resets and timeouts are worth another attempt
+pub fn is_transient_error(err: &LakestreamError) -> bool {
+    match err {
+        LakestreamError::Io(err) => is_transient_io_error(err),
+        #[cfg(not(target_arch = "wasm32"))]
+        LakestreamError::Anyhow(err)
+            if err.chain().any(|cause| cause.is::<ProxyError>()) =>
+        {
+            false
+        }
+        LakestreamError::Anyhow(err) => err.chain().any(|cause| {
+            if let Some(err) = cause.downcast_ref::<std::io::Error>() {
+                return is_transient_io_error(err);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(err) = cause.downcast_ref::<hyper::Error>() {
+                return err.is_connect()
+                    || err.is_timeout()
+                    || err.is_incomplete_message();
+            }
+            false
+        }),
+        _ => false,
+    }
+}
+
+fn is_transient_io_error(err: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+    matches!(
+        err.kind(),
+        ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::ConnectionRefused
+            | ErrorKind::BrokenPipe
+            | ErrorKind::TimedOut
+            | ErrorKind::UnexpectedEof
+    )
+}
+
+// randomness for jitter only, does not need to be of high quality
+fn random_u64() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn now_ms() -> u64 {
+    js_sys::Date::now() as u64
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn sleep(delay: Duration) {
+    tokio::time::sleep(delay).await;
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn sleep(delay: Duration) {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        if let Some(window) = web_sys::window() {
+            let _ = window
+                .set_timeout_with_callback_and_timeout_and_arguments_0(
+                    &resolve,
+                    delay.as_millis() as i32,
+                );
+        }
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_cap() {
+        let policy = RetryPolicy::new(
+            5,
+            Duration::from_millis(100),
+            Duration::from_millis(1000),
+            Duration::from_secs(10),
+        );
+        assert_eq!(policy.delay_cap(1), Duration::from_millis(100));
+        assert_eq!(policy.delay_cap(2), Duration::from_millis(200));
+        assert_eq!(policy.delay_cap(4), Duration::from_millis(800));
+        assert_eq!(policy.delay_cap(5), Duration::from_millis(1000));
+        assert_eq!(policy.delay_cap(64), Duration::from_millis(1000));
+        for retry in 1..10 {
+            assert!(policy.delay(retry) <= policy.delay_cap(retry));
+        }
+    }
+
+    #[test]
+    fn test_transient_errors() {
+        assert!(is_retryable_status(503));
+        assert!(!is_retryable_status(404));
+        let reset = std::io::Error::from(std::io::ErrorKind::ConnectionReset);
+        assert!(is_transient_error(&LakestreamError::Io(reset)));
+        let not_found = std::io::Error::from(std::io::ErrorKind::NotFound);
+        assert!(is_transient_error(&LakestreamError::Anyhow(
+            anyhow::Error::from(std::io::Error::from(
+                std::io::ErrorKind::TimedOut
+            ))
+        )));
+        assert!(!is_transient_error(&LakestreamError::Io(not_found)));
+    }
+}
@@ -9,18 +9,26 @@ pub(crate) mod table;
 pub(crate) mod utils;
 
 pub use api::object_store_handler::{ObjectStoreBackend, ObjectStoreHandler};
+pub use base::byte_range::ByteRange;
 pub use base::callback_wrapper::{
     BinaryCallbackWrapper, CallbackItem, CallbackWrapper,
 };
 pub use base::config::EnvironmentConfig;
+pub use base::delete_result::DeleteResult;
 pub use base::file_object::FileObject;
 pub use base::filters::FileObjectFilter;
-pub use base::object_store::{ObjectStore, ObjectStoreTrait};
+pub use base::list_page::{ListPage, ListStart};
+pub use base::object_metadata::ObjectMetadata;
+pub use base::object_store::{
+    ByteStream, FileObjectStream, ObjectStore, ObjectStoreTrait,
+};
 // re-export all defaults
 pub use default::*;
-pub use error::LakestreamError;
+pub use error::{LakestreamError, S3ErrorResponse};
 pub use table::{
     FileObjectTable, ObjectStoreTable, Table, TableCallback, TableColumn,
     TableColumnValue, TableRow,
 };
 pub use utils::formatters;
+pub use utils::time_parse::parse_duration;
+pub use utils::uri_parse::ParsedUri;
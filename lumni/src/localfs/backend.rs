@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 
-pub use super::bucket::LocalFsBucket;
+pub use super::bucket::{remove_empty_dirs, LocalFsBucket};
 use crate::{
     EnvironmentConfig, LakestreamError, ObjectStoreBackend, ObjectStoreTable,
 };
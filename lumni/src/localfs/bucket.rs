@@ -4,12 +4,22 @@ use std::io;
 use std::path::Path;
 
 use async_trait::async_trait;
+use futures::stream;
 
-use super::get::get_object;
+use super::delete::{delete_object, delete_objects};
+use super::get::{get_object, stream_object};
+use super::head::head_object;
 use super::list::list_files;
+use super::list_page::list_page;
+use super::list_stream::list_stream;
+use super::put::{put_object, put_object_stream};
 use crate::base::config::EnvironmentConfig;
 use crate::table::FileObjectTable;
-use crate::{FileObjectFilter, LakestreamError, ObjectStoreTrait};
+use crate::{
+    BinaryCallbackWrapper, ByteRange, ByteStream, DeleteResult,
+    FileObjectFilter, FileObjectStream, LakestreamError, ListPage, ListStart,
+    ObjectMetadata, ObjectStoreTrait,
+};
 
 pub struct LocalFileSystem;
 
@@ -42,6 +52,14 @@ impl LocalFsBucket {
     }
 }
 
+// remove directories that become empty after deleting objects,
+// enabled by setting LOCALFS_REMOVE_EMPTY_DIRS to "true"
+pub fn remove_empty_dirs(config: &EnvironmentConfig) -> bool {
+    config
+        .get("LOCALFS_REMOVE_EMPTY_DIRS")
+        .is_some_and(|value| value.eq_ignore_ascii_case("true"))
+}
+
 #[async_trait(?Send)]
 impl ObjectStoreTrait for LocalFsBucket {
     fn name(&self) -> &str {
@@ -77,21 +95,111 @@ impl ObjectStoreTrait for LocalFsBucket {
         Ok(())
     }
 
+    async fn list_page(
+        &self,
+        prefix: Option<&str>,
+        recursive: bool,
+        max_keys: Option<u32>,
+        start: Option<&ListStart>,
+        filter: &Option<FileObjectFilter>,
+    ) -> Result<ListPage, LakestreamError> {
+        let path = match prefix {
+            Some(prefix) => Path::new(&self.name).join(prefix),
+            None => Path::new(&self.name).to_path_buf(),
+        };
+
+        // to be considered a Bucket, path must be a directory
+        if !path.is_dir() {
+            return Err(LakestreamError::NoBucketInUri(
+                path.to_string_lossy().to_string(),
+            ));
+        }
+        list_page(&path, recursive, max_keys, start, filter).await
+    }
+
+    fn list_stream(
+        &self,
+        prefix: Option<&str>,
+        recursive: bool,
+        max_keys: Option<u32>,
+        filter: &Option<FileObjectFilter>,
+    ) -> FileObjectStream<'static> {
+        let path = match prefix {
+            Some(prefix) => Path::new(&self.name).join(prefix),
+            None => Path::new(&self.name).to_path_buf(),
+        };
+
+        // to be considered a Bucket, path must be a directory
+        if !path.is_dir() {
+            let err = LakestreamError::NoBucketInUri(
+                path.to_string_lossy().to_string(),
+            );
+            return Box::pin(stream::once(async { Err(err) }));
+        }
+        list_stream(&path, recursive, max_keys, filter)
+    }
+
     async fn get_object(
         &self,
         key: &str,
+        range: Option<&ByteRange>,
         data: &mut Vec<u8>,
     ) -> Result<(), LakestreamError> {
         let path = Path::new(&self.name);
-        get_object(path, key, data).await
+        get_object(path, key, range, data).await
+    }
+
+    async fn stream_object(
+        &self,
+        key: &str,
+        range: Option<&ByteRange>,
+        callback: &BinaryCallbackWrapper,
+    ) -> Result<(), LakestreamError> {
+        let path = Path::new(&self.name);
+        stream_object(path, key, range, callback).await
     }
 
     async fn head_object(
         &self,
-        _key: &str,
-    ) -> Result<(u16, HashMap<String, String>), LakestreamError> {
-        return Err(LakestreamError::InternalError(
-            "Not implemented".to_string(),
-        ));
+        key: &str,
+    ) -> Result<ObjectMetadata, LakestreamError> {
+        let path = Path::new(&self.name);
+        head_object(path, key).await
+    }
+
+    async fn put_object(
+        &self,
+        key: &str,
+        data: &[u8],
+        _metadata: Option<&HashMap<String, String>>,
+    ) -> Result<(), LakestreamError> {
+        // metadata is not (yet) persisted on the local filesystem
+        let path = Path::new(&self.name);
+        put_object(path, key, data).await
+    }
+
+    async fn put_object_stream(
+        &self,
+        key: &str,
+        data: ByteStream<'_>,
+        _size: Option<u64>,
+        _metadata: Option<&HashMap<String, String>>,
+    ) -> Result<(), LakestreamError> {
+        // metadata is not (yet) persisted on the local filesystem
+        let path = Path::new(&self.name);
+        put_object_stream(path, key, data).await
+    }
+
+    async fn delete_object(&self, key: &str) -> Result<(), LakestreamError> {
+        let path = Path::new(&self.name);
+        delete_object(path, key, remove_empty_dirs(&self.config)).await
+    }
+
+    async fn delete_objects(
+        &self,
+        keys: &[String],
+    ) -> Result<Vec<DeleteResult>, LakestreamError> {
+        let path = Path::new(&self.name);
+        delete_objects(path, keys, remove_empty_dirs(&self.config)).await
     }
 }
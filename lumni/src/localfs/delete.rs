@@ -0,0 +1,67 @@
+// localfs/delete.rs
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use crate::{DeleteResult, LakestreamError};
+
+pub async fn delete_object(
+    path: &Path,
+    key: &str,
+    remove_empty_dirs: bool,
+) -> Result<(), LakestreamError> {
+    let object_path = path.join(key);
+
+    if object_path.is_dir() {
+        return Err(LakestreamError::InternalError(format!(
+            "Cannot delete object {}: path is a directory",
+            object_path.display()
+        )));
+    }
+
+    // similar to S3, deleting a non-existing object is not an error
+    match fs::remove_file(&object_path) {
+        Ok(_) => {}
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(err) => {
+            return Err(LakestreamError::InternalError(format!(
+                "Failed to delete file {}: {}",
+                object_path.display(),
+                err
+            )))
+        }
+    }
+
+    if remove_empty_dirs {
+        // remove parent directories that became empty, up to the bucket
+        let mut parent = object_path.parent();
+        while let Some(dir) = parent {
+            if dir == path || !dir.starts_with(path) {
+                break;
+            }
+            // remove_dir fails on directories that are not empty
+            if fs::remove_dir(dir).is_err() {
+                break;
+            }
+            parent = dir.parent();
+        }
+    }
+    Ok(())
+}
+
+pub async fn delete_objects(
+    path: &Path,
+    keys: &[String],
+    remove_empty_dirs: bool,
+) -> Result<Vec<DeleteResult>, LakestreamError> {
+    let mut results = Vec::with_capacity(keys.len());
+    for key in keys {
+        let result = match delete_object(path, key, remove_empty_dirs).await {
+            Ok(_) => DeleteResult::deleted(key),
+            Err(err) => DeleteResult::failed(key, &err.to_string()),
+        };
+        results.push(result);
+    }
+    Ok(results)
+}
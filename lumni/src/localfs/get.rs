@@ -1,40 +1,95 @@
 // localfs/get.rs
 
 use std::fs;
-use std::io::Read;
+use std::io::{Read, Seek, SeekFrom};
 use std::path::Path;
 
-use crate::LakestreamError;
+use crate::{BinaryCallbackWrapper, ByteRange, LakestreamError};
+
+// size of the buffer used to read a file in chunks
+const READ_CHUNK_SIZE: usize = 64 * 1024;
 
 pub async fn get_object(
     path: &Path,
     key: &str,
+    range: Option<&ByteRange>,
     data: &mut Vec<u8>,
 ) -> Result<(), LakestreamError> {
     let object_path = path.join(key);
+    let mut reader = open_object(&object_path, key, range)?;
 
-    if object_path.is_file() {
-        let mut file = fs::File::open(&object_path).map_err(|err| {
-            LakestreamError::InternalError(format!(
-                "Failed to open file {}: {}",
-                object_path.display(),
-                err
-            ))
-        })?;
+    reader.read_to_end(data).map_err(|err| {
+        LakestreamError::InternalError(format!(
+            "Failed to read file {}: {}",
+            object_path.display(),
+            err
+        ))
+    })?;
 
-        file.read_to_end(data).map_err(|err| {
+    Ok(())
+}
+
+pub async fn stream_object(
+    path: &Path,
+    key: &str,
+    range: Option<&ByteRange>,
+    callback: &BinaryCallbackWrapper,
+) -> Result<(), LakestreamError> {
+    let object_path = path.join(key);
+    let mut reader = open_object(&object_path, key, range)?;
+
+    let mut buffer = vec![0u8; READ_CHUNK_SIZE];
+    loop {
+        let bytes_read = reader.read(&mut buffer).map_err(|err| {
             LakestreamError::InternalError(format!(
                 "Failed to read file {}: {}",
                 object_path.display(),
                 err
             ))
         })?;
+        if bytes_read == 0 {
+            break;
+        }
+        callback.call(buffer[..bytes_read].to_vec()).await?;
+    }
+    Ok(())
+}
 
-        Ok(())
-    } else {
-        Err(LakestreamError::NotFound(format!(
+// open the file and position it at the start of the (optional) range,
+// the returned reader stops at the end of the range
+fn open_object(
+    object_path: &Path,
+    key: &str,
+    range: Option<&ByteRange>,
+) -> Result<std::io::Take<fs::File>, LakestreamError> {
+    if !object_path.is_file() {
+        return Err(LakestreamError::NotFound(format!(
             "Object not found for key: {}",
             key
-        )))
+        )));
+    }
+
+    let mut file = fs::File::open(object_path).map_err(|err| {
+        LakestreamError::InternalError(format!(
+            "Failed to open file {}: {}",
+            object_path.display(),
+            err
+        ))
+    })?;
+
+    let file_size = file.metadata()?.len();
+    let (start, length) = match range {
+        Some(range) => range.resolve(file_size).ok_or_else(|| {
+            LakestreamError::String(format!(
+                "Requested range not satisfiable for key: {}",
+                key
+            ))
+        })?,
+        None => (0, file_size),
+    };
+
+    if start > 0 {
+        file.seek(SeekFrom::Start(start))?;
     }
+    Ok(file.take(length))
 }
@@ -0,0 +1,78 @@
+// localfs/head.rs
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::{LakestreamError, ObjectMetadata};
+
+pub async fn head_object(
+    path: &Path,
+    key: &str,
+) -> Result<ObjectMetadata, LakestreamError> {
+    let object_path = path.join(key);
+
+    // directories are not objects, similar to prefixes on S3
+    let metadata = match fs::metadata(&object_path) {
+        Ok(metadata) if metadata.is_file() => metadata,
+        _ => {
+            return Err(LakestreamError::NotFound(format!(
+                "Object not found for key: {}",
+                key
+            )))
+        }
+    };
+
+    let mtime = metadata
+        .modified()
+        .ok()
+        .and_then(|mtime| mtime.duration_since(std::time::UNIX_EPOCH).ok())
+        .unwrap_or_default();
+
+    // like a web server, the ETag is derived from the modification time
+    // and size instead of the contents, which are not read. It changes
+    // with the file, but can not be compared with an S3 ETag
+    let etag = format!("{:x}-{:x}", mtime.as_nanos(), metadata.len());
+
+    Ok(ObjectMetadata::new(
+        metadata.len(),
+        Some(mtime.as_secs()),
+        Some(etag),
+        Some(content_type(&object_path).to_string()),
+        HashMap::new(),
+    ))
+}
+
+// content type guessed from the file extension, files of an unknown type
+// are binary data as on S3
+fn content_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "txt" | "log" => "text/plain",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "md" => "text/markdown",
+        "js" => "text/javascript",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "yaml" | "yml" => "application/yaml",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "tar" => "application/x-tar",
+        "parquet" => "application/vnd.apache.parquet",
+        "wasm" => "application/wasm",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "mp3" => "audio/mpeg",
+        "mp4" => "video/mp4",
+        _ => "application/octet-stream",
+    }
+}
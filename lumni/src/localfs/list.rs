@@ -1,7 +1,11 @@
 use std::collections::HashMap;
 use std::fs;
+use std::io::{self, Read};
 use std::path::Path;
 
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
 use super::bucket::{FileSystem, LocalFileSystem};
 use crate::table::{FileObjectTable, TableColumnValue};
 use crate::{FileObject, FileObjectFilter};
@@ -30,7 +34,6 @@ async fn list_files_next(
     let mut directory_stack = vec![path.to_owned()];
     let mut object_count = 0usize;
 
-    println!("Selected columns: {:?}", selected_columns);
     while let Some(current_path) = directory_stack.pop() {
         let mut temp_rows = Vec::new();
 
@@ -117,6 +120,14 @@ fn handle_directory(
             TableColumnValue::OptionalUint64Column(None),
         );
     }
+    for column in ["etag", "md5", "sha256"] {
+        if is_selected(selected_columns, column) {
+            dir_row_data.insert(
+                column.to_string(),
+                TableColumnValue::OptionalStringColumn(None),
+            );
+        }
+    }
 
     if dir_row_data.is_empty() {
         None
@@ -178,6 +189,33 @@ fn handle_file(
             TableColumnValue::OptionalUint64Column(modified),
         );
     }
+    if is_selected(selected_columns, "etag") {
+        row_data.insert(
+            "etag".to_string(),
+            TableColumnValue::OptionalStringColumn(None),
+        );
+    }
+    let with_md5 = is_selected(selected_columns, "md5");
+    let with_sha256 = is_selected(selected_columns, "sha256");
+    if with_md5 || with_sha256 {
+        // a file that can not be read is listed without digests
+        let (md5, sha256) = match file_digests(&entry.path()) {
+            Ok((md5, sha256)) => (Some(md5), Some(sha256)),
+            Err(_) => (None, None),
+        };
+        if with_md5 {
+            row_data.insert(
+                "md5".to_string(),
+                TableColumnValue::OptionalStringColumn(md5),
+            );
+        }
+        if with_sha256 {
+            row_data.insert(
+                "sha256".to_string(),
+                TableColumnValue::OptionalStringColumn(sha256),
+            );
+        }
+    }
 
     if row_data.is_empty() {
         None
@@ -185,3 +223,27 @@ fn handle_file(
         Some(row_data)
     }
 }
+
+// digest columns are only added when explicitly selected
+fn is_selected(selected_columns: &Option<Vec<&str>>, column: &str) -> bool {
+    selected_columns
+        .as_ref()
+        .is_some_and(|cols| cols.contains(&column))
+}
+
+// hex encoded MD5 and SHA-256 digests, reading the file once
+fn file_digests(path: &Path) -> io::Result<(String, String)> {
+    let mut file = fs::File::open(path)?;
+    let mut md5 = Md5::new();
+    let mut sha256 = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let count = file.read(&mut buffer)?;
+        if count == 0 {
+            break;
+        }
+        md5.update(&buffer[..count]);
+        sha256.update(&buffer[..count]);
+    }
+    Ok((hex::encode(md5.finalize()), hex::encode(sha256.finalize())))
+}
@@ -0,0 +1,138 @@
+// localfs/list_page.rs
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    FileObject, FileObjectFilter, LakestreamError, ListPage, ListStart,
+    AWS_MAX_LIST_OBJECTS,
+};
+
+// list a single page of files, ordered by path. Both the next_token and a
+// start_after key are paths relative to the listed directory
+pub async fn list_page(
+    path: &Path,
+    recursive: bool,
+    max_keys: Option<u32>,
+    start: Option<&ListStart>,
+    filter: &Option<FileObjectFilter>,
+) -> Result<ListPage, LakestreamError> {
+    let page_size = max_keys
+        .unwrap_or(AWS_MAX_LIST_OBJECTS)
+        .min(AWS_MAX_LIST_OBJECTS) as usize;
+    let mut collector = PageCollector {
+        cursor: start.map(|start| match start {
+            ListStart::Token(key) | ListStart::After(key) => PathBuf::from(key),
+        }),
+        recursive,
+        filter,
+        // collect one more than requested to know if there is a next page
+        limit: page_size + 1,
+        rows: Vec::new(),
+    };
+    collector.collect(path, Path::new(""))?;
+
+    let mut rows = collector.rows;
+    let next_token = if rows.len() > page_size {
+        rows.truncate(page_size);
+        rows.last()
+            .map(|(key, _)| key.to_string_lossy().to_string())
+    } else {
+        None
+    };
+    Ok(ListPage::new(
+        rows.into_iter()
+            .map(|(_, file_object)| file_object)
+            .collect(),
+        next_token,
+    ))
+}
+
+struct PageCollector<'a> {
+    cursor: Option<PathBuf>,
+    recursive: bool,
+    filter: &'a Option<FileObjectFilter>,
+    limit: usize,
+    rows: Vec<(PathBuf, FileObject)>, // (relative key, file object)
+}
+
+impl PageCollector<'_> {
+    fn after_cursor(&self, key: &Path) -> bool {
+        self.cursor.as_deref().is_none_or(|cursor| key > cursor)
+    }
+
+    // walk the directory in sorted order, recursing into subdirectories
+    // so that keys are listed in the same order as they are compared
+    fn collect(
+        &mut self,
+        directory: &Path,
+        relative: &Path,
+    ) -> Result<(), LakestreamError> {
+        let mut entries = fs::read_dir(directory)
+            .map_err(|err| {
+                LakestreamError::InternalError(format!(
+                    "Failed to read directory {}: {}",
+                    directory.display(),
+                    err
+                ))
+            })?
+            .flatten()
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for entry in entries {
+            if self.rows.len() >= self.limit {
+                break;
+            }
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+            let key = relative.join(entry.file_name());
+
+            if metadata.is_file() {
+                if !self.after_cursor(&key) {
+                    continue;
+                }
+                let modified = metadata.modified().ok().map(|mtime| {
+                    mtime
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .map(|duration| duration.as_secs())
+                        .unwrap_or(0)
+                });
+                let file_object = FileObject::new(
+                    entry.path().to_string_lossy().to_string(),
+                    metadata.len(),
+                    modified,
+                    None,
+                );
+                if self
+                    .filter
+                    .as_ref()
+                    .is_none_or(|filter| filter.matches(&file_object))
+                {
+                    self.rows.push((key, file_object));
+                }
+            } else if metadata.is_dir() {
+                if self.recursive {
+                    // directories that sort before the cursor are done
+                    let listed = self.cursor.as_deref().is_some_and(|cursor| {
+                        key.as_path() < cursor && !cursor.starts_with(&key)
+                    });
+                    if !listed {
+                        self.collect(&entry.path(), &key)?;
+                    }
+                } else if self.filter.is_none() && self.after_cursor(&key) {
+                    let file_object = FileObject::new(
+                        format!("{}/", entry.path().to_string_lossy()),
+                        0,
+                        None,
+                        None,
+                    );
+                    self.rows.push((key, file_object));
+                }
+            }
+        }
+        Ok(())
+    }
+}
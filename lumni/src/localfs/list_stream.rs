@@ -0,0 +1,107 @@
+// localfs/list_stream.rs
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use futures::stream::{self, StreamExt};
+
+use crate::{FileObject, FileObjectFilter, FileObjectStream, LakestreamError};
+
+type Page = Vec<Result<FileObject, LakestreamError>>;
+
+pub fn list_stream(
+    path: &Path,
+    recursive: bool,
+    max_keys: Option<u32>,
+    filter: &Option<FileObjectFilter>,
+) -> FileObjectStream<'static> {
+    // directories are read one at a time, so only a single directory is
+    // kept in memory
+    let state = (vec![path.to_path_buf()], recursive, filter.clone());
+    let file_objects = stream::unfold(
+        state,
+        |(mut directory_stack, recursive, filter)| async move {
+            while let Some(directory) = directory_stack.pop() {
+                let page = read_directory(
+                    &directory,
+                    recursive,
+                    &filter,
+                    &mut directory_stack,
+                );
+                if !page.is_empty() {
+                    return Some((page, (directory_stack, recursive, filter)));
+                }
+            }
+            None
+        },
+    )
+    .flat_map(stream::iter);
+
+    match max_keys {
+        Some(max_keys) => Box::pin(file_objects.take(max_keys as usize)),
+        None => Box::pin(file_objects),
+    }
+}
+
+fn read_directory(
+    directory: &Path,
+    recursive: bool,
+    filter: &Option<FileObjectFilter>,
+    directory_stack: &mut Vec<PathBuf>,
+) -> Page {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(err) => {
+            return vec![Err(LakestreamError::InternalError(format!(
+                "Failed to read directory {}: {}",
+                directory.display(),
+                err
+            )))]
+        }
+    };
+
+    let mut page = Vec::new();
+    for entry in entries.flatten() {
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+        let entry_path = entry.path();
+
+        if metadata.is_file() {
+            let modified = metadata.modified().ok().map(|mtime| {
+                mtime
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|duration| duration.as_secs())
+                    .unwrap_or(0)
+            });
+            let file_object = FileObject::new(
+                entry_path.to_string_lossy().to_string(),
+                metadata.len(),
+                modified,
+                None,
+            );
+            if filter
+                .as_ref()
+                .is_none_or(|filter| filter.matches(&file_object))
+            {
+                page.push(Ok(file_object));
+            }
+        } else if metadata.is_dir() {
+            // directories are listed with a trailing "/", similar to S3
+            // prefixes, and only when no filter is provided
+            if filter.is_none() {
+                page.push(Ok(FileObject::new(
+                    format!("{}/", entry_path.to_string_lossy()),
+                    0,
+                    None,
+                    None,
+                )));
+            }
+            if recursive {
+                directory_stack.push(entry_path);
+            }
+        }
+    }
+    page
+}
@@ -1,5 +1,10 @@
 // expose to library via backend mod
 pub mod backend;
 mod bucket;
+mod delete;
 mod get;
+mod head;
 mod list;
+mod list_page;
+mod list_stream;
+mod put;
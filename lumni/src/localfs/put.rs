@@ -0,0 +1,90 @@
+// localfs/put.rs
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use futures::TryStreamExt;
+
+use crate::{ByteStream, LakestreamError};
+
+pub async fn put_object(
+    path: &Path,
+    key: &str,
+    data: &[u8],
+) -> Result<(), LakestreamError> {
+    let (object_path, mut file) = create_object_file(path, key)?;
+    write_chunk(&object_path, &mut file, data)
+}
+
+// chunks are written as they arrive, a partially written file is removed
+// when the stream fails
+pub async fn put_object_stream(
+    path: &Path,
+    key: &str,
+    mut data: ByteStream<'_>,
+) -> Result<(), LakestreamError> {
+    let (object_path, mut file) = create_object_file(path, key)?;
+    loop {
+        let result = match data.try_next().await {
+            Ok(Some(chunk)) => write_chunk(&object_path, &mut file, &chunk),
+            Ok(None) => return Ok(()),
+            Err(err) => Err(err),
+        };
+        if let Err(err) = result {
+            drop(file);
+            fs::remove_file(&object_path).ok();
+            return Err(err);
+        }
+    }
+}
+
+fn create_object_file(
+    path: &Path,
+    key: &str,
+) -> Result<(PathBuf, fs::File), LakestreamError> {
+    let object_path = path.join(key);
+
+    if object_path.is_dir() {
+        return Err(LakestreamError::InternalError(format!(
+            "Cannot write object {}: path is a directory",
+            object_path.display()
+        )));
+    }
+
+    // create missing parent directories, similar to how keys with a "/"
+    // implicitly create (virtual) directories on an object store
+    if let Some(parent) = object_path.parent() {
+        fs::create_dir_all(parent).map_err(|err| {
+            LakestreamError::InternalError(format!(
+                "Failed to create directory {}: {}",
+                parent.display(),
+                err
+            ))
+        })?;
+    }
+
+    let file = fs::File::create(&object_path).map_err(|err| {
+        LakestreamError::InternalError(format!(
+            "Failed to create file {}: {}",
+            object_path.display(),
+            err
+        ))
+    })?;
+
+    Ok((object_path, file))
+}
+
+fn write_chunk(
+    object_path: &Path,
+    file: &mut fs::File,
+    data: &[u8],
+) -> Result<(), LakestreamError> {
+    file.write_all(data).map_err(|err| {
+        LakestreamError::InternalError(format!(
+            "Failed to write file {}: {}",
+            object_path.display(),
+            err
+        ))
+    })
+}
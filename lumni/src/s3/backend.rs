@@ -4,6 +4,7 @@ use log::error;
 pub use super::bucket::S3Bucket;
 pub use super::config::validate_config;
 pub use super::list::list_buckets;
+pub use super::presign::presign_url;
 use crate::{
     EnvironmentConfig, LakestreamError, ObjectStoreBackend, ObjectStoreTable,
 };
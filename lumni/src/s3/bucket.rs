@@ -1,20 +1,31 @@
 use std::collections::HashMap;
 
 use async_trait::async_trait;
+use url::Url;
 
-use super::get::get_object;
+use super::delete::{delete_object, delete_objects};
+use super::get::{get_object, stream_object};
 use super::head::head_object;
 use super::list::list_files;
+use super::list_page::list_page;
+use super::list_stream::list_stream;
+use super::provider::AddressingStyle;
+use super::put::{put_object, put_object_stream};
 use crate::base::config::EnvironmentConfig;
-use crate::s3::config::validate_config;
+use crate::s3::config::{addressing_style, validate_config};
 use crate::table::FileObjectTable;
-use crate::{FileObjectFilter, LakestreamError, ObjectStoreTrait};
+use crate::{
+    BinaryCallbackWrapper, ByteRange, ByteStream, DeleteResult,
+    FileObjectFilter, FileObjectStream, LakestreamError, ListPage, ListStart,
+    ObjectMetadata, ObjectStoreTrait,
+};
 
 #[derive(Clone)]
 pub struct S3Credentials {
     access_key: String,
     secret_key: String,
     session_token: Option<String>,
+    expiration: Option<u64>,
 }
 
 impl S3Credentials {
@@ -27,9 +38,16 @@ impl S3Credentials {
             access_key,
             secret_key,
             session_token,
+            expiration: None,
         }
     }
 
+    // temporary credentials expire at the given time (epoch seconds)
+    pub fn with_expiration(mut self, expiration: Option<u64>) -> Self {
+        self.expiration = expiration;
+        self
+    }
+
     pub fn access_key(&self) -> &str {
         &self.access_key
     }
@@ -41,6 +59,10 @@ impl S3Credentials {
     pub fn session_token(&self) -> Option<&str> {
         self.session_token.as_deref()
     }
+
+    pub fn expiration(&self) -> Option<u64> {
+        self.expiration
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -71,8 +93,28 @@ impl S3Bucket {
         let endpoint_url =
             self.config.get("S3_ENDPOINT_URL").map(String::as_str);
         let name = Some(self.name().to_string());
+        // validated in validate_config
+        let addressing_style =
+            addressing_style(&self.config).unwrap_or_default();
+
+        configure_bucket_url(
+            region,
+            endpoint_url,
+            name.as_deref(),
+            addressing_style,
+        )
+    }
 
-        configure_bucket_url(region, endpoint_url, name.as_deref())
+    // a prefix should not exist as a file object
+    pub async fn check_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<(), LakestreamError> {
+        match self.head_object(prefix.trim_end_matches('/')).await {
+            Err(LakestreamError::NotFound(_)) => Ok(()),
+            Ok(_) => Err(LakestreamError::NoBucketInUri(prefix.to_string())),
+            Err(err) => Err(err),
+        }
     }
 }
 
@@ -95,12 +137,7 @@ impl ObjectStoreTrait for S3Bucket {
         table: &mut FileObjectTable,
     ) -> Result<(), LakestreamError> {
         if let Some(prefix) = prefix {
-            // prefix should not exist as a file object
-            let (status_code, _response_headers) =
-                self.head_object(prefix.trim_end_matches("/")).await?;
-            if status_code != 404 {
-                return Err(LakestreamError::NoBucketInUri(prefix.to_string()));
-            }
+            self.check_prefix(prefix).await?;
         }
         list_files(
             self,
@@ -114,37 +151,111 @@ impl ObjectStoreTrait for S3Bucket {
         .await
     }
 
+    async fn list_page(
+        &self,
+        prefix: Option<&str>,
+        recursive: bool,
+        max_keys: Option<u32>,
+        start: Option<&ListStart>,
+        filter: &Option<FileObjectFilter>,
+    ) -> Result<ListPage, LakestreamError> {
+        list_page(self, prefix, recursive, max_keys, start, filter).await
+    }
+
+    fn list_stream(
+        &self,
+        prefix: Option<&str>,
+        recursive: bool,
+        max_keys: Option<u32>,
+        filter: &Option<FileObjectFilter>,
+    ) -> FileObjectStream<'static> {
+        list_stream(self, prefix, recursive, max_keys, filter)
+    }
+
     async fn get_object(
         &self,
         key: &str,
+        range: Option<&ByteRange>,
         data: &mut Vec<u8>,
     ) -> Result<(), LakestreamError> {
-        get_object(self, key, data).await
+        get_object(self, key, range, data).await
+    }
+
+    async fn stream_object(
+        &self,
+        key: &str,
+        range: Option<&ByteRange>,
+        callback: &BinaryCallbackWrapper,
+    ) -> Result<(), LakestreamError> {
+        stream_object(self, key, range, callback).await
     }
 
     async fn head_object(
         &self,
         key: &str,
-    ) -> Result<(u16, HashMap<String, String>), LakestreamError> {
+    ) -> Result<ObjectMetadata, LakestreamError> {
         head_object(self, key).await
     }
+
+    async fn put_object(
+        &self,
+        key: &str,
+        data: &[u8],
+        metadata: Option<&HashMap<String, String>>,
+    ) -> Result<(), LakestreamError> {
+        put_object(self, key, data, metadata).await
+    }
+
+    async fn put_object_stream(
+        &self,
+        key: &str,
+        data: ByteStream<'_>,
+        size: Option<u64>,
+        metadata: Option<&HashMap<String, String>>,
+    ) -> Result<(), LakestreamError> {
+        put_object_stream(self, key, data, size, metadata).await
+    }
+
+    async fn delete_object(&self, key: &str) -> Result<(), LakestreamError> {
+        delete_object(self, key).await
+    }
+
+    async fn delete_objects(
+        &self,
+        keys: &[String],
+    ) -> Result<Vec<DeleteResult>, LakestreamError> {
+        delete_objects(self, keys).await
+    }
 }
 
+// url of the bucket (or of the service when bucket_name is None), the
+// bucket is either part of the host name (virtual) or of the path (path)
 pub fn configure_bucket_url(
     region: &str,
     endpoint_url: Option<&str>,
     bucket_name: Option<&str>,
+    addressing_style: AddressingStyle,
 ) -> String {
-    match endpoint_url {
-        Some(url) => match bucket_name {
-            Some(name) => format!("{}/{}", url.trim_end_matches('/'), name),
-            None => url.to_owned(),
-        },
-        None => match bucket_name {
-            Some(name) => {
-                format!("https://{}.s3.{}.amazonaws.com", name, region)
+    let service_url = match endpoint_url {
+        Some(url) => url.trim_end_matches('/').to_owned(),
+        None => format!("https://s3.{}.amazonaws.com", region),
+    };
+    let name = match bucket_name {
+        Some(name) => name,
+        None => return service_url,
+    };
+    match addressing_style.resolve(endpoint_url, name) {
+        AddressingStyle::Virtual => match Url::parse(&service_url) {
+            Ok(mut url) => {
+                let host = url.host_str().unwrap_or_default().to_owned();
+                if url.set_host(Some(&format!("{}.{}", name, host))).is_ok() {
+                    url.as_str().trim_end_matches('/').to_owned()
+                } else {
+                    format!("{}/{}", service_url, name)
+                }
             }
-            None => format!("https://s3.{}.amazonaws.com", region),
+            Err(_) => format!("{}/{}", service_url, name),
         },
+        _ => format!("{}/{}", service_url, name),
     }
 }
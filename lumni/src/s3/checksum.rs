@@ -0,0 +1,371 @@
+use std::fmt;
+use std::str::FromStr;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+// Checksum sent along with an upload, S3 rejects the upload when it does
+// not match the data it received
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+    Md5,
+    Sha256,
+    Crc32c,
+}
+
+impl ChecksumAlgorithm {
+    pub fn header_name(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Md5 => "content-md5",
+            ChecksumAlgorithm::Sha256 => "x-amz-checksum-sha256",
+            ChecksumAlgorithm::Crc32c => "x-amz-checksum-crc32c",
+        }
+    }
+
+    // base64 encoded checksum, as sent in the header
+    pub fn checksum(&self, data: &[u8]) -> String {
+        match self {
+            ChecksumAlgorithm::Md5 => STANDARD.encode(Md5::digest(data)),
+            ChecksumAlgorithm::Sha256 => STANDARD.encode(Sha256::digest(data)),
+            ChecksumAlgorithm::Crc32c => {
+                STANDARD.encode(crc32c(data).to_be_bytes())
+            }
+        }
+    }
+
+    // name of the additional checksum (x-amz-checksum-algorithm), used for
+    // multipart uploads. Content-MD5 is checked per request instead
+    pub fn additional_checksum(&self) -> Option<&'static str> {
+        match self {
+            ChecksumAlgorithm::Md5 => None,
+            ChecksumAlgorithm::Sha256 => Some("SHA256"),
+            ChecksumAlgorithm::Crc32c => Some("CRC32C"),
+        }
+    }
+}
+
+impl FromStr for ChecksumAlgorithm {
+    type Err = String;
+
+    fn from_str(algorithm: &str) -> Result<Self, Self::Err> {
+        match algorithm.trim().to_ascii_lowercase().as_str() {
+            "md5" | "content-md5" => Ok(ChecksumAlgorithm::Md5),
+            "sha256" => Ok(ChecksumAlgorithm::Sha256),
+            "crc32c" => Ok(ChecksumAlgorithm::Crc32c),
+            _ => Err(format!(
+                "Unsupported checksum algorithm: {}, expected md5, sha256 \
+                 or crc32c",
+                algorithm
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ChecksumAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let algorithm = match self {
+            ChecksumAlgorithm::Md5 => "md5",
+            ChecksumAlgorithm::Sha256 => "sha256",
+            ChecksumAlgorithm::Crc32c => "crc32c",
+        };
+        write!(f, "{}", algorithm)
+    }
+}
+
+// Checksum of the data of an upload (or part)
+#[derive(Debug, Clone)]
+pub struct Checksum {
+    algorithm: ChecksumAlgorithm,
+    value: String,
+}
+
+impl Checksum {
+    pub fn new(algorithm: ChecksumAlgorithm, data: &[u8]) -> Self {
+        Checksum {
+            algorithm,
+            value: algorithm.checksum(data),
+        }
+    }
+
+    pub fn algorithm(&self) -> ChecksumAlgorithm {
+        self.algorithm
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+// CRC-32C (Castagnoli), reflected polynomial
+const CRC32C_TABLE: [u32; 256] = crc32c_table();
+
+const fn crc32c_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut crc = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0x82f6_3b78
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+    table
+}
+
+pub fn crc32c(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, byte| {
+        CRC32C_TABLE[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+// Calculates the ETag S3 assigns to an object: the MD5 digest of the data,
+// or for a multipart upload the MD5 digest of the part digests followed by
+// "-<number of parts>". The part size is not stored with the object, so
+// the digests are calculated for each of the candidate part sizes
+pub struct EtagHasher {
+    object: Md5,
+    parts: Vec<PartHasher>,
+}
+
+struct PartHasher {
+    part_size: u64,
+    filled: u64,
+    current: Md5,
+    digests: Vec<u8>,
+    count: u64,
+}
+
+impl PartHasher {
+    fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let remaining = (self.part_size - self.filled) as usize;
+            let (chunk, rest) = data.split_at(remaining.min(data.len()));
+            self.current.update(chunk);
+            self.filled += chunk.len() as u64;
+            if self.filled == self.part_size {
+                self.finish_part();
+            }
+            data = rest;
+        }
+    }
+
+    fn finish_part(&mut self) {
+        let current = std::mem::take(&mut self.current);
+        self.digests.extend_from_slice(&current.finalize());
+        self.filled = 0;
+        self.count += 1;
+    }
+
+    fn etag(mut self) -> (u64, String) {
+        if self.filled > 0 {
+            self.finish_part();
+        }
+        (self.count, hex::encode(Md5::digest(&self.digests)))
+    }
+}
+
+impl EtagHasher {
+    pub fn new(part_sizes: &[u64]) -> Self {
+        let mut part_sizes = part_sizes
+            .iter()
+            .copied()
+            .filter(|part_size| *part_size > 0)
+            .collect::<Vec<_>>();
+        part_sizes.sort_unstable();
+        part_sizes.dedup();
+
+        EtagHasher {
+            object: Md5::new(),
+            parts: part_sizes
+                .into_iter()
+                .map(|part_size| PartHasher {
+                    part_size,
+                    filled: 0,
+                    current: Md5::new(),
+                    digests: Vec::new(),
+                    count: 0,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.object.update(data);
+        for part in &mut self.parts {
+            part.update(data);
+        }
+    }
+
+    pub fn verify(self, etag: &str) -> Result<(), String> {
+        let etag = etag.trim_matches('"').to_ascii_lowercase();
+        let (digest, part_count) = match etag.split_once('-') {
+            Some((digest, count)) => {
+                let count = count
+                    .parse::<u64>()
+                    .map_err(|_| format!("Invalid ETag: {}", etag))?;
+                (digest.to_string(), Some(count))
+            }
+            None => (etag.clone(), None),
+        };
+
+        let part_count = match part_count {
+            None => {
+                let object_digest = hex::encode(self.object.finalize());
+                return if object_digest == digest {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "ETag mismatch, expected {} but calculated {}",
+                        etag, object_digest
+                    ))
+                };
+            }
+            Some(part_count) => part_count,
+        };
+
+        let mut calculated = Vec::new();
+        for part in self.parts {
+            let (count, part_digest) = part.etag();
+            if count == part_count && part_digest == digest {
+                return Ok(());
+            }
+            if count == part_count {
+                calculated.push(format!("{}-{}", part_digest, count));
+            }
+        }
+        if calculated.is_empty() {
+            Err(format!(
+                "ETag {} can not be verified, the part size of the \
+                 multipart upload is unknown",
+                etag
+            ))
+        } else {
+            Err(format!(
+                "ETag mismatch, expected {} but calculated {}",
+                etag,
+                calculated.join(" or ")
+            ))
+        }
+    }
+}
+
+// number of parts from the ETag of a multipart upload, 0 otherwise
+pub fn etag_part_count(etag: &str) -> u64 {
+    etag.trim_matches('"')
+        .rsplit_once('-')
+        .and_then(|(_, count)| count.parse::<u64>().ok())
+        .unwrap_or(0)
+}
+
+// candidate part sizes of an object uploaded in part_count parts: the
+// smallest size giving part_count parts, as is and rounded up to whole
+// MiB (as most clients use), and the default part sizes. Only the sizes
+// that give part_count parts are hashed, falling back to the defaults
+// when the size and part count rule out all of them
+pub fn multipart_part_sizes(
+    object_size: u64,
+    part_count: u64,
+    default_part_sizes: &[u64],
+) -> Vec<u64> {
+    const MIB: u64 = 1024 * 1024;
+    if part_count == 0 {
+        // not a multipart upload, only the object digest is verified
+        return Vec::new();
+    }
+    let part_size = object_size.div_ceil(part_count);
+    let mut part_sizes = vec![part_size, part_size.div_ceil(MIB) * MIB];
+    part_sizes.extend_from_slice(default_part_sizes);
+    part_sizes
+        .retain(|size| *size > 0 && object_size.div_ceil(*size) == part_count);
+    if part_sizes.is_empty() {
+        return default_part_sizes.to_vec();
+    }
+    part_sizes.sort_unstable();
+    part_sizes.dedup();
+    part_sizes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksums() {
+        // check value of CRC-32C, RFC 3720
+        assert_eq!(crc32c(b"123456789"), 0xe306_9283);
+        assert_eq!(crc32c(&[0u8; 32]), 0x8a91_36aa);
+
+        assert_eq!(
+            ChecksumAlgorithm::Md5.checksum(b""),
+            "1B2M2Y8AsgTpgAmY7PhCfg=="
+        );
+        assert_eq!(
+            ChecksumAlgorithm::Sha256.checksum(b"abc"),
+            "ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0="
+        );
+        assert_eq!(
+            ChecksumAlgorithm::Crc32c.checksum(b"123456789"),
+            "4waSgw=="
+        );
+        assert!("crc32".parse::<ChecksumAlgorithm>().is_err());
+    }
+
+    #[test]
+    fn test_etag_hasher() {
+        let data = (0..2500u32).map(|i| i as u8).collect::<Vec<u8>>();
+
+        let mut hasher = EtagHasher::new(&[]);
+        hasher.update(&data);
+        let etag = format!("\"{}\"", hex::encode(Md5::digest(&data)));
+        assert!(hasher.verify(&etag).is_ok());
+
+        // multipart: 3 parts of 1000 bytes, fed in uneven chunks
+        let mut digests = Vec::new();
+        for part in data.chunks(1000) {
+            digests.extend_from_slice(&Md5::digest(part));
+        }
+        let etag = format!("\"{}-3\"", hex::encode(Md5::digest(&digests)));
+        assert_eq!(etag_part_count(&etag), 3);
+
+        let mut hasher = EtagHasher::new(&[500, 1000]);
+        for chunk in data.chunks(333) {
+            hasher.update(chunk);
+        }
+        assert!(hasher.verify(&etag).is_ok());
+
+        let mut hasher =
+            EtagHasher::new(&multipart_part_sizes(2500, 3, &[800]));
+        hasher.update(&data[..2499]);
+        assert!(hasher.verify(&etag).unwrap_err().contains("mismatch"));
+
+        // no candidate results in 3 parts
+        let mut hasher = EtagHasher::new(&[2000]);
+        hasher.update(&data);
+        assert!(hasher.verify(&etag).unwrap_err().contains("unknown"));
+    }
+
+    #[test]
+    fn test_multipart_part_sizes() {
+        const MIB: u64 = 1024 * 1024;
+        // only sizes giving the part count of the ETag are hashed
+        assert_eq!(multipart_part_sizes(2500, 3, &[800, 8 * MIB]), [834]);
+        assert_eq!(
+            multipart_part_sizes(20 * MIB, 3, &[5 * MIB, 8 * MIB]),
+            [(20 * MIB).div_ceil(3), 7 * MIB, 8 * MIB]
+        );
+        assert_eq!(multipart_part_sizes(3 * MIB, 3, &[MIB]), [MIB]);
+        // no multipart upload
+        assert!(multipart_part_sizes(2500, 0, &[800]).is_empty());
+        // no size gives 4 parts of 9 bytes, use the defaults
+        assert_eq!(multipart_part_sizes(9, 4, &[2, 8]), [2, 8]);
+    }
+}
@@ -1,5 +1,5 @@
 use super::client_config::S3ClientConfig;
-use super::request_builder::RequestBuilder;
+use super::request_builder::{uri_encode, RequestBuilder};
 
 pub struct S3Client {
     pub resource: Option<String>,
@@ -38,7 +38,7 @@ impl S3Client {
         let mut url = format!(
             "{}/{}",
             self.config.bucket_url(),
-            self.resource.as_ref().unwrap_or(&"".to_string())
+            uri_encode(self.resource.as_deref().unwrap_or_default())
         );
 
         if let Some(query) = self.query_string.as_ref() {
@@ -1,30 +1,67 @@
 use super::bucket::{configure_bucket_url, S3Credentials};
+use super::credentials::SharedCredentialProvider;
+use super::provider::AddressingStyle;
+use crate::http::client_config::HttpClientConfig;
+use crate::http::retry::RetryPolicy;
+use crate::LakestreamError;
 
 #[derive(Clone)]
 pub struct S3ClientConfig {
-    credentials: S3Credentials,
+    // None for anonymous (unsigned) requests
+    credential_provider: Option<SharedCredentialProvider>,
+    // set from the provider before each request, see resolve_credentials
+    credentials: Option<S3Credentials>,
     bucket_name: Option<String>,
     endpoint_url: Option<String>,
     region: String,
+    addressing_style: AddressingStyle,
+    retry_policy: RetryPolicy,
+    http_client_config: HttpClientConfig,
 }
 
 impl S3ClientConfig {
     pub fn new(
-        credentials: S3Credentials,
+        credential_provider: Option<SharedCredentialProvider>,
         bucket_name: Option<&str>,
         endpoint_url: Option<&str>,
         region: &str,
+        addressing_style: AddressingStyle,
+        retry_policy: RetryPolicy,
+        http_client_config: HttpClientConfig,
     ) -> S3ClientConfig {
         S3ClientConfig {
-            credentials,
+            credential_provider,
+            credentials: None,
             bucket_name: bucket_name.map(str::to_string),
             endpoint_url: endpoint_url.map(str::to_string),
             region: region.to_string(),
+            addressing_style,
+            retry_policy,
+            http_client_config,
         }
     }
 
-    pub fn credentials(&self) -> &S3Credentials {
-        &self.credentials
+    pub fn credential_provider(&self) -> Option<&SharedCredentialProvider> {
+        self.credential_provider.as_ref()
+    }
+
+    pub fn is_anonymous(&self) -> bool {
+        self.credential_provider.is_none()
+    }
+
+    pub fn credentials(&self) -> Result<&S3Credentials, LakestreamError> {
+        self.credentials.as_ref().ok_or_else(|| {
+            LakestreamError::String("Credentials are not resolved".to_string())
+        })
+    }
+
+    // get the current credentials from the provider, providers cache
+    // temporary credentials and refresh these before they expire
+    pub async fn resolve_credentials(&mut self) -> Result<(), LakestreamError> {
+        if let Some(provider) = &self.credential_provider {
+            self.credentials = Some(provider.credentials().await?);
+        }
+        Ok(())
     }
 
     pub fn bucket_name(&self) -> Option<&str> {
@@ -39,11 +76,24 @@ impl S3ClientConfig {
         &self.region
     }
 
+    pub fn addressing_style(&self) -> AddressingStyle {
+        self.addressing_style
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    pub fn http_client_config(&self) -> &HttpClientConfig {
+        &self.http_client_config
+    }
+
     pub fn bucket_url(&self) -> String {
         configure_bucket_url(
             self.region(),
             self.endpoint_url.as_deref(),
             self.bucket_name.as_deref(),
+            self.addressing_style,
         )
     }
 }
@@ -2,8 +2,10 @@ use std::collections::HashMap;
 
 use url::form_urlencoded;
 
+use super::checksum::{Checksum, ChecksumAlgorithm};
 use super::client::S3Client;
-use crate::{LakestreamError, AWS_MAX_LIST_OBJECTS};
+use super::request_builder::query_encode;
+use crate::{ByteRange, LakestreamError, AWS_MAX_LIST_OBJECTS};
 
 pub trait Headers {
     fn generate_list_buckets_headers(
@@ -14,20 +16,66 @@ pub trait Headers {
         prefix: Option<&str>,
         max_keys: Option<u32>,
         continuation_token: Option<&str>,
+        start_after: Option<&str>,
+        delimiter: Option<&str>,
     ) -> Result<HashMap<String, String>, LakestreamError>;
     fn generate_get_object_headers(
         &mut self,
         object_key: &str,
+        range: Option<&ByteRange>,
     ) -> Result<HashMap<String, String>, LakestreamError>;
     fn generate_head_object_headers(
         &mut self,
         object_key: &str,
     ) -> Result<HashMap<String, String>, LakestreamError>;
+    fn generate_put_object_headers(
+        &mut self,
+        object_key: &str,
+        payload_hash: &str,
+        metadata: Option<&HashMap<String, String>>,
+        checksum: Option<&Checksum>,
+    ) -> Result<HashMap<String, String>, LakestreamError>;
+    fn generate_delete_object_headers(
+        &mut self,
+        object_key: &str,
+    ) -> Result<HashMap<String, String>, LakestreamError>;
+    fn generate_delete_objects_headers(
+        &mut self,
+        payload_hash: &str,
+        content_md5: &str,
+    ) -> Result<HashMap<String, String>, LakestreamError>;
+    fn generate_create_multipart_upload_headers(
+        &mut self,
+        object_key: &str,
+        metadata: Option<&HashMap<String, String>>,
+        checksum_algorithm: Option<ChecksumAlgorithm>,
+    ) -> Result<HashMap<String, String>, LakestreamError>;
+    fn generate_upload_part_headers(
+        &mut self,
+        object_key: &str,
+        upload_id: &str,
+        part_number: u32,
+        payload_hash: &str,
+        checksum: Option<&Checksum>,
+    ) -> Result<HashMap<String, String>, LakestreamError>;
+    fn generate_complete_multipart_upload_headers(
+        &mut self,
+        object_key: &str,
+        upload_id: &str,
+        payload_hash: &str,
+    ) -> Result<HashMap<String, String>, LakestreamError>;
+    fn generate_abort_multipart_upload_headers(
+        &mut self,
+        object_key: &str,
+        upload_id: &str,
+    ) -> Result<HashMap<String, String>, LakestreamError>;
     fn create_list_objects_query_string(
         &self,
         prefix: Option<&str>,
         max_keys: Option<u32>,
         continuation_token: Option<&str>,
+        start_after: Option<&str>,
+        delimiter: Option<&str>,
     ) -> String;
 }
 
@@ -42,6 +90,7 @@ impl Headers for S3Client {
             None,
             None,
             None,
+            None,
         )
     }
 
@@ -50,12 +99,16 @@ impl Headers for S3Client {
         prefix: Option<&str>,
         max_keys: Option<u32>,
         continuation_token: Option<&str>,
+        start_after: Option<&str>,
+        delimiter: Option<&str>,
     ) -> Result<HashMap<String, String>, LakestreamError> {
         let method = "GET";
         let query_string = Some(self.create_list_objects_query_string(
             prefix,
             max_keys,
             continuation_token,
+            start_after,
+            delimiter,
         ));
 
         self.query_string = query_string.clone();
@@ -65,21 +118,27 @@ impl Headers for S3Client {
             self.resource.as_deref(),
             query_string.as_deref(),
             None,
+            None,
         )
     }
 
     fn generate_get_object_headers(
         &mut self,
         object_key: &str,
+        range: Option<&ByteRange>,
     ) -> Result<HashMap<String, String>, LakestreamError> {
         self.resource = Some(object_key.to_string());
         let method = "GET";
+        let range_headers = range.map(|range| {
+            HashMap::from([("range".to_string(), range.to_header_value())])
+        });
         self.request_builder.generate_headers(
             self.config(),
             method,
             self.resource.as_deref(),
             self.query_string.as_deref(),
             None,
+            range_headers.as_ref(),
         )
     }
 
@@ -95,6 +154,179 @@ impl Headers for S3Client {
             self.resource.as_deref(),
             None,
             None,
+            None,
+        )
+    }
+
+    fn generate_put_object_headers(
+        &mut self,
+        object_key: &str,
+        payload_hash: &str,
+        metadata: Option<&HashMap<String, String>>,
+        checksum: Option<&Checksum>,
+    ) -> Result<HashMap<String, String>, LakestreamError> {
+        self.resource = Some(object_key.to_string());
+        let method = "PUT";
+
+        let mut extra_headers =
+            metadata.map(user_metadata_headers).unwrap_or_default();
+        if let Some(checksum) = checksum {
+            extra_headers.insert(
+                checksum.algorithm().header_name().to_string(),
+                checksum.value().to_string(),
+            );
+        }
+
+        self.request_builder.generate_headers(
+            self.config(),
+            method,
+            self.resource.as_deref(),
+            None,
+            Some(payload_hash),
+            Some(&extra_headers),
+        )
+    }
+
+    fn generate_delete_object_headers(
+        &mut self,
+        object_key: &str,
+    ) -> Result<HashMap<String, String>, LakestreamError> {
+        self.resource = Some(object_key.to_string());
+        let method = "DELETE";
+        self.request_builder.generate_headers(
+            self.config(),
+            method,
+            self.resource.as_deref(),
+            None,
+            None,
+            None,
+        )
+    }
+
+    fn generate_delete_objects_headers(
+        &mut self,
+        payload_hash: &str,
+        content_md5: &str,
+    ) -> Result<HashMap<String, String>, LakestreamError> {
+        let method = "POST";
+        self.query_string = Some("delete".to_string());
+
+        // S3 requires a Content-MD5 header for multi-object deletes
+        let md5_headers = HashMap::from([(
+            "content-md5".to_string(),
+            content_md5.to_string(),
+        )]);
+
+        self.request_builder.generate_headers(
+            self.config(),
+            method,
+            self.resource.as_deref(),
+            self.query_string.as_deref(),
+            Some(payload_hash),
+            Some(&md5_headers),
+        )
+    }
+
+    fn generate_create_multipart_upload_headers(
+        &mut self,
+        object_key: &str,
+        metadata: Option<&HashMap<String, String>>,
+        checksum_algorithm: Option<ChecksumAlgorithm>,
+    ) -> Result<HashMap<String, String>, LakestreamError> {
+        self.resource = Some(object_key.to_string());
+        self.query_string = Some("uploads".to_string());
+        let method = "POST";
+
+        // metadata is set when the upload is created, not per part
+        let mut extra_headers =
+            metadata.map(user_metadata_headers).unwrap_or_default();
+        // parts are uploaded with this checksum
+        if let Some(algorithm) = checksum_algorithm
+            .and_then(|algorithm| algorithm.additional_checksum())
+        {
+            extra_headers.insert(
+                "x-amz-checksum-algorithm".to_string(),
+                algorithm.to_string(),
+            );
+        }
+
+        self.request_builder.generate_headers(
+            self.config(),
+            method,
+            self.resource.as_deref(),
+            self.query_string.as_deref(),
+            None,
+            Some(&extra_headers),
+        )
+    }
+
+    fn generate_upload_part_headers(
+        &mut self,
+        object_key: &str,
+        upload_id: &str,
+        part_number: u32,
+        payload_hash: &str,
+        checksum: Option<&Checksum>,
+    ) -> Result<HashMap<String, String>, LakestreamError> {
+        self.resource = Some(object_key.to_string());
+        self.query_string = Some(format!(
+            "partNumber={}&uploadId={}",
+            part_number,
+            query_encode(upload_id)
+        ));
+        let method = "PUT";
+        let checksum_headers = checksum.map(|checksum| {
+            HashMap::from([(
+                checksum.algorithm().header_name().to_string(),
+                checksum.value().to_string(),
+            )])
+        });
+        self.request_builder.generate_headers(
+            self.config(),
+            method,
+            self.resource.as_deref(),
+            self.query_string.as_deref(),
+            Some(payload_hash),
+            checksum_headers.as_ref(),
+        )
+    }
+
+    fn generate_complete_multipart_upload_headers(
+        &mut self,
+        object_key: &str,
+        upload_id: &str,
+        payload_hash: &str,
+    ) -> Result<HashMap<String, String>, LakestreamError> {
+        self.resource = Some(object_key.to_string());
+        self.query_string =
+            Some(format!("uploadId={}", query_encode(upload_id)));
+        let method = "POST";
+        self.request_builder.generate_headers(
+            self.config(),
+            method,
+            self.resource.as_deref(),
+            self.query_string.as_deref(),
+            Some(payload_hash),
+            None,
+        )
+    }
+
+    fn generate_abort_multipart_upload_headers(
+        &mut self,
+        object_key: &str,
+        upload_id: &str,
+    ) -> Result<HashMap<String, String>, LakestreamError> {
+        self.resource = Some(object_key.to_string());
+        self.query_string =
+            Some(format!("uploadId={}", query_encode(upload_id)));
+        let method = "DELETE";
+        self.request_builder.generate_headers(
+            self.config(),
+            method,
+            self.resource.as_deref(),
+            self.query_string.as_deref(),
+            None,
+            None,
         )
     }
 
@@ -103,6 +335,8 @@ impl Headers for S3Client {
         prefix: Option<&str>,
         max_keys: Option<u32>,
         continuation_token: Option<&str>,
+        start_after: Option<&str>,
+        delimiter: Option<&str>,
     ) -> String {
         // Ensure max_keys does not exceed AWS_MAX_LIST_OBJECTS
         let max_keys = max_keys
@@ -112,7 +346,10 @@ impl Headers for S3Client {
         let mut query_parts = form_urlencoded::Serializer::new(String::new());
         query_parts.append_pair("list-type", "2");
         query_parts.append_pair("max-keys", &max_keys.to_string());
-        query_parts.append_pair("delimiter", "/");
+        // without a delimiter all keys under the prefix are listed
+        if let Some(delimiter) = delimiter {
+            query_parts.append_pair("delimiter", delimiter);
+        }
         query_parts.append_pair("encoding-type", "url");
 
         if let Some(p) = prefix {
@@ -121,7 +358,23 @@ impl Headers for S3Client {
         if let Some(token) = continuation_token {
             query_parts.append_pair("continuation-token", token);
         }
+        // ignored by S3 when a continuation token is given
+        if let Some(key) = start_after {
+            query_parts.append_pair("start-after", key);
+        }
 
         query_parts.finish()
     }
 }
+
+// user-defined metadata is stored as x-amz-meta-* headers
+fn user_metadata_headers(
+    metadata: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    metadata
+        .iter()
+        .map(|(key, value)| {
+            (format!("x-amz-meta-{}", key.to_lowercase()), value.clone())
+        })
+        .collect()
+}
@@ -1,65 +1,623 @@
 use std::env;
+use std::str::FromStr;
+use std::time::Duration;
 
-use crate::{EnvironmentConfig, LakestreamError};
+use log::warn;
 
-const AWS_DEFAULT_REGION: &str = "us-east-1";
+use crate::http::client_config::{
+    HttpClientConfig, Proxy, ProxyConfig, TlsConfig,
+};
+use crate::http::retry::RetryPolicy;
+use crate::s3::checksum::ChecksumAlgorithm;
+use crate::s3::profile::{AwsProfile, AwsProfileFiles, DEFAULT_PROFILE};
+use crate::s3::provider::{AddressingStyle, S3Provider};
+use crate::{
+    EnvironmentConfig, LakestreamError, HTTP_POOL_IDLE_TIMEOUT_SECS,
+    HTTP_POOL_MAX_IDLE_PER_HOST, RETRY_BASE_DELAY_MS, RETRY_MAX_ATTEMPTS,
+    RETRY_MAX_DELAY_MS, RETRY_MAX_ELAPSED_MS,
+};
+
+// optional settings that can also be set via the environment
+const OPTIONAL_SETTINGS: [&str; 34] = [
+    "AWS_CONFIG_FILE",
+    "AWS_SHARED_CREDENTIALS_FILE",
+    "AWS_ENDPOINT_URL_STS",
+    "S3_CHECKSUM_ALGORITHM",
+    "S3_VERIFY_CHECKSUM",
+    "S3_PROVIDER",
+    "S3_ACCOUNT_ID",
+    "S3_ADDRESSING_STYLE",
+    "S3_NO_SIGN_REQUEST",
+    "AWS_CONTAINER_CREDENTIALS_FULL_URI",
+    "AWS_CONTAINER_CREDENTIALS_RELATIVE_URI",
+    "AWS_CONTAINER_AUTHORIZATION_TOKEN",
+    "AWS_CONTAINER_AUTHORIZATION_TOKEN_FILE",
+    "AWS_EC2_METADATA_DISABLED",
+    "AWS_EC2_METADATA_SERVICE_ENDPOINT",
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "NO_PROXY",
+    "AWS_CA_BUNDLE",
+    "HTTP_TLS_CLIENT_CERT",
+    "HTTP_TLS_CLIENT_KEY",
+    "HTTP_TLS_INSECURE",
+    "HTTP_POOL_MAX_IDLE_PER_HOST",
+    "HTTP_POOL_IDLE_TIMEOUT_SECS",
+    "HTTP2",
+    "AWS_MAX_ATTEMPTS",
+    "S3_RETRY_BASE_DELAY_MS",
+    "S3_RETRY_MAX_DELAY_MS",
+    "S3_RETRY_MAX_ELAPSED_MS",
+    "S3_LIST_FLAT",
+    "S3_LIST_CONCURRENCY",
+    "S3_MULTIPART_THRESHOLD",
+    "S3_MULTIPART_PART_SIZE",
+    "S3_MULTIPART_CONCURRENCY",
+];
 
 pub fn validate_config(
     config: &mut EnvironmentConfig,
 ) -> Result<(), LakestreamError> {
+    validate_config_with_env(config, &|key| env::var(key).ok())
+}
+
+// as validate_config, with the environment variables looked up via env
+fn validate_config_with_env(
+    config: &mut EnvironmentConfig,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> Result<(), LakestreamError> {
+    // Optional settings, these have defaults when not set
+    for key in OPTIONAL_SETTINGS {
+        if !config.contains_key(key) {
+            if let Some(value) = env(key) {
+                config.insert(key.to_string(), value);
+            }
+        }
+    }
+
+    // proxy variables are commonly set in lowercase
+    for key in ["HTTP_PROXY", "HTTPS_PROXY", "NO_PROXY"] {
+        if !config.contains_key(key) {
+            if let Some(value) = env(&key.to_ascii_lowercase()) {
+                config.insert(key.to_string(), value);
+            }
+        }
+    }
+
+    // settings not found in the config or environment are taken from the
+    // profile in the shared AWS config and credentials files
+    let (profile_files, profile, is_selected) = load_profile(config, env)?;
+
+    // preset for S3 compatible services, provides defaults for the region,
+    // endpoint and addressing style
+    let provider = s3_provider(config)?;
+
     // Set AWS_REGION
     if !config.contains_key("AWS_REGION") {
         if let Some(region) = config.get("region").cloned() {
             config.insert("AWS_REGION".to_string(), region);
         } else {
-            let region = env::var("AWS_REGION").unwrap_or_else(|_| {
-                env::var("AWS_DEFAULT_REGION")
-                    .unwrap_or_else(|_| AWS_DEFAULT_REGION.to_owned())
-            });
+            let region = env("AWS_REGION")
+                .or_else(|| env("AWS_DEFAULT_REGION"))
+                .or_else(|| {
+                    profile
+                        .as_ref()
+                        .and_then(|profile| profile.region())
+                        .map(String::from)
+                })
+                .or_else(|| provider.default_region().map(String::from))
+                .ok_or_else(|| {
+                    LakestreamError::ConfigError(format!(
+                        "AWS_REGION is required for S3_PROVIDER {}",
+                        provider
+                    ))
+                })?;
             config.insert("AWS_REGION".to_string(), region);
         }
     }
 
-    // Set AWS_ACCESS_KEY_ID
-    if !config.contains_key("AWS_ACCESS_KEY_ID") {
-        if let Ok(aws_access_key_id) = env::var("AWS_ACCESS_KEY_ID") {
+    // Static credentials are taken from the config, environment or profile,
+    // in that order. Keys from different sources are not mixed. Without
+    // static credentials, other providers are tried when signing requests,
+    // see credentials::credential_provider. A profile with a role_arn is
+    // checked here, the role is assumed when signing requests
+    let no_sign_request = no_sign_request(config)?;
+    let mut from_profile = false;
+    if !config.contains_key("AWS_ACCESS_KEY_ID") && !no_sign_request {
+        if let Some(aws_access_key_id) = env("AWS_ACCESS_KEY_ID") {
             config.insert("AWS_ACCESS_KEY_ID".to_string(), aws_access_key_id);
-        } else {
-            return Err(LakestreamError::ConfigError(
-                "AWS_ACCESS_KEY_ID not found in the config and environment"
-                    .to_string(),
-            ));
+        } else if let Some(profile) = &profile {
+            if let Some(credentials) = profile.credentials() {
+                config.insert(
+                    "AWS_ACCESS_KEY_ID".to_string(),
+                    credentials.access_key,
+                );
+                config.insert(
+                    "AWS_SECRET_ACCESS_KEY".to_string(),
+                    credentials.secret_key,
+                );
+                if let Some(session_token) = credentials.session_token {
+                    config
+                        .insert("AWS_SESSION_TOKEN".to_string(), session_token);
+                }
+                from_profile = true;
+            } else if profile.role_arn().is_some() {
+                match profile_files.role_chain(profile.name()) {
+                    Ok(_) => {
+                        config.insert(
+                            "AWS_ROLE_PROFILE".to_string(),
+                            profile.name().to_string(),
+                        );
+                        if let Some(endpoint_url) =
+                            profile.get("sts.endpoint_url")
+                        {
+                            if !config.contains_key("AWS_ENDPOINT_URL_STS") {
+                                config.insert(
+                                    "AWS_ENDPOINT_URL_STS".to_string(),
+                                    endpoint_url.to_string(),
+                                );
+                            }
+                        }
+                    }
+                    // an implicit default profile does not fail the config,
+                    // other providers can still supply credentials
+                    Err(err) if !is_selected => {
+                        warn!("Ignoring role of profile: {}", err);
+                    }
+                    Err(err) => return Err(err),
+                }
+            } else if let Some(command) = profile.get("credential_process") {
+                if !config.contains_key("AWS_CREDENTIAL_PROCESS") {
+                    config.insert(
+                        "AWS_CREDENTIAL_PROCESS".to_string(),
+                        command.to_string(),
+                    );
+                }
+            }
         }
     }
 
-    // Set AWS_SECRET_ACCESS_KEY
-    if !config.contains_key("AWS_SECRET_ACCESS_KEY") {
-        if let Ok(aws_secret_access_key) = env::var("AWS_SECRET_ACCESS_KEY") {
-            config.insert(
-                "AWS_SECRET_ACCESS_KEY".to_string(),
-                aws_secret_access_key,
-            );
-        } else {
-            return Err(LakestreamError::ConfigError(
-                "AWS_SECRET_ACCESS_KEY not found in the config and environment"
-                    .to_string(),
-            ));
+    if config.contains_key("AWS_ACCESS_KEY_ID")
+        && !from_profile
+        && !no_sign_request
+    {
+        // Set AWS_SECRET_ACCESS_KEY
+        if !config.contains_key("AWS_SECRET_ACCESS_KEY") {
+            if let Some(aws_secret_access_key) = env("AWS_SECRET_ACCESS_KEY") {
+                config.insert(
+                    "AWS_SECRET_ACCESS_KEY".to_string(),
+                    aws_secret_access_key,
+                );
+            } else {
+                return Err(LakestreamError::ConfigError(
+                    "AWS_SECRET_ACCESS_KEY not found in the config and \
+                     environment"
+                        .to_string(),
+                ));
+            }
         }
-    }
 
-    // Set AWS_SESSION_TOKEN (optional)
-    if !config.contains_key("AWS_SESSION_TOKEN") {
-        if let Ok(aws_session_token) = env::var("AWS_SESSION_TOKEN") {
-            config.insert("AWS_SESSION_TOKEN".to_string(), aws_session_token);
+        // Set AWS_SESSION_TOKEN (optional)
+        if !config.contains_key("AWS_SESSION_TOKEN") {
+            if let Some(aws_session_token) = env("AWS_SESSION_TOKEN") {
+                config
+                    .insert("AWS_SESSION_TOKEN".to_string(), aws_session_token);
+            }
         }
     }
 
     // Set AWS Endpoint (optional)
     if !config.contains_key("S3_ENDPOINT_URL") {
-        if let Ok(s3_endpoint_url) = env::var("S3_ENDPOINT_URL") {
+        if let Some(s3_endpoint_url) = env("S3_ENDPOINT_URL") {
             config.insert("S3_ENDPOINT_URL".to_string(), s3_endpoint_url);
+        } else if let Some(endpoint_url) =
+            profile.as_ref().and_then(|profile| profile.endpoint_url())
+        {
+            config.insert("S3_ENDPOINT_URL".to_string(), endpoint_url.into());
+        } else if let Some(endpoint_url) = provider
+            .endpoint_url(
+                config.get("AWS_REGION").map_or("", String::as_str),
+                config.get("S3_ACCOUNT_ID").map(String::as_str),
+            )
+            .map_err(LakestreamError::ConfigError)?
+        {
+            config.insert("S3_ENDPOINT_URL".to_string(), endpoint_url);
         }
     }
 
+    // Set S3_ADDRESSING_STYLE
+    if !config.contains_key("S3_ADDRESSING_STYLE") {
+        let addressing_style = profile
+            .as_ref()
+            .and_then(|profile| profile.get("s3.addressing_style"))
+            .map(String::from)
+            .unwrap_or_else(|| provider.addressing_style().to_string());
+        config.insert("S3_ADDRESSING_STYLE".to_string(), addressing_style);
+    }
+    addressing_style(config)?;
+
+    // fail early on invalid checksum, retry and http settings
+    checksum_algorithm(config)?;
+    verify_checksum(config)?;
+    retry_policy(config)?;
+    http_client_config(config)?;
     Ok(())
 }
+
+// the profile named by AWS_PROFILE (config or environment), otherwise
+// the default profile if it exists. The flag is true for a profile
+// selected by name
+fn load_profile(
+    config: &EnvironmentConfig,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> Result<(AwsProfileFiles, Option<AwsProfile>, bool), LakestreamError> {
+    let profile_files = AwsProfileFiles::load(config);
+    let profile_name = config
+        .get("AWS_PROFILE")
+        .cloned()
+        .or_else(|| env("AWS_PROFILE"));
+    match profile_name {
+        Some(name) => {
+            let profile = profile_files.profile(&name).ok_or_else(|| {
+                LakestreamError::ConfigError(format!(
+                    "Profile {} not found in the AWS config or credentials \
+                     file",
+                    name
+                ))
+            })?;
+            Ok((profile_files, Some(profile), true))
+        }
+        None => {
+            let profile = profile_files.profile(DEFAULT_PROFILE);
+            Ok((profile_files, profile, false))
+        }
+    }
+}
+
+// send requests without signature, e.g. to access public buckets
+pub fn no_sign_request(
+    config: &EnvironmentConfig,
+) -> Result<bool, LakestreamError> {
+    parse_setting(config, "S3_NO_SIGN_REQUEST", false)
+}
+
+// checksum sent with uploads, S3 rejects data that does not match
+pub fn checksum_algorithm(
+    config: &EnvironmentConfig,
+) -> Result<Option<ChecksumAlgorithm>, LakestreamError> {
+    config
+        .get("S3_CHECKSUM_ALGORITHM")
+        .map(|_| {
+            parse_setting(
+                config,
+                "S3_CHECKSUM_ALGORITHM",
+                ChecksumAlgorithm::Md5,
+            )
+        })
+        .transpose()
+}
+
+// verify downloaded objects against their ETag
+pub fn verify_checksum(
+    config: &EnvironmentConfig,
+) -> Result<bool, LakestreamError> {
+    parse_setting(config, "S3_VERIFY_CHECKSUM", false)
+}
+
+pub fn s3_provider(
+    config: &EnvironmentConfig,
+) -> Result<S3Provider, LakestreamError> {
+    parse_setting(config, "S3_PROVIDER", S3Provider::Aws)
+}
+
+// auto resolves to virtual hosted style for AWS, path style otherwise
+pub fn addressing_style(
+    config: &EnvironmentConfig,
+) -> Result<AddressingStyle, LakestreamError> {
+    parse_setting(config, "S3_ADDRESSING_STYLE", AddressingStyle::Auto)
+}
+
+pub fn retry_policy(
+    config: &EnvironmentConfig,
+) -> Result<RetryPolicy, LakestreamError> {
+    let millis = |key, default| {
+        parse_setting(config, key, default).map(Duration::from_millis)
+    };
+    Ok(RetryPolicy::new(
+        parse_setting(config, "AWS_MAX_ATTEMPTS", RETRY_MAX_ATTEMPTS)?,
+        millis("S3_RETRY_BASE_DELAY_MS", RETRY_BASE_DELAY_MS)?,
+        millis("S3_RETRY_MAX_DELAY_MS", RETRY_MAX_DELAY_MS)?,
+        millis("S3_RETRY_MAX_ELAPSED_MS", RETRY_MAX_ELAPSED_MS)?,
+    ))
+}
+
+// parse an optional setting, falling back to a default if not set
+pub fn parse_setting<T: FromStr>(
+    config: &EnvironmentConfig,
+    key: &str,
+    default: T,
+) -> Result<T, LakestreamError> {
+    match config.get(key) {
+        Some(value) => value.trim().parse::<T>().map_err(|_| {
+            LakestreamError::ConfigError(format!(
+                "Invalid value for {}: {}",
+                key, value
+            ))
+        }),
+        None => Ok(default),
+    }
+}
+
+pub fn http_client_config(
+    config: &EnvironmentConfig,
+) -> Result<HttpClientConfig, LakestreamError> {
+    Ok(HttpClientConfig::new(
+        parse_setting(
+            config,
+            "HTTP_POOL_MAX_IDLE_PER_HOST",
+            HTTP_POOL_MAX_IDLE_PER_HOST,
+        )?,
+        Duration::from_secs(parse_setting(
+            config,
+            "HTTP_POOL_IDLE_TIMEOUT_SECS",
+            HTTP_POOL_IDLE_TIMEOUT_SECS,
+        )?),
+        parse_setting(config, "HTTP2", true)?,
+        tls_config(config)?,
+        proxy_config(config)?,
+    ))
+}
+
+fn proxy_config(
+    config: &EnvironmentConfig,
+) -> Result<ProxyConfig, LakestreamError> {
+    // an empty value disables the proxy
+    let proxy = |key: &str| match config.get(key) {
+        Some(value) if !value.trim().is_empty() => {
+            value.parse::<Proxy>().map(Some).map_err(|err| {
+                LakestreamError::ConfigError(format!("{}: {}", key, err))
+            })
+        }
+        _ => Ok(None),
+    };
+    Ok(ProxyConfig::new(
+        proxy("HTTP_PROXY")?,
+        proxy("HTTPS_PROXY")?,
+        config
+            .get("NO_PROXY")
+            .map_or("", |no_proxy| no_proxy.as_str()),
+    ))
+}
+
+fn tls_config(
+    config: &EnvironmentConfig,
+) -> Result<TlsConfig, LakestreamError> {
+    let client_cert = config.get("HTTP_TLS_CLIENT_CERT").cloned();
+    let client_key = config.get("HTTP_TLS_CLIENT_KEY").cloned();
+    if client_cert.is_some() != client_key.is_some() {
+        return Err(LakestreamError::ConfigError(
+            "HTTP_TLS_CLIENT_CERT and HTTP_TLS_CLIENT_KEY must be set together"
+                .to_string(),
+        ));
+    }
+    Ok(TlsConfig::new(
+        config.get("AWS_CA_BUNDLE").cloned(),
+        client_cert.zip(client_key),
+        parse_setting(config, "HTTP_TLS_INSECURE", false)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::fs;
+
+    use super::*;
+
+    const CONFIG: &str = "
+[default]
+region = eu-west-1
+
+[profile dev]
+s3 =
+  addressing_style = path
+
+[profile admin]
+role_arn = arn:aws:iam::123456789012:role/admin
+source_profile = dev
+sts =
+  endpoint_url = http://localhost:4566
+
+[profile loop]
+role_arn = arn:aws:iam::123456789012:role/loop
+source_profile = loop-source
+
+[profile loop-source]
+role_arn = arn:aws:iam::123456789012:role/loop-source
+source_profile = loop
+";
+
+    const CREDENTIALS: &str = "
+[default]
+aws_access_key_id = AKIDDEFAULT
+aws_secret_access_key = default-secret
+
+[dev]
+aws_access_key_id = AKIDDEV
+aws_secret_access_key = dev-secret
+";
+
+    // config with the AWS files in a temporary directory
+    fn profile_config(
+        dir: &tempfile::TempDir,
+        settings: &[(&str, &str)],
+    ) -> EnvironmentConfig {
+        let mut config = EnvironmentConfig::default();
+        for (key, file) in [
+            ("AWS_CONFIG_FILE", "config"),
+            ("AWS_SHARED_CREDENTIALS_FILE", "credentials"),
+        ] {
+            let path = dir.path().join(file).to_string_lossy().to_string();
+            config.set(key.to_string(), path);
+        }
+        config.set("S3_ENDPOINT_URL".to_string(), "http://localhost".into());
+        for (key, value) in settings {
+            config.set(key.to_string(), value.to_string());
+        }
+        config
+    }
+
+    // validate with the given environment instead of the process one
+    fn validate_with_env(
+        config: &mut EnvironmentConfig,
+        env: &[(&str, &str)],
+    ) -> Result<(), LakestreamError> {
+        let env: HashMap<String, String> = env
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        validate_config_with_env(config, &|key| env.get(key).cloned())
+    }
+
+    #[test]
+    fn test_profile_precedence() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("config"), CONFIG).unwrap();
+        fs::write(dir.path().join("credentials"), CREDENTIALS).unwrap();
+        let validated = |settings: &[(&str, &str)], env: &[(&str, &str)]| {
+            let mut config = profile_config(&dir, settings);
+            validate_with_env(&mut config, env).map(|_| config)
+        };
+        let value = |config: &EnvironmentConfig, key: &str| {
+            config.get(key).cloned().unwrap_or_default()
+        };
+
+        // without AWS_PROFILE, the default profile is used
+        let config = validated(&[], &[]).unwrap();
+        assert_eq!(value(&config, "AWS_ACCESS_KEY_ID"), "AKIDDEFAULT");
+        assert_eq!(value(&config, "AWS_REGION"), "eu-west-1");
+
+        // AWS_PROFILE in the config (set by --profile) selects the profile,
+        // and takes precedence over the environment
+        let config =
+            validated(&[("AWS_PROFILE", "dev")], &[("AWS_PROFILE", "missing")])
+                .unwrap();
+        assert_eq!(value(&config, "AWS_ACCESS_KEY_ID"), "AKIDDEV");
+        assert_eq!(value(&config, "AWS_SECRET_ACCESS_KEY"), "dev-secret");
+        assert_eq!(value(&config, "S3_ADDRESSING_STYLE"), "path");
+        let config = validated(&[], &[("AWS_PROFILE", "dev")]).unwrap();
+        assert_eq!(value(&config, "AWS_ACCESS_KEY_ID"), "AKIDDEV");
+
+        // keys in the config take precedence over the environment, and
+        // keys in the environment over the profile. Keys from different
+        // sources are not mixed
+        let env_keys = [
+            ("AWS_ACCESS_KEY_ID", "AKIDENV"),
+            ("AWS_SECRET_ACCESS_KEY", "env-secret"),
+            ("AWS_REGION", "us-west-2"),
+        ];
+        let config = validated(
+            &[
+                ("AWS_PROFILE", "dev"),
+                ("AWS_ACCESS_KEY_ID", "AKIDCONFIG"),
+                ("AWS_SECRET_ACCESS_KEY", "config-secret"),
+            ],
+            &env_keys,
+        )
+        .unwrap();
+        assert_eq!(value(&config, "AWS_ACCESS_KEY_ID"), "AKIDCONFIG");
+        assert_eq!(value(&config, "AWS_SECRET_ACCESS_KEY"), "config-secret");
+        let config = validated(&[("AWS_PROFILE", "dev")], &env_keys).unwrap();
+        assert_eq!(value(&config, "AWS_ACCESS_KEY_ID"), "AKIDENV");
+        assert_eq!(value(&config, "AWS_SECRET_ACCESS_KEY"), "env-secret");
+        assert_eq!(value(&config, "AWS_REGION"), "us-west-2");
+        let err =
+            validated(&[], &[("AWS_ACCESS_KEY_ID", "AKIDENV")]).unwrap_err();
+        assert!(err.to_string().contains("AWS_SECRET_ACCESS_KEY not found"));
+
+        let err = validated(&[("AWS_PROFILE", "missing")], &[]).unwrap_err();
+        assert!(err.to_string().contains("Profile missing not found"));
+
+        // the role of a selected profile is assumed when signing, its
+        // source_profile keys are not used directly
+        let config = validated(&[("AWS_PROFILE", "admin")], &[]).unwrap();
+        assert_eq!(value(&config, "AWS_ROLE_PROFILE"), "admin");
+        assert_eq!(
+            value(&config, "AWS_ENDPOINT_URL_STS"),
+            "http://localhost:4566"
+        );
+        assert!(!config.contains_key("AWS_ACCESS_KEY_ID"));
+
+        let err = validated(&[("AWS_PROFILE", "loop")], &[]).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Cycle in the source_profile chain"));
+    }
+
+    #[test]
+    fn test_default_role_profile() {
+        // the role of the implicit default profile is assumed as well,
+        // its other settings still apply
+        let dir = tempfile::tempdir().unwrap();
+        let config_file = "
+[default]
+region = eu-west-1
+role_arn = arn:aws:iam::123456789012:role/admin
+source_profile = base
+s3 =
+  addressing_style = virtual
+
+[profile base]
+aws_access_key_id = AKIDBASE
+aws_secret_access_key = base-secret
+
+[profile broken]
+role_arn = arn:aws:iam::123456789012:role/broken
+";
+        fs::write(dir.path().join("config"), config_file).unwrap();
+        let mut config = profile_config(&dir, &[]);
+        validate_with_env(&mut config, &[]).unwrap();
+        assert_eq!(
+            config.get("S3_ADDRESSING_STYLE").map(String::as_str),
+            Some("virtual")
+        );
+        assert_eq!(
+            config.get("AWS_ROLE_PROFILE").map(String::as_str),
+            Some(DEFAULT_PROFILE)
+        );
+        assert!(!config.contains_key("AWS_ACCESS_KEY_ID"));
+
+        // a broken role profile fails the config once selected
+        let mut config = profile_config(&dir, &[]);
+        let err = validate_with_env(&mut config, &[("AWS_PROFILE", "broken")])
+            .unwrap_err();
+        assert!(err.to_string().contains("without source_profile"));
+    }
+
+    #[test]
+    fn test_tls_client_identity_pair() {
+        let config = |settings: &[(&str, &str)]| {
+            let mut config = EnvironmentConfig::default();
+            for (key, value) in settings {
+                config.set(key.to_string(), value.to_string());
+            }
+            config
+        };
+        let cert = ("HTTP_TLS_CLIENT_CERT", "/tmp/cert.pem");
+        let key = ("HTTP_TLS_CLIENT_KEY", "/tmp/key.pem");
+
+        for settings in [&[cert][..], &[key][..]] {
+            let err = tls_config(&config(settings)).unwrap_err().to_string();
+            assert!(err.contains("must be set together"));
+        }
+        let tls = tls_config(&config(&[cert, key])).unwrap();
+        assert_eq!(
+            tls.client_identity(),
+            Some(("/tmp/cert.pem", "/tmp/key.pem"))
+        );
+        assert!(tls_config(&config(&[]))
+            .unwrap()
+            .client_identity()
+            .is_none());
+    }
+}
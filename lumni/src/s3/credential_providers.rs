@@ -0,0 +1,804 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use tokio::process::Command;
+use url::{Host, Url};
+
+use super::bucket::S3Credentials;
+use super::config::http_client_config;
+use super::credentials::{CredentialProvider, SharedCredentialProvider};
+use super::profile::AwsProfileFiles;
+use super::sts::{assume_role, sts_endpoint_url};
+use crate::http::client_config::HttpClientConfig;
+use crate::http::requests::http_request_with_headers;
+use crate::utils::time::{rfc3339_to_epoch, system_time_in_seconds};
+use crate::{EnvironmentConfig, LakestreamError};
+
+// refresh temporary credentials this long before they expire
+const REFRESH_MARGIN_SECS: u64 = 300;
+
+const CONTAINER_ENDPOINT: &str = "http://169.254.170.2";
+const CONTAINER_TIMEOUT: Duration = Duration::from_secs(5);
+// hosts of the ECS and EKS pod identity agents, which can be reached over
+// http. Other hosts require https, or must be a loopback address
+const CONTAINER_HOSTS: [&str; 3] =
+    ["169.254.170.2", "169.254.170.23", "fd00:ec2::23"];
+const IMDS_ENDPOINT: &str = "http://169.254.169.254";
+const IMDS_TIMEOUT: Duration = Duration::from_secs(1);
+const IMDS_TOKEN_TTL_SECS: u32 = 21600;
+
+// providers are shared, so that temporary credentials are fetched once
+// instead of for every client
+static PROVIDERS: OnceLock<Mutex<HashMap<String, SharedCredentialProvider>>> =
+    OnceLock::new();
+
+pub fn default_provider(
+    config: &EnvironmentConfig,
+) -> SharedCredentialProvider {
+    if let Some(profile) = config.get("AWS_ROLE_PROFILE") {
+        let key = format!(
+            "assume-role:{}:{}:{}",
+            profile,
+            config.get("AWS_CONFIG_FILE").map_or("", String::as_str),
+            config
+                .get("AWS_SHARED_CREDENTIALS_FILE")
+                .map_or("", String::as_str),
+        );
+        let provider = AssumeRoleProvider {
+            profile: profile.clone(),
+            config: config.clone(),
+        };
+        return shared_provider(key, || provider);
+    }
+
+    if let Some(command) = config.get("AWS_CREDENTIAL_PROCESS") {
+        let command = command.clone();
+        return shared_provider(format!("process:{}", command), || {
+            ProcessProvider { command }
+        });
+    }
+
+    let container_uri = config
+        .get("AWS_CONTAINER_CREDENTIALS_FULL_URI")
+        .cloned()
+        .or_else(|| {
+            config
+                .get("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI")
+                .map(|path| format!("{}{}", CONTAINER_ENDPOINT, path))
+        });
+    if let Some(uri) = container_uri {
+        let provider = ContainerProvider {
+            uri,
+            token: config.get("AWS_CONTAINER_AUTHORIZATION_TOKEN").cloned(),
+            token_file: config
+                .get("AWS_CONTAINER_AUTHORIZATION_TOKEN_FILE")
+                .cloned(),
+        };
+        return shared_provider(format!("container:{}", provider.uri), || {
+            provider
+        });
+    }
+
+    let imds_disabled = config
+        .get("AWS_EC2_METADATA_DISABLED")
+        .is_some_and(|value| value.eq_ignore_ascii_case("true"));
+    if imds_disabled {
+        return Arc::new(NoCredentialsProvider);
+    }
+    let endpoint = config
+        .get("AWS_EC2_METADATA_SERVICE_ENDPOINT")
+        .map_or(IMDS_ENDPOINT, |endpoint| endpoint.as_str())
+        .trim_end_matches('/')
+        .to_string();
+    shared_provider(format!("imds:{}", endpoint), || ImdsProvider { endpoint })
+}
+
+fn shared_provider<P, F>(key: String, create: F) -> SharedCredentialProvider
+where
+    P: CredentialProvider + 'static,
+    F: FnOnce() -> P,
+{
+    let mut providers = PROVIDERS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    providers
+        .entry(key)
+        .or_insert_with(|| Arc::new(CachedProvider::new(create())))
+        .clone()
+}
+
+// Caches the credentials of another provider until shortly before they
+// expire. Credentials without an expiration are cached indefinitely. The
+// lock is held during a refresh, so concurrent requests wait for the
+// same fetch instead of each fetching the credentials
+pub struct CachedProvider<P> {
+    provider: P,
+    cached: tokio::sync::Mutex<Option<S3Credentials>>,
+}
+
+impl<P: CredentialProvider> CachedProvider<P> {
+    pub fn new(provider: P) -> Self {
+        CachedProvider {
+            provider,
+            cached: tokio::sync::Mutex::new(None),
+        }
+    }
+}
+
+fn needs_refresh(credentials: &S3Credentials) -> bool {
+    credentials.expiration().is_some_and(|expiration| {
+        system_time_in_seconds() + REFRESH_MARGIN_SECS >= expiration
+    })
+}
+
+#[async_trait(?Send)]
+impl<P: CredentialProvider> CredentialProvider for CachedProvider<P> {
+    async fn credentials(&self) -> Result<S3Credentials, LakestreamError> {
+        let mut cached = self.cached.lock().await;
+        if let Some(credentials) = cached
+            .as_ref()
+            .filter(|credentials| !needs_refresh(credentials))
+        {
+            return Ok(credentials.clone());
+        }
+        let credentials = self.provider.credentials().await?;
+        *cached = Some(credentials.clone());
+        Ok(credentials)
+    }
+}
+
+// Assumes the role of a profile via STS AssumeRole, following its
+// source_profile chain. The profile files are read again on each refresh
+pub struct AssumeRoleProvider {
+    profile: String,
+    config: EnvironmentConfig,
+}
+
+#[async_trait(?Send)]
+impl CredentialProvider for AssumeRoleProvider {
+    async fn credentials(&self) -> Result<S3Credentials, LakestreamError> {
+        let chain = AwsProfileFiles::load(&self.config)
+            .role_chain(&self.profile)?
+            .ok_or_else(|| {
+                LakestreamError::ConfigError(format!(
+                    "Profile {} does not assume a role",
+                    self.profile
+                ))
+            })?;
+        // set in validate_config
+        let region = self.config.get("AWS_REGION").ok_or_else(|| {
+            LakestreamError::ConfigError("AWS_REGION is not set".to_string())
+        })?;
+        let endpoint_url = self
+            .config
+            .get("AWS_ENDPOINT_URL_STS")
+            .cloned()
+            .unwrap_or_else(|| sts_endpoint_url(region));
+        let client_config = http_client_config(&self.config)?;
+
+        let mut credentials = S3Credentials::new(
+            chain.source.access_key,
+            chain.source.secret_key,
+            chain.source.session_token,
+        );
+        for role in &chain.roles {
+            credentials = assume_role(
+                &endpoint_url,
+                region,
+                &credentials,
+                role,
+                &client_config,
+            )
+            .await?;
+        }
+        Ok(credentials)
+    }
+}
+
+// Runs the credential_process command of a profile, which prints the
+// credentials as JSON
+pub struct ProcessProvider {
+    command: String,
+}
+
+#[async_trait(?Send)]
+impl CredentialProvider for ProcessProvider {
+    async fn credentials(&self) -> Result<S3Credentials, LakestreamError> {
+        let mut command = if cfg!(windows) {
+            let mut command = Command::new("cmd");
+            command.arg("/C");
+            command
+        } else {
+            let mut command = Command::new("sh");
+            command.arg("-c");
+            command
+        };
+        let output =
+            command.arg(&self.command).output().await.map_err(|err| {
+                credentials_error("credential_process", err.to_string())
+            })?;
+
+        if !output.status.success() {
+            return Err(credentials_error(
+                "credential_process",
+                format!(
+                    "{} ({})",
+                    String::from_utf8_lossy(&output.stderr).trim(),
+                    output.status
+                ),
+            ));
+        }
+        let response = parse_credentials_response(&output.stdout)
+            .map_err(|err| credentials_error("credential_process", err))?;
+        if response.version != Some(1) {
+            return Err(credentials_error(
+                "credential_process",
+                "unsupported Version, expected 1".to_string(),
+            ));
+        }
+        response.into_credentials()
+    }
+}
+
+// The container credentials endpoint, as provided by ECS and EKS
+pub struct ContainerProvider {
+    uri: String,
+    token: Option<String>,
+    token_file: Option<String>,
+}
+
+#[async_trait(?Send)]
+impl CredentialProvider for ContainerProvider {
+    async fn credentials(&self) -> Result<S3Credentials, LakestreamError> {
+        let source = "container credentials endpoint";
+        // the authorization token is only sent to a trusted endpoint
+        check_container_uri(&self.uri)
+            .map_err(|err| credentials_error(source, err))?;
+        let mut headers = HashMap::new();
+        // the token file is re-read as the token can be rotated
+        let token = match &self.token_file {
+            Some(path) => Some(
+                std::fs::read_to_string(path)
+                    .map_err(|err| {
+                        credentials_error(source, format!("{}: {}", path, err))
+                    })?
+                    .trim()
+                    .to_string(),
+            ),
+            None => self.token.clone(),
+        };
+        if let Some(token) = token {
+            headers.insert("Authorization".to_string(), token);
+        }
+
+        let body = metadata_request(
+            source,
+            &self.uri,
+            "GET",
+            headers,
+            CONTAINER_TIMEOUT,
+        )
+        .await?;
+        parse_credentials_response(&body)
+            .map_err(|err| credentials_error(source, err))?
+            .into_credentials()
+    }
+}
+
+// https is allowed for any host, http only for loopback addresses and
+// the link-local hosts of the ECS and EKS agents
+fn check_container_uri(uri: &str) -> Result<(), String> {
+    let url = Url::parse(uri)
+        .map_err(|err| format!("invalid uri {}: {}", uri, err))?;
+    let is_allowed = match (url.scheme(), url.host()) {
+        ("https", Some(_)) => true,
+        ("http", Some(Host::Domain(domain))) => domain == "localhost",
+        ("http", Some(Host::Ipv4(ip))) => {
+            ip.is_loopback() || CONTAINER_HOSTS.contains(&&*ip.to_string())
+        }
+        ("http", Some(Host::Ipv6(ip))) => {
+            ip.is_loopback() || CONTAINER_HOSTS.contains(&&*ip.to_string())
+        }
+        _ => false,
+    };
+    if !is_allowed {
+        return Err(format!(
+            "{} is not allowed, http is only supported for loopback \
+             addresses and the ECS and EKS hosts ({})",
+            uri,
+            CONTAINER_HOSTS.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+// The EC2 instance metadata service, using (session based) IMDSv2
+pub struct ImdsProvider {
+    endpoint: String,
+}
+
+#[async_trait(?Send)]
+impl CredentialProvider for ImdsProvider {
+    async fn credentials(&self) -> Result<S3Credentials, LakestreamError> {
+        let source = "instance metadata service";
+        let token_headers = HashMap::from([(
+            "x-aws-ec2-metadata-token-ttl-seconds".to_string(),
+            IMDS_TOKEN_TTL_SECS.to_string(),
+        )]);
+        let token = metadata_request(
+            source,
+            &format!("{}/latest/api/token", self.endpoint),
+            "PUT",
+            token_headers,
+            IMDS_TIMEOUT,
+        )
+        .await
+        .map_err(|err| match err {
+            // the metadata service is the last provider tried
+            LakestreamError::ConfigError(message) => {
+                LakestreamError::ConfigError(format!(
+                    "No credentials found in the config, environment or \
+                     profile. {}",
+                    message
+                ))
+            }
+            err => err,
+        })?;
+
+        let headers = HashMap::from([(
+            "x-aws-ec2-metadata-token".to_string(),
+            String::from_utf8_lossy(&token).trim().to_string(),
+        )]);
+        let roles_url = format!(
+            "{}/latest/meta-data/iam/security-credentials/",
+            self.endpoint
+        );
+        let roles = metadata_request(
+            source,
+            &roles_url,
+            "GET",
+            headers.clone(),
+            IMDS_TIMEOUT,
+        )
+        .await?;
+        let roles = String::from_utf8_lossy(&roles);
+        let role = roles.lines().next().unwrap_or_default().trim();
+        if role.is_empty() {
+            return Err(credentials_error(
+                source,
+                "no IAM role attached to the instance".to_string(),
+            ));
+        }
+
+        let body = metadata_request(
+            source,
+            &format!("{}{}", roles_url, role),
+            "GET",
+            headers,
+            IMDS_TIMEOUT,
+        )
+        .await?;
+        let response = parse_credentials_response(&body)
+            .map_err(|err| credentials_error(source, err))?;
+        if let Some(code) = response.code.as_deref().filter(|c| *c != "Success")
+        {
+            return Err(credentials_error(source, format!("status {}", code)));
+        }
+        response.into_credentials()
+    }
+}
+
+// the chain ends here when the metadata service is disabled
+struct NoCredentialsProvider;
+
+#[async_trait(?Send)]
+impl CredentialProvider for NoCredentialsProvider {
+    async fn credentials(&self) -> Result<S3Credentials, LakestreamError> {
+        Err(LakestreamError::ConfigError(
+            "No credentials found in the config, environment or profile"
+                .to_string(),
+        ))
+    }
+}
+
+async fn metadata_request(
+    source: &str,
+    url: &str,
+    method: &str,
+    headers: HashMap<String, String>,
+    timeout: Duration,
+) -> Result<Vec<u8>, LakestreamError> {
+    // metadata endpoints are local, and never reached via a proxy
+    let client_config = HttpClientConfig::default();
+    let request =
+        http_request_with_headers(url, &headers, method, None, &client_config);
+    let (body, status, _) = tokio::time::timeout(timeout, request)
+        .await
+        .map_err(|_| credentials_error(source, format!("{} timed out", url)))?
+        .map_err(|err| credentials_error(source, err.to_string()))?;
+    if !(200..300).contains(&status) {
+        return Err(credentials_error(
+            source,
+            format!("status {} from {}", status, url),
+        ));
+    }
+    Ok(body.to_vec())
+}
+
+fn credentials_error(source: &str, message: String) -> LakestreamError {
+    LakestreamError::ConfigError(format!(
+        "Failed to get credentials from the {}: {}",
+        source, message
+    ))
+}
+
+// Credentials as returned by the container and metadata endpoints and by
+// a credential_process, these differ slightly in naming
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct CredentialsResponse {
+    version: Option<u32>,
+    code: Option<String>,
+    access_key_id: String,
+    secret_access_key: String,
+    #[serde(alias = "SessionToken")]
+    token: Option<String>,
+    expiration: Option<String>,
+}
+
+fn parse_credentials_response(
+    body: &[u8],
+) -> Result<CredentialsResponse, String> {
+    serde_json::from_slice(body)
+        .map_err(|err| format!("invalid credentials response: {}", err))
+}
+
+impl CredentialsResponse {
+    fn into_credentials(self) -> Result<S3Credentials, LakestreamError> {
+        let expiration = match &self.expiration {
+            Some(expiration) => {
+                Some(rfc3339_to_epoch(expiration).map_err(|err| {
+                    LakestreamError::ConfigError(format!(
+                        "Invalid credentials expiration {}: {}",
+                        expiration, err
+                    ))
+                })?)
+            }
+            None => None,
+        };
+        Ok(S3Credentials::new(
+            self.access_key_id,
+            self.secret_access_key,
+            self.token,
+        )
+        .with_expiration(expiration))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::http::mock_endpoint::{
+        mock_endpoint, query_param, MockResponse,
+    };
+
+    const CREDENTIALS_JSON: &str = r#"{
+        "Code": "Success",
+        "AccessKeyId": "AKIDTEMP",
+        "SecretAccessKey": "temp-secret",
+        "Token": "temp-token",
+        "Expiration": "2099-01-01T00:00:00Z"
+    }"#;
+
+    #[tokio::test]
+    async fn test_container_provider() {
+        let (endpoint, requests) = mock_endpoint(|request| {
+            let token = request.headers.get("authorization");
+            match (request.method.as_str(), request.path.as_str(), token) {
+                ("GET", "/v2/credentials", Some(token))
+                    if token == "secret" =>
+                {
+                    MockResponse::new(200, CREDENTIALS_JSON)
+                }
+                _ => MockResponse::new(401, ""),
+            }
+        })
+        .await;
+
+        let provider = CachedProvider::new(ContainerProvider {
+            uri: format!("{}/v2/credentials", endpoint),
+            token: Some("secret".to_string()),
+            token_file: None,
+        });
+        for _ in 0..3 {
+            let credentials = provider.credentials().await.unwrap();
+            assert_eq!(credentials.access_key(), "AKIDTEMP");
+            assert_eq!(credentials.session_token(), Some("temp-token"));
+            assert_eq!(credentials.expiration(), Some(4070908800));
+        }
+        // fetched once, then served from the cache
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        let unauthorized = ContainerProvider {
+            uri: format!("{}/v2/credentials", endpoint),
+            token: None,
+            token_file: None,
+        };
+        let err = unauthorized.credentials().await.err().unwrap().to_string();
+        assert!(err.contains("status 401"));
+    }
+
+    #[tokio::test]
+    async fn test_imds_provider() {
+        let (endpoint, requests) = mock_endpoint(|request| {
+            let headers = &request.headers;
+            let token = headers.get("x-aws-ec2-metadata-token");
+            let (status, body) =
+                match (request.method.as_str(), request.path.as_str()) {
+                    ("PUT", "/latest/api/token")
+                        if headers.contains_key(
+                            "x-aws-ec2-metadata-token-ttl-seconds",
+                        ) =>
+                    {
+                        (200, "imds-token")
+                    }
+                    (_, _)
+                        if token.map(String::as_str) != Some("imds-token") =>
+                    {
+                        (401, "")
+                    }
+                    ("GET", "/latest/meta-data/iam/security-credentials/") => {
+                        (200, "instance-role\n")
+                    }
+                    (
+                        "GET",
+                        "/latest/meta-data/iam/security-credentials/\
+                         instance-role",
+                    ) => (200, CREDENTIALS_JSON),
+                    _ => (404, ""),
+                };
+            MockResponse::new(status, body)
+        })
+        .await;
+
+        let provider = ImdsProvider { endpoint };
+        let credentials = provider.credentials().await.unwrap();
+        assert_eq!(credentials.access_key(), "AKIDTEMP");
+        assert_eq!(credentials.secret_key(), "temp-secret");
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_process_provider() {
+        let provider = ProcessProvider {
+            command: r#"echo '{"Version": 1, "AccessKeyId": "AKIDPROC",
+                "SecretAccessKey": "proc-secret", "SessionToken": "t"}'"#
+                .to_string(),
+        };
+        let credentials = provider.credentials().await.unwrap();
+        assert_eq!(credentials.access_key(), "AKIDPROC");
+        assert_eq!(credentials.session_token(), Some("t"));
+        assert_eq!(credentials.expiration(), None);
+
+        let failing = ProcessProvider {
+            command: "echo denied >&2; exit 3".to_string(),
+        };
+        let err = failing.credentials().await.err().unwrap().to_string();
+        assert!(err.contains("denied"));
+    }
+
+    struct CountingProvider {
+        calls: AtomicUsize,
+        valid_for_secs: u64,
+        delay: Duration,
+    }
+
+    #[async_trait(?Send)]
+    impl CredentialProvider for CountingProvider {
+        async fn credentials(&self) -> Result<S3Credentials, LakestreamError> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(self.delay).await;
+            let expiration = system_time_in_seconds() + self.valid_for_secs;
+            Ok(
+                S3Credentials::new(format!("AKID{}", call), "s".into(), None)
+                    .with_expiration(Some(expiration)),
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cached_provider_refresh() {
+        // credentials that expire within the refresh margin are refetched
+        let expiring = CachedProvider::new(CountingProvider {
+            calls: AtomicUsize::new(0),
+            valid_for_secs: REFRESH_MARGIN_SECS / 2,
+            delay: Duration::ZERO,
+        });
+        assert_eq!(expiring.credentials().await.unwrap().access_key(), "AKID0");
+        assert_eq!(expiring.credentials().await.unwrap().access_key(), "AKID1");
+
+        let valid = CachedProvider::new(CountingProvider {
+            calls: AtomicUsize::new(0),
+            valid_for_secs: 3600,
+            delay: Duration::ZERO,
+        });
+        assert_eq!(valid.credentials().await.unwrap().access_key(), "AKID0");
+        assert_eq!(valid.credentials().await.unwrap().access_key(), "AKID0");
+    }
+
+    #[tokio::test]
+    async fn test_cached_provider_single_fetch() {
+        // concurrent requests wait for the refresh in progress
+        let provider = CachedProvider::new(CountingProvider {
+            calls: AtomicUsize::new(0),
+            valid_for_secs: 3600,
+            delay: Duration::from_millis(50),
+        });
+        let results =
+            futures::future::join_all((0..5).map(|_| provider.credentials()))
+                .await;
+        for result in results {
+            assert_eq!(result.unwrap().access_key(), "AKID0");
+        }
+        assert_eq!(provider.provider.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_check_container_uri() {
+        for uri in [
+            "http://127.0.0.1:8080/credentials",
+            "http://localhost/credentials",
+            "http://[::1]/credentials",
+            "http://169.254.170.2/v2/credentials",
+            "http://169.254.170.23/v1/credentials",
+            "http://[fd00:ec2::23]/v1/credentials",
+            "https://credentials.example.com/",
+        ] {
+            assert!(check_container_uri(uri).is_ok(), "{}", uri);
+        }
+        for uri in [
+            "http://credentials.example.com/",
+            "http://10.0.0.1/credentials",
+            "http://169.254.169.254/credentials",
+            "ftp://127.0.0.1/credentials",
+            "not a uri",
+        ] {
+            assert!(check_container_uri(uri).is_err(), "{}", uri);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_container_provider_untrusted_host() {
+        // rejected before the token is sent
+        let provider = ContainerProvider {
+            uri: "http://credentials.example.com/".to_string(),
+            token: Some("secret".to_string()),
+            token_file: None,
+        };
+        let err = provider.credentials().await.err().unwrap().to_string();
+        assert!(err.contains("is not allowed"));
+    }
+
+    fn role_response(access_key: &str, session_token: &str) -> String {
+        format!(
+            "<AssumeRoleResponse \
+             xmlns=\"https://sts.amazonaws.com/doc/2011-06-15/\">\
+             <AssumeRoleResult><Credentials>\
+             <AccessKeyId>{}</AccessKeyId>\
+             <SecretAccessKey>secret</SecretAccessKey>\
+             <SessionToken>{}</SessionToken>\
+             <Expiration>2099-01-01T00:00:00.123Z</Expiration>\
+             </Credentials></AssumeRoleResult></AssumeRoleResponse>",
+            access_key, session_token
+        )
+    }
+
+    #[tokio::test]
+    async fn test_assume_role_chain() {
+        // audit is assumed with the credentials of admin, which is
+        // assumed with the keys of base
+        let (endpoint, requests) = mock_endpoint(|request| {
+            let body = String::from_utf8_lossy(&request.body);
+            let param = |name| query_param(&format!("?{}", body), name);
+            let authorization = request
+                .headers
+                .get("authorization")
+                .map_or("", String::as_str);
+            let token = request.headers.get("x-amz-security-token");
+            let signed_by = |access_key: &str| {
+                authorization.contains(&format!("Credential={}/", access_key))
+                    && authorization.contains("/eu-west-1/sts/aws4_request")
+            };
+            if request.method != "POST"
+                || param("Action").as_deref() != Some("AssumeRole")
+            {
+                return MockResponse::new(400, "");
+            }
+            match param("RoleArn").as_deref() {
+                Some("arn:aws:iam::123456789012:role/admin")
+                    if signed_by("AKIDBASE")
+                        && token.is_none()
+                        && param("ExternalId").as_deref() == Some("ext") =>
+                {
+                    MockResponse::new(
+                        200,
+                        role_response("AKIDADMIN", "admin-token"),
+                    )
+                }
+                Some("arn:aws:iam::210987654321:role/audit")
+                    if signed_by("AKIDADMIN")
+                        && token.map(String::as_str) == Some("admin-token")
+                        && param("RoleSessionName").as_deref()
+                            == Some("auditor") =>
+                {
+                    MockResponse::new(
+                        200,
+                        role_response("AKIDAUDIT", "audit-token"),
+                    )
+                }
+                _ => MockResponse::new(403, "<ErrorResponse/>"),
+            }
+        })
+        .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let config_file = dir.path().join("config");
+        std::fs::write(
+            &config_file,
+            "
+[profile base]
+aws_access_key_id = AKIDBASE
+aws_secret_access_key = base-secret
+
+[profile admin]
+role_arn = arn:aws:iam::123456789012:role/admin
+source_profile = base
+external_id = ext
+
+[profile audit]
+role_arn = arn:aws:iam::210987654321:role/audit
+source_profile = admin
+role_session_name = auditor
+
+[profile denied]
+role_arn = arn:aws:iam::123456789012:role/denied
+source_profile = base
+",
+        )
+        .unwrap();
+        let mut config = EnvironmentConfig::default();
+        for (key, value) in [
+            ("AWS_CONFIG_FILE", config_file.to_string_lossy().as_ref()),
+            ("AWS_SHARED_CREDENTIALS_FILE", "/nonexistent"),
+            ("AWS_REGION", "eu-west-1"),
+            ("AWS_ENDPOINT_URL_STS", &endpoint),
+        ] {
+            config.set(key.to_string(), value.to_string());
+        }
+
+        let provider = AssumeRoleProvider {
+            profile: "audit".to_string(),
+            config: config.clone(),
+        };
+        let credentials = provider.credentials().await.unwrap();
+        assert_eq!(credentials.access_key(), "AKIDAUDIT");
+        assert_eq!(credentials.session_token(), Some("audit-token"));
+        assert_eq!(credentials.expiration(), Some(4070908800));
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        let denied = AssumeRoleProvider {
+            profile: "denied".to_string(),
+            config,
+        };
+        let err = denied.credentials().await.err().unwrap().to_string();
+        assert!(err.contains("Failed to assume role"));
+        assert!(err.contains("status 403"));
+    }
+}
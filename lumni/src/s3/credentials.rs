@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::bucket::S3Credentials;
+use super::config::no_sign_request;
+use crate::{EnvironmentConfig, LakestreamError};
+
+// Source of the credentials used to sign requests
+#[async_trait(?Send)]
+pub trait CredentialProvider: Send + Sync {
+    async fn credentials(&self) -> Result<S3Credentials, LakestreamError>;
+}
+
+pub type SharedCredentialProvider = Arc<dyn CredentialProvider>;
+
+// Credentials that do not change, e.g. keys from the config, environment
+// or profile
+pub struct StaticProvider(S3Credentials);
+
+impl StaticProvider {
+    pub fn new(credentials: S3Credentials) -> Self {
+        StaticProvider(credentials)
+    }
+}
+
+#[async_trait(?Send)]
+impl CredentialProvider for StaticProvider {
+    async fn credentials(&self) -> Result<S3Credentials, LakestreamError> {
+        Ok(self.0.clone())
+    }
+}
+
+// Returns None when requests are not signed (S3_NO_SIGN_REQUEST), otherwise
+// providers are tried in order:
+// 1. keys set in the config, environment or profile (see validate_config)
+// 2. the role of the profile, assumed via its source_profile chain
+// 3. credential_process of the profile
+// 4. the container credentials endpoint (ECS, EKS pod identity)
+// 5. the instance metadata service (IMDSv2)
+pub fn credential_provider(
+    config: &EnvironmentConfig,
+) -> Option<SharedCredentialProvider> {
+    // validated in validate_config
+    if no_sign_request(config).unwrap_or_default() {
+        return None;
+    }
+    if let (Some(access_key), Some(secret_key)) = (
+        config.get("AWS_ACCESS_KEY_ID"),
+        config.get("AWS_SECRET_ACCESS_KEY"),
+    ) {
+        return Some(Arc::new(StaticProvider::new(S3Credentials::new(
+            access_key.to_string(),
+            secret_key.to_string(),
+            config.get("AWS_SESSION_TOKEN").cloned(),
+        ))));
+    }
+    Some(default_provider(config))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn default_provider(config: &EnvironmentConfig) -> SharedCredentialProvider {
+    super::credential_providers::default_provider(config)
+}
+
+// only static credentials are supported in the browser
+#[cfg(target_arch = "wasm32")]
+fn default_provider(_config: &EnvironmentConfig) -> SharedCredentialProvider {
+    struct MissingCredentials;
+
+    #[async_trait(?Send)]
+    impl CredentialProvider for MissingCredentials {
+        async fn credentials(&self) -> Result<S3Credentials, LakestreamError> {
+            Err(LakestreamError::ConfigError(
+                "AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY are not set"
+                    .to_string(),
+            ))
+        }
+    }
+    Arc::new(MissingCredentials)
+}
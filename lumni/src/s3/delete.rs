@@ -0,0 +1,217 @@
+use bytes::Bytes;
+use log::info;
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+use super::bucket::S3Bucket;
+use super::client_headers::Headers;
+use super::list::create_s3_client;
+use super::parse_http_response::parse_delete_result;
+use super::request_handler::http_with_redirect_handling;
+use crate::{
+    DeleteResult, LakestreamError, ObjectStoreTrait, AWS_MAX_DELETE_OBJECTS,
+};
+
+pub async fn delete_object(
+    s3_bucket: &S3Bucket,
+    object_key: &str,
+) -> Result<(), LakestreamError> {
+    let s3_client =
+        create_s3_client(s3_bucket.config(), Some(s3_bucket.name()));
+
+    info!("Deleting object: {}", object_key);
+    let (_body_bytes, _updated_s3_client, status_code, _response_headers) =
+        http_with_redirect_handling(
+            &s3_client,
+            |s3_client| s3_client.generate_delete_object_headers(object_key),
+            "DELETE",
+            None,
+        )
+        .await?;
+
+    // S3 returns 204 also when the object does not exist
+    if !(200..300).contains(&status_code) {
+        return Err(LakestreamError::String(format!(
+            "Failed to delete object {}: status code {}",
+            object_key, status_code
+        )));
+    }
+    Ok(())
+}
+
+pub async fn delete_objects(
+    s3_bucket: &S3Bucket,
+    object_keys: &[String],
+) -> Result<Vec<DeleteResult>, LakestreamError> {
+    let mut results = Vec::with_capacity(object_keys.len());
+    // a single DeleteObjects request is limited to 1000 keys
+    for keys in object_keys.chunks(AWS_MAX_DELETE_OBJECTS) {
+        results.extend(delete_objects_batch(s3_bucket, keys).await?);
+    }
+    Ok(results)
+}
+
+async fn delete_objects_batch(
+    s3_bucket: &S3Bucket,
+    object_keys: &[String],
+) -> Result<Vec<DeleteResult>, LakestreamError> {
+    let s3_client =
+        create_s3_client(s3_bucket.config(), Some(s3_bucket.name()));
+
+    let body = delete_objects_body(object_keys);
+    let payload_hash = hex::encode(Sha256::digest(body.as_bytes()));
+    let content_md5 = base64::Engine::encode(
+        &base64::engine::general_purpose::STANDARD,
+        Md5::digest(body.as_bytes()),
+    );
+
+    info!("Deleting {} objects", object_keys.len());
+    let (body_bytes, _updated_s3_client, status_code, _response_headers) =
+        http_with_redirect_handling(
+            &s3_client,
+            |s3_client| {
+                s3_client.generate_delete_objects_headers(
+                    &payload_hash,
+                    &content_md5,
+                )
+            },
+            "POST",
+            Some(Bytes::from(body.clone())),
+        )
+        .await?;
+
+    if !(200..300).contains(&status_code) {
+        return Err(LakestreamError::String(format!(
+            "Failed to delete objects: status code {}",
+            status_code
+        )));
+    }
+
+    let response = String::from_utf8_lossy(&body_bytes);
+    parse_delete_result(&response).map_err(LakestreamError::from)
+}
+
+fn delete_objects_body(object_keys: &[String]) -> String {
+    let objects = object_keys
+        .iter()
+        .map(|key| format!("<Object><Key>{}</Key></Object>", xml_escape(key)))
+        .collect::<String>();
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+         <Delete><Quiet>false</Quiet>{}</Delete>",
+        objects
+    )
+}
+
+fn xml_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::http::mock_endpoint::{
+        mock_endpoint, mock_s3_config, MockResponse,
+    };
+
+    const SPECIAL_KEY: &str = "a&b<c>\"d'.txt";
+
+    // keys of the <Object> elements, as (escaped) XML text
+    fn request_keys(body: &str) -> Vec<String> {
+        body.split("<Key>")
+            .skip(1)
+            .filter_map(|part| part.split_once("</Key>"))
+            .map(|(key, _)| key.to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_delete_objects_in_chunks() {
+        let bodies = Arc::new(Mutex::new(Vec::new()));
+        let request_bodies = bodies.clone();
+        let (endpoint, requests) = mock_endpoint(move |request| {
+            if request.method != "POST"
+                || !request.path.ends_with("?delete")
+                || !request.headers.contains_key("content-md5")
+            {
+                return MockResponse::new(400, "");
+            }
+            let body = String::from_utf8_lossy(&request.body).to_string();
+            // a key in each request is denied, the first key of the
+            // second request without a message
+            let entries = request_keys(&body)
+                .iter()
+                .enumerate()
+                .map(|(index, key)| match index {
+                    7 => format!(
+                        "<Error><Key>{}</Key><Code>AccessDenied</Code>\
+                         <Message>Access Denied</Message></Error>",
+                        key
+                    ),
+                    0 if key.starts_with("key") => format!(
+                        "<Error><Key>{}</Key><Code>AccessDenied</Code>\
+                         </Error>",
+                        key
+                    ),
+                    _ => format!("<Deleted><Key>{}</Key></Deleted>", key),
+                })
+                .collect::<String>();
+            request_bodies.lock().unwrap().push(body);
+            MockResponse::new(
+                200,
+                format!("<DeleteResult>{}</DeleteResult>", entries),
+            )
+        })
+        .await;
+
+        let bucket =
+            S3Bucket::new("bucket", mock_s3_config(&endpoint)).unwrap();
+        let mut keys = vec![SPECIAL_KEY.to_string()];
+        keys.extend((1..1001).map(|index| format!("key{}", index)));
+        let results = delete_objects(&bucket, &keys).await.unwrap();
+
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+        let bodies = bodies.lock().unwrap();
+        assert_eq!(request_keys(&bodies[0]).len(), AWS_MAX_DELETE_OBJECTS);
+        assert_eq!(request_keys(&bodies[1]), ["key1000"]);
+        assert!(bodies[0].contains(
+            "<Object><Key>a&amp;b&lt;c&gt;&quot;d&apos;.txt</Key></Object>"
+        ));
+
+        // a result for every key, in order and across both requests
+        assert_eq!(results.len(), 1001);
+        let result_keys = results
+            .iter()
+            .map(|result| result.key())
+            .collect::<Vec<_>>();
+        assert_eq!(result_keys, keys);
+        assert_eq!(results[0], DeleteResult::deleted(SPECIAL_KEY));
+        assert_eq!(
+            results[7],
+            DeleteResult::failed("key7", "AccessDenied: Access Denied")
+        );
+        assert_eq!(
+            results[1000],
+            DeleteResult::failed("key1000", "AccessDenied")
+        );
+        let failed = results
+            .iter()
+            .filter(|result| result.error().is_some())
+            .count();
+        assert_eq!(failed, 2);
+    }
+}
@@ -1,35 +1,460 @@
-use log::info;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use log::{info, warn};
 
 use super::bucket::S3Bucket;
+use super::checksum::{etag_part_count, multipart_part_sizes, EtagHasher};
 use super::client_headers::Headers;
+use super::config::verify_checksum;
 use super::list::create_s3_client;
-use super::request_handler::http_with_redirect_handling;
-use crate::{LakestreamError, ObjectStoreTrait};
+use super::multipart::MultipartSettings;
+use super::request_handler::{
+    http_stream_with_redirect_handling, http_with_redirect_handling,
+};
+use crate::{
+    BinaryCallbackWrapper, ByteRange, LakestreamError, ObjectStoreTrait,
+    S3_MULTIPART_PART_SIZE,
+};
 
 pub async fn get_object(
     s3_bucket: &S3Bucket,
     object_key: &str,
+    range: Option<&ByteRange>,
     data: &mut Vec<u8>,
 ) -> Result<(), LakestreamError> {
     let s3_client =
         create_s3_client(s3_bucket.config(), Some(s3_bucket.name()));
 
     info!("Getting object: {}", object_key);
-    let (body_bytes, _updated_s3_client, _status_code, _response_headers) =
+    let (body_bytes, _updated_s3_client, status_code, response_headers) =
         http_with_redirect_handling(
             &s3_client,
-            |s3_client| s3_client.generate_get_object_headers(object_key),
+            |s3_client| {
+                s3_client.generate_get_object_headers(object_key, range)
+            },
             "GET",
+            None,
         )
         .await?;
+    check_get_status(object_key, status_code, range)?;
     info!(
         "Got object: {} of size {} bytes",
         object_key,
         body_bytes.len()
     );
+    if verify_checksum(s3_bucket.config())? {
+        if let Some(etag) =
+            etag_to_verify(object_key, status_code, &response_headers)
+        {
+            let part_count = etag_part_count(etag);
+            let settings = MultipartSettings::from_config(s3_bucket.config())?;
+            let object_size = body_bytes.len() as u64;
+            let mut hasher = EtagHasher::new(&multipart_part_sizes(
+                object_size,
+                part_count,
+                &default_part_sizes(&settings, object_size),
+            ));
+            hasher.update(&body_bytes);
+            verify_etag(object_key, hasher, etag)?;
+        }
+    }
     // Write response body directly into the provided Vec<u8>
     data.clear();
     data.extend_from_slice(&body_bytes);
 
     Ok(())
 }
+
+// The data is hashed while it is passed on to the callback, so a
+// checksum mismatch (S3_VERIFY_CHECKSUM) is only detected after the
+// callback received all data. Callers that write the data to a file
+// should only keep the file when this returns Ok
+pub async fn stream_object(
+    s3_bucket: &S3Bucket,
+    object_key: &str,
+    range: Option<&ByteRange>,
+    callback: &BinaryCallbackWrapper,
+) -> Result<(), LakestreamError> {
+    let s3_client =
+        create_s3_client(s3_bucket.config(), Some(s3_bucket.name()));
+
+    info!("Streaming object: {}", object_key);
+    // a byte range is not verified
+    if range.is_some() {
+        let (callback, on_headers) = partial_content_callback(callback);
+        let (status_code, _response_headers) =
+            http_stream_with_redirect_handling(
+                &s3_client,
+                |s3_client| {
+                    s3_client.generate_get_object_headers(object_key, range)
+                },
+                "GET",
+                &callback,
+                Some(&on_headers),
+            )
+            .await?;
+        return check_get_status(object_key, status_code, range);
+    }
+    if !verify_checksum(s3_bucket.config())? {
+        let (status_code, _response_headers) =
+            http_stream_with_redirect_handling(
+                &s3_client,
+                |s3_client| {
+                    s3_client.generate_get_object_headers(object_key, range)
+                },
+                "GET",
+                callback,
+                None,
+            )
+            .await?;
+        return check_get_status(object_key, status_code, range);
+    }
+
+    // the part sizes to hash with follow from the size and ETag of the
+    // object, which are known once the response headers are received
+    let settings = MultipartSettings::from_config(s3_bucket.config())?;
+    let hasher: Arc<Mutex<Option<EtagHasher>>> = Arc::new(Mutex::new(None));
+    let on_headers = {
+        let hasher = hasher.clone();
+        move |headers: &HashMap<String, String>| {
+            let part_sizes = stream_part_sizes(&settings, headers);
+            *hasher.lock().unwrap() = Some(EtagHasher::new(&part_sizes));
+        }
+    };
+    let hashing_callback = hashing_callback(callback, hasher.clone());
+    let (status_code, response_headers) = http_stream_with_redirect_handling(
+        &s3_client,
+        |s3_client| s3_client.generate_get_object_headers(object_key, range),
+        "GET",
+        &hashing_callback,
+        Some(&on_headers),
+    )
+    .await?;
+    check_get_status(object_key, status_code, range)?;
+
+    let hasher = hasher.lock().unwrap().take();
+    if let (Some(etag), Some(hasher)) = (
+        etag_to_verify(object_key, status_code, &response_headers),
+        hasher,
+    ) {
+        verify_etag(object_key, hasher, etag)?;
+    }
+    Ok(())
+}
+
+fn hashing_callback(
+    callback: &BinaryCallbackWrapper,
+    hasher: Arc<Mutex<Option<EtagHasher>>>,
+) -> BinaryCallbackWrapper {
+    let BinaryCallbackWrapper::Async(inner) = callback;
+    let inner = inner.clone();
+    BinaryCallbackWrapper::create_async(move |data: Vec<u8>| {
+        if let Some(hasher) = hasher.lock().unwrap().as_mut() {
+            hasher.update(&data);
+        }
+        inner(data)
+    })
+}
+
+// a server that ignores the Range header responds with the complete
+// object (200 instead of 206), which is not passed on to the callback
+fn partial_content_callback(
+    callback: &BinaryCallbackWrapper,
+) -> (BinaryCallbackWrapper, impl Fn(&HashMap<String, String>)) {
+    let is_partial = Arc::new(AtomicBool::new(false));
+    let on_headers = {
+        let is_partial = is_partial.clone();
+        move |headers: &HashMap<String, String>| {
+            let has_range = headers.contains_key("content-range");
+            is_partial.store(has_range, Ordering::SeqCst);
+        }
+    };
+    let BinaryCallbackWrapper::Async(inner) = callback;
+    let inner = inner.clone();
+    let callback = BinaryCallbackWrapper::create_async(move |data: Vec<u8>| {
+        let forward = is_partial.load(Ordering::SeqCst);
+        let inner = inner.clone();
+        async move {
+            if forward {
+                inner(data).await;
+            }
+        }
+    });
+    (callback, on_headers)
+}
+
+// part sizes of a multipart upload of the object, derived from the
+// Content-Length and the part count of the response
+fn stream_part_sizes(
+    settings: &MultipartSettings,
+    headers: &HashMap<String, String>,
+) -> Vec<u64> {
+    let object_size = headers
+        .get("content-length")
+        .and_then(|length| length.parse::<u64>().ok())
+        .unwrap_or(0);
+    let part_count = headers
+        .get("x-amz-mp-parts-count")
+        .and_then(|count| count.parse::<u64>().ok())
+        .or_else(|| headers.get("etag").map(|etag| etag_part_count(etag)))
+        .unwrap_or(0);
+    multipart_part_sizes(
+        object_size,
+        part_count,
+        &default_part_sizes(settings, object_size),
+    )
+}
+
+// part sizes to try when the size and part count do not determine it
+fn default_part_sizes(
+    settings: &MultipartSettings,
+    object_size: u64,
+) -> [u64; 2] {
+    [settings.part_size(object_size), S3_MULTIPART_PART_SIZE]
+}
+
+// ETag of a complete object. Not returned for byte ranges, or objects
+// encrypted with SSE-KMS or SSE-C as their ETag is not a MD5 digest
+fn etag_to_verify<'a>(
+    object_key: &str,
+    status_code: u16,
+    headers: &'a HashMap<String, String>,
+) -> Option<&'a str> {
+    if status_code != 200 {
+        return None;
+    }
+    let is_kms = headers
+        .get("x-amz-server-side-encryption")
+        .is_some_and(|encryption| encryption.starts_with("aws:kms"));
+    let is_sse_c =
+        headers.contains_key("x-amz-server-side-encryption-customer-algorithm");
+    if is_kms || is_sse_c {
+        warn!(
+            "ETag of {} is not verified, the object is encrypted with a \
+             customer or KMS key",
+            object_key
+        );
+        return None;
+    }
+    let etag = headers.get("etag").map(String::as_str);
+    if etag.is_none() {
+        warn!("ETag of {} is not verified, no ETag returned", object_key);
+    }
+    etag
+}
+
+fn verify_etag(
+    object_key: &str,
+    hasher: EtagHasher,
+    etag: &str,
+) -> Result<(), LakestreamError> {
+    hasher.verify(etag).map_err(|err| {
+        LakestreamError::String(format!(
+            "Checksum verification failed for {}: {}",
+            object_key, err
+        ))
+    })?;
+    info!("Verified ETag of {}", object_key);
+    Ok(())
+}
+
+// 200 for a full object, 206 (Partial Content) for a byte range
+fn check_get_status(
+    object_key: &str,
+    status_code: u16,
+    range: Option<&ByteRange>,
+) -> Result<(), LakestreamError> {
+    match (status_code, range) {
+        (200, None) | (206, Some(_)) => Ok(()),
+        (200, Some(range)) => Err(LakestreamError::String(format!(
+            "Range {} of {} was ignored, the server returned the complete \
+             object",
+            range, object_key
+        ))),
+        (206, None) => Err(LakestreamError::String(format!(
+            "Partial content returned for {} without a requested range",
+            object_key
+        ))),
+        (404, _) => Err(LakestreamError::NotFound(format!(
+            "Object not found for key: {}",
+            object_key
+        ))),
+        (416, _) => Err(LakestreamError::String(format!(
+            "Requested range not satisfiable for key: {}",
+            object_key
+        ))),
+        _ => Err(LakestreamError::String(format!(
+            "Failed to get object {}: status code {}",
+            object_key, status_code
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use md5::{Digest, Md5};
+
+    use super::*;
+    use crate::http::mock_endpoint::{
+        mock_endpoint, mock_s3_config, MockResponse,
+    };
+    use crate::EnvironmentConfig;
+
+    async fn mock_bucket(
+        data: Vec<u8>,
+        etag: String,
+    ) -> (S3Bucket, Arc<std::sync::atomic::AtomicUsize>) {
+        let (endpoint, requests) =
+            mock_endpoint(move |request| match request.method.as_str() {
+                "GET" => MockResponse::new(200, data.clone())
+                    .with_header("ETag", &format!("\"{}\"", etag)),
+                _ => MockResponse::new(405, ""),
+            })
+            .await;
+        let mut config = mock_s3_config(&endpoint);
+        config.set("S3_VERIFY_CHECKSUM".to_string(), "true".to_string());
+        (S3Bucket::new("testbucket", config).unwrap(), requests)
+    }
+
+    async fn stream(s3_bucket: &S3Bucket) -> Result<Vec<u8>, LakestreamError> {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let data = received.clone();
+        let callback = BinaryCallbackWrapper::create_async(move |chunk| {
+            data.lock().unwrap().extend(chunk);
+            async {}
+        });
+        stream_object(s3_bucket, "key", None, &callback).await?;
+        let received = received.lock().unwrap().clone();
+        Ok(received)
+    }
+
+    #[tokio::test]
+    async fn test_get_verify_etag() {
+        let data = (0..3000u32).map(|i| i as u8).collect::<Vec<u8>>();
+        let etag = hex::encode(Md5::digest(&data));
+
+        let (s3_bucket, requests) =
+            mock_bucket(data.clone(), etag.clone()).await;
+        let mut collected = Vec::new();
+        get_object(&s3_bucket, "key", None, &mut collected)
+            .await
+            .unwrap();
+        assert_eq!(collected, data);
+        assert_eq!(stream(&s3_bucket).await.unwrap(), data);
+        // verified from the GET response alone
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        let (s3_bucket, _) = mock_bucket(data, "0".repeat(32)).await;
+        let err = get_object(&s3_bucket, "key", None, &mut collected)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Checksum verification failed"));
+        let err = stream(&s3_bucket).await.unwrap_err();
+        assert!(err.to_string().contains("Checksum verification failed"));
+    }
+
+    #[tokio::test]
+    async fn test_stream_verify_multipart_etag() {
+        // three parts of 1000 bytes, the part size follows from the
+        // Content-Length and the part count of the ETag
+        let data = (0..3000u32).map(|i| (i % 251) as u8).collect::<Vec<u8>>();
+        let digests = data
+            .chunks(1000)
+            .flat_map(|part| Md5::digest(part).to_vec())
+            .collect::<Vec<u8>>();
+        let etag = format!("{}-3", hex::encode(Md5::digest(&digests)));
+
+        let (s3_bucket, requests) = mock_bucket(data.clone(), etag).await;
+        assert_eq!(stream(&s3_bucket).await.unwrap(), data);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        let wrong_etag = format!("{}-3", "0".repeat(32));
+        let (s3_bucket, _) = mock_bucket(data, wrong_etag).await;
+        assert!(stream(&s3_bucket).await.is_err());
+    }
+
+    #[test]
+    fn test_stream_part_sizes() {
+        const MIB: u64 = 1024 * 1024;
+        let settings =
+            MultipartSettings::from_config(&EnvironmentConfig::default())
+                .unwrap();
+        let headers = |size: u64, etag: &str, parts: Option<&str>| {
+            let mut headers = HashMap::from([
+                ("content-length".to_string(), size.to_string()),
+                ("etag".to_string(), etag.to_string()),
+            ]);
+            if let Some(parts) = parts {
+                headers.insert(
+                    "x-amz-mp-parts-count".to_string(),
+                    parts.to_string(),
+                );
+            }
+            headers
+        };
+
+        // the size and part count rule out the default part size
+        let part_sizes =
+            stream_part_sizes(&settings, &headers(3000, "\"abc-3\"", None));
+        assert_eq!(part_sizes, [1000]);
+        let part_sizes = stream_part_sizes(
+            &settings,
+            &headers(20 * MIB, "\"abc\"", Some("2")),
+        );
+        assert_eq!(part_sizes, [10 * MIB]);
+        // the default part size is kept when it gives the part count
+        let part_sizes =
+            stream_part_sizes(&settings, &headers(20 * MIB, "\"abc-3\"", None));
+        assert!(part_sizes.contains(&S3_MULTIPART_PART_SIZE));
+        // no multipart upload, only the object digest
+        let part_sizes =
+            stream_part_sizes(&settings, &headers(3000, "\"abc\"", None));
+        assert!(part_sizes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_range_requires_partial_content() {
+        // the object "ignored" is served by a server ignoring the range
+        let (endpoint, _) = mock_endpoint(|request| {
+            match (request.headers.get("range"), request.path.as_str()) {
+                (Some(range), "/testbucket/partial") => {
+                    assert_eq!(range, "bytes=2-4");
+                    MockResponse::new(206, "234")
+                        .with_header("Content-Range", "bytes 2-4/10")
+                }
+                _ => MockResponse::new(200, "0123456789"),
+            }
+        })
+        .await;
+        let mut config = mock_s3_config(&endpoint);
+        config.set("S3_ADDRESSING_STYLE".to_string(), "path".to_string());
+        let s3_bucket = S3Bucket::new("testbucket", config).unwrap();
+        let range = ByteRange::new(2, Some(3)).unwrap();
+
+        let mut data = Vec::new();
+        get_object(&s3_bucket, "partial", Some(&range), &mut data)
+            .await
+            .unwrap();
+        assert_eq!(data, b"234");
+        let err = get_object(&s3_bucket, "ignored", Some(&range), &mut data)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("was ignored"));
+
+        for (key, expected) in [("partial", &b"234"[..]), ("ignored", b"")] {
+            let received = Arc::new(Mutex::new(Vec::new()));
+            let chunks = received.clone();
+            let callback = BinaryCallbackWrapper::create_async(move |chunk| {
+                chunks.lock().unwrap().extend(chunk);
+                async {}
+            });
+            let result =
+                stream_object(&s3_bucket, key, Some(&range), &callback).await;
+            assert_eq!(result.is_ok(), key == "partial");
+            // the complete object is not passed on as the range
+            assert_eq!(received.lock().unwrap().as_slice(), expected);
+        }
+    }
+}
@@ -6,25 +6,61 @@ use super::bucket::S3Bucket;
 use super::client_headers::Headers;
 use super::list::create_s3_client;
 use super::request_handler::http_with_redirect_handling;
-use crate::{LakestreamError, ObjectStoreTrait};
+use crate::utils::time::http_date_to_epoch;
+use crate::{LakestreamError, ObjectMetadata, ObjectStoreTrait};
 
 pub async fn head_object(
     s3_bucket: &S3Bucket,
     object_key: &str,
-) -> Result<(u16, HashMap<String, String>), LakestreamError> {
+) -> Result<ObjectMetadata, LakestreamError> {
     let s3_client =
         create_s3_client(s3_bucket.config(), Some(s3_bucket.name()));
 
     info!("Head object: {}", object_key);
 
-    // TODO: head does not return a body, we need to update http_with_redirect_handling
-    // to return response headers as a separate object
     let (_body_bytes, _updated_s3_client, status_code, response_headers) =
         http_with_redirect_handling(
             &s3_client,
             |s3_client| s3_client.generate_head_object_headers(object_key),
             "HEAD",
+            None,
         )
         .await?;
-    Ok((status_code, response_headers))
+
+    match status_code {
+        200..=299 => Ok(metadata_from_headers(&response_headers)),
+        404 => Err(LakestreamError::NotFound(format!(
+            "Object not found for key: {}",
+            object_key
+        ))),
+        _ => Err(LakestreamError::String(format!(
+            "Failed to head object {}: status code {}",
+            object_key, status_code
+        ))),
+    }
+}
+
+fn metadata_from_headers(headers: &HashMap<String, String>) -> ObjectMetadata {
+    let size = headers
+        .get("content-length")
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(0);
+    let last_modified = headers
+        .get("last-modified")
+        .and_then(|value| http_date_to_epoch(value).ok());
+    let etag = headers
+        .get("etag")
+        .map(|value| value.trim_matches('"').to_string());
+    let content_type = headers.get("content-type").cloned();
+
+    // user-defined metadata is returned as x-amz-meta-* headers
+    let user_metadata = headers
+        .iter()
+        .filter_map(|(key, value)| {
+            key.strip_prefix("x-amz-meta-")
+                .map(|key| (key.to_string(), value.clone()))
+        })
+        .collect();
+
+    ObjectMetadata::new(size, last_modified, etag, content_type, user_metadata)
 }
@@ -1,11 +1,16 @@
 use std::collections::HashMap;
 
+use futures::stream::{self, StreamExt};
 use log::error;
 
-use super::bucket::{S3Bucket, S3Credentials};
+use super::bucket::S3Bucket;
 use super::client::S3Client;
 use super::client_config::S3ClientConfig;
 use super::client_headers::Headers;
+use super::config::{
+    addressing_style, http_client_config, parse_setting, retry_policy,
+};
+use super::credentials::credential_provider;
 use super::parse_http_response::{
     extract_continuation_token, parse_bucket_objects, parse_file_objects,
 };
@@ -15,7 +20,7 @@ use crate::http::requests::http_get_request;
 use crate::table::{FileObjectTable, ObjectStoreTable, Table};
 use crate::{
     FileObject, FileObjectFilter, LakestreamError, ObjectStoreTrait,
-    AWS_MAX_LIST_OBJECTS,
+    AWS_MAX_LIST_OBJECTS, S3_LIST_CONCURRENCY,
 };
 
 pub struct ListFilesParams<'a> {
@@ -25,6 +30,14 @@ pub struct ListFilesParams<'a> {
     continuation_token: Option<String>,
     recursive: bool,
     filter: &'a Option<FileObjectFilter>,
+    concurrency: usize,
+}
+
+// result of listing a single prefix (virtual directory)
+struct PrefixListing {
+    file_objects: Vec<FileObject>,
+    virtual_directories: Vec<String>,
+    s3_client: Option<S3Client>, // set when redirected to another region
 }
 
 pub async fn list_files(
@@ -38,110 +51,277 @@ pub async fn list_files(
 ) -> Result<(), LakestreamError> {
     let mut s3_client =
         create_s3_client(s3_bucket.config(), Some(s3_bucket.name()));
+    let concurrency = parse_setting(
+        s3_bucket.config(),
+        "S3_LIST_CONCURRENCY",
+        S3_LIST_CONCURRENCY,
+    )?
+    .max(1);
 
-    list_files_next(
-        &mut ListFilesParams {
-            prefix: prefix.map(|p| p.to_owned()),
-            max_keys,
-            s3_client: &mut s3_client,
-            continuation_token: None, // start with no continuation_token
-            recursive,
-            filter: &(*filter).clone(),
-        },
-        table,
-        selected_columns,
-    )
-    .await?;
+    let mut params = ListFilesParams {
+        prefix: prefix.map(|p| p.to_owned()),
+        max_keys,
+        s3_client: &mut s3_client,
+        continuation_token: None, // start with no continuation_token
+        recursive,
+        filter: &(*filter).clone(),
+        concurrency,
+    };
+
+    if is_flat_listing(s3_bucket.config(), recursive) {
+        list_files_flat(&mut params, table).await?;
+    } else {
+        list_files_next(&mut params, table, selected_columns).await?;
+    }
     Ok(())
 }
 
-async fn list_files_next(
+// a recursive listing pages through all keys under the prefix without a
+// delimiter, unless the virtual directories are walked. Used by both the
+// buffered and the streamed listing, so these list the same objects
+pub fn is_flat_listing(config: &EnvironmentConfig, recursive: bool) -> bool {
+    recursive && !walk_concurrently(config)
+}
+
+// set S3_LIST_FLAT to "false" to walk the virtual directories
+fn use_flat_listing(config: &EnvironmentConfig) -> bool {
+    config
+        .get("S3_LIST_FLAT")
+        .is_none_or(|value| !value.eq_ignore_ascii_case("false"))
+}
+
+// a recursive listing walks the virtual directories in parallel when
+// S3_LIST_CONCURRENCY is set, unless a flat listing is asked for explicitly
+fn walk_concurrently(config: &EnvironmentConfig) -> bool {
+    match config.get("S3_LIST_FLAT") {
+        Some(_) => !use_flat_listing(config),
+        None => config.get("S3_LIST_CONCURRENCY").is_some(),
+    }
+}
+
+async fn list_files_flat(
     params: &mut ListFilesParams<'_>,
     table: &mut FileObjectTable,
-    _selected_columns: &Option<Vec<&str>>, // not yet implemented
 ) -> Result<(), LakestreamError> {
-    let mut directory_stack = std::collections::VecDeque::new();
-    let mut temp_file_objects = Vec::new();
-
-    directory_stack.push_back(params.prefix.clone());
-    let mut object_count = 0usize;
-
     let effective_max_keys =
         get_effective_max_keys(params.filter, params.max_keys);
+    let prefix = params.prefix.clone();
+    // keys are listed in order, so the keys below a directory are listed
+    // consecutively. Comparing with the directories of the previous key
+    // is enough to add each directory once
+    let mut last_directories: Vec<String> = Vec::new();
 
-    while let Some(prefix) = directory_stack.pop_front() {
-        let mut virtual_directories = Vec::<String>::new();
-        loop {
-            let (
-                body_bytes,
-                updated_s3_client,
-                _status_code,
-                _response_headers,
-            ) = http_with_redirect_handling(
+    loop {
+        let (body_bytes, updated_s3_client, _status_code, _response_headers) =
+            http_with_redirect_handling(
                 params.s3_client,
                 |s3_client: &mut S3Client| {
                     s3_client.generate_list_objects_headers(
                         prefix.as_deref(),
                         Some(effective_max_keys),
                         params.continuation_token.as_deref(),
+                        None,
+                        None,
                     )
                 },
                 "GET",
+                None,
             )
             .await?;
 
-            if let Some(new_s3_client) = updated_s3_client {
-                *params.s3_client = new_s3_client;
-            }
-
-            let body = String::from_utf8_lossy(&body_bytes).to_string();
-            params.continuation_token = process_response_body(
-                &body,
-                params.recursive,
-                params.filter,
-                &mut temp_file_objects,
-                &mut virtual_directories,
-            );
+        if let Some(new_s3_client) = updated_s3_client {
+            *params.s3_client = new_s3_client;
+        }
 
-            object_count += temp_file_objects.len();
+        let body = String::from_utf8_lossy(&body_bytes).to_string();
+        let file_objects = if body.is_empty() {
+            Vec::new()
+        } else {
+            parse_file_objects(&body).unwrap_or_default()
+        };
 
-            if params.continuation_token.is_none()
-                || object_count
-                    >= params.max_keys.unwrap_or(AWS_MAX_LIST_OBJECTS) as usize
-            {
-                break;
+        let mut temp_file_objects = Vec::new();
+        for file_object in file_objects {
+            // without a delimiter no common prefixes are returned, directory
+            // rows are derived from the keys instead
+            if params.filter.is_none() {
+                let directories =
+                    parent_directories(prefix.as_deref(), file_object.name());
+                for directory in &directories {
+                    if !last_directories.contains(directory) {
+                        temp_file_objects.push(FileObject::new(
+                            directory.clone(),
+                            0,
+                            None,
+                            None,
+                        ));
+                    }
+                }
+                last_directories = directories;
+            }
+            // directory markers (keys ending with "/") are covered by the
+            // derived directory rows
+            if !file_object.name().ends_with('/') {
+                process_file_object(
+                    file_object,
+                    false,
+                    params.filter,
+                    &mut Vec::new(),
+                    &mut temp_file_objects,
+                );
             }
         }
 
-        // Before adding to table, check if adding these objects would exceed max_keys limit.
-        let max_to_add = params.max_keys.unwrap_or(AWS_MAX_LIST_OBJECTS)
-            as usize
-            - table.len();
+        let max_to_add = remaining_keys(params.max_keys, table.len());
         if !temp_file_objects.is_empty() && max_to_add > 0 {
-            let objects_to_add = temp_file_objects
-                .drain(..)
-                .take(max_to_add)
-                .collect::<Vec<_>>();
+            let objects_to_add =
+                temp_file_objects.into_iter().take(max_to_add).collect();
             table.add_file_objects(objects_to_add).await?;
         }
 
-        if params.recursive {
-            for virtual_directory in virtual_directories.drain(..) {
-                if table.len()
-                    == params.max_keys.unwrap_or(AWS_MAX_LIST_OBJECTS) as usize
-                {
-                    break;
-                }
-                directory_stack.push_back(Some(virtual_directory));
-            }
+        params.continuation_token = extract_continuation_token(&body);
+        if params.continuation_token.is_none()
+            || remaining_keys(params.max_keys, table.len()) == 0
+        {
+            break;
         }
-        params.continuation_token = None;
     }
+    Ok(())
+}
+
+// directories between the prefix and the key, e.g. for prefix "a/" and key
+// "a/b/c/d.txt" these are "a/b/" and "a/b/c/"
+pub fn parent_directories(prefix: Option<&str>, key: &str) -> Vec<String> {
+    let prefix = prefix.unwrap_or_default();
+    let relative_key = key.strip_prefix(prefix).unwrap_or(key);
+    relative_key
+        .match_indices('/')
+        .map(|(index, _)| format!("{}{}", prefix, &relative_key[..=index]))
+        .collect()
+}
+
+// walk the virtual directories level by level, listing up to
+// params.concurrency prefixes in parallel. Results are added in the order
+// of the prefixes, so the output is the same as when listed one by one
+async fn list_files_next(
+    params: &mut ListFilesParams<'_>,
+    table: &mut FileObjectTable,
+    _selected_columns: &Option<Vec<&str>>, // not yet implemented
+) -> Result<(), LakestreamError> {
+    let effective_max_keys =
+        get_effective_max_keys(params.filter, params.max_keys);
+    let mut prefixes = vec![params.prefix.clone()];
 
+    while !prefixes.is_empty() {
+        let s3_client = &*params.s3_client;
+        let (recursive, filter) = (params.recursive, params.filter);
+        let limit = remaining_keys(params.max_keys, table.len());
+
+        let listings = stream::iter(prefixes.drain(..))
+            .map(|prefix| {
+                list_prefix(
+                    s3_client,
+                    prefix,
+                    effective_max_keys,
+                    limit,
+                    recursive,
+                    filter,
+                )
+            })
+            .buffered(params.concurrency)
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut updated_s3_client = None;
+        for listing in listings {
+            let listing = listing?;
+            if listing.s3_client.is_some() {
+                updated_s3_client = listing.s3_client;
+            }
+
+            let max_to_add = remaining_keys(params.max_keys, table.len());
+            if max_to_add == 0 {
+                break;
+            }
+            if !listing.file_objects.is_empty() {
+                let objects_to_add = listing
+                    .file_objects
+                    .into_iter()
+                    .take(max_to_add)
+                    .collect::<Vec<_>>();
+                table.add_file_objects(objects_to_add).await?;
+            }
+            if params.recursive {
+                prefixes
+                    .extend(listing.virtual_directories.into_iter().map(Some));
+            }
+        }
+
+        if let Some(new_s3_client) = updated_s3_client {
+            *params.s3_client = new_s3_client;
+        }
+        if remaining_keys(params.max_keys, table.len()) == 0 {
+            break;
+        }
+    }
     Ok(())
 }
 
-fn process_file_object(
+// list all pages of a single prefix, up to limit objects
+async fn list_prefix(
+    s3_client: &S3Client,
+    prefix: Option<String>,
+    page_size: u32,
+    limit: usize,
+    recursive: bool,
+    filter: &Option<FileObjectFilter>,
+) -> Result<PrefixListing, LakestreamError> {
+    let mut listing = PrefixListing {
+        file_objects: Vec::new(),
+        virtual_directories: Vec::new(),
+        s3_client: None,
+    };
+    let mut continuation_token = None;
+
+    loop {
+        let (body_bytes, updated_s3_client, _status_code, _response_headers) =
+            http_with_redirect_handling(
+                listing.s3_client.as_ref().unwrap_or(s3_client),
+                |s3_client: &mut S3Client| {
+                    s3_client.generate_list_objects_headers(
+                        prefix.as_deref(),
+                        Some(page_size),
+                        continuation_token.as_deref(),
+                        None,
+                        Some("/"),
+                    )
+                },
+                "GET",
+                None,
+            )
+            .await?;
+
+        if updated_s3_client.is_some() {
+            listing.s3_client = updated_s3_client;
+        }
+
+        let body = String::from_utf8_lossy(&body_bytes).to_string();
+        continuation_token = process_response_body(
+            &body,
+            recursive,
+            filter,
+            &mut listing.file_objects,
+            &mut listing.virtual_directories,
+        );
+
+        if continuation_token.is_none() || listing.file_objects.len() >= limit {
+            break;
+        }
+    }
+    Ok(listing)
+}
+
+pub fn process_file_object(
     file_object: FileObject,
     recursive: bool,
     filter: &Option<FileObjectFilter>,
@@ -197,10 +377,16 @@ pub async fn list_buckets(
     max_files: Option<u32>,
     table: &mut ObjectStoreTable,
 ) -> Result<(), LakestreamError> {
-    let s3_client = create_s3_client(config, None);
+    let mut s3_client = create_s3_client(config, None);
+    s3_client.config.resolve_credentials().await?;
     let headers: HashMap<String, String> =
         s3_client.generate_list_buckets_headers().unwrap();
-    let result = http_get_request(&s3_client.url().clone(), &headers).await;
+    let result = http_get_request(
+        &s3_client.url().clone(),
+        &headers,
+        s3_client.config().http_client_config(),
+    )
+    .await;
     let mut object_count = 0usize;
 
     match result {
@@ -235,30 +421,35 @@ pub fn create_s3_client(
     let region = config
         .get("AWS_REGION")
         .expect("Missing region in the configuration");
-    let access_key = config
-        .get("AWS_ACCESS_KEY_ID")
-        .expect("Missing access_key in the configuration");
-    let secret_key = config
-        .get("AWS_SECRET_ACCESS_KEY")
-        .expect("Missing secret_key in the configuration");
-
-    let session_token = config.get("AWS_SESSION_TOKEN").map(|s| s.to_string());
-
-    let credentials = S3Credentials::new(
-        String::from(access_key),
-        String::from(secret_key),
-        session_token,
-    );
     let endpoint_url = config.get("S3_ENDPOINT_URL").map(String::as_str);
 
-    let s3_client_config =
-        S3ClientConfig::new(credentials, bucket_name, endpoint_url, region);
+    // retry and http settings are validated when the bucket is created
+    let retry_policy = retry_policy(config).unwrap_or_default();
+    let http_client_config = http_client_config(config).unwrap_or_default();
+    let addressing_style = addressing_style(config).unwrap_or_default();
+
+    let s3_client_config = S3ClientConfig::new(
+        credential_provider(config),
+        bucket_name,
+        endpoint_url,
+        region,
+        addressing_style,
+        retry_policy,
+        http_client_config,
+    );
     S3Client::new(s3_client_config)
 }
 
+// number of keys that can still be added, max_keys None means no limit
+fn remaining_keys(max_keys: Option<u32>, object_count: usize) -> usize {
+    max_keys.map_or(usize::MAX, |max_keys| {
+        (max_keys as usize).saturating_sub(object_count)
+    })
+}
+
 // when filter is provided, the effective max_keys is AWS_MAX_LIST_OBJECTS
 // because we are not sure how many objects will be filtered out
-fn get_effective_max_keys(
+pub fn get_effective_max_keys(
     filter: &Option<FileObjectFilter>,
     max_keys: Option<u32>,
 ) -> u32 {
@@ -268,3 +459,206 @@ fn get_effective_max_keys(
         max_keys.unwrap_or(AWS_MAX_LIST_OBJECTS)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use super::*;
+    use crate::http::mock_endpoint::{
+        mock_endpoint, mock_s3_config, query_param, MockResponse,
+    };
+    use crate::{TableCallback, TableColumnValue, TableRow};
+
+    struct CollectNames(Mutex<Vec<String>>);
+
+    impl TableCallback for CollectNames {
+        fn on_row_add(&self, row: &mut TableRow) {
+            for (column, value) in row.data() {
+                if let ("name", TableColumnValue::StringColumn(name)) =
+                    (column.as_str(), value)
+                {
+                    self.0.lock().unwrap().push(name.clone());
+                }
+            }
+        }
+    }
+
+    fn list_bucket_result(keys: &[String], prefixes: &[String]) -> String {
+        let contents = keys
+            .iter()
+            .map(|key| {
+                format!(
+                    "<Contents><Key>{}</Key><LastModified>\
+                     2024-01-01T00:00:00.000Z</LastModified><Size>1</Size>\
+                     <ETag>\"etag\"</ETag></Contents>",
+                    key
+                )
+            })
+            .collect::<String>();
+        let prefixes = prefixes
+            .iter()
+            .map(|prefix| {
+                format!(
+                    "<CommonPrefixes><Prefix>{}</Prefix></CommonPrefixes>",
+                    prefix
+                )
+            })
+            .collect::<String>();
+        format!(
+            "<ListBucketResult>{}{}</ListBucketResult>",
+            contents, prefixes
+        )
+    }
+
+    async fn list_names(
+        config: EnvironmentConfig,
+        recursive: bool,
+        max_keys: Option<u32>,
+    ) -> Vec<String> {
+        let bucket = S3Bucket::new("bucket", config).unwrap();
+        let names = Arc::new(CollectNames(Mutex::new(Vec::new())));
+        let mut table = FileObjectTable::new(&None);
+        table.set_callback(names.clone());
+        list_files(
+            &bucket, None, &None, recursive, max_keys, &None, &mut table,
+        )
+        .await
+        .unwrap();
+        let names = names.0.lock().unwrap().clone();
+        names
+    }
+
+    #[tokio::test]
+    async fn test_list_prefixes_concurrently() {
+        const PREFIXES: usize = 5;
+        // prefixes listed first respond last
+        let (endpoint, requests) = mock_endpoint(|request| {
+            let prefix =
+                query_param(&request.path, "prefix").unwrap_or_default();
+            if prefix.is_empty() {
+                let prefixes = (0..PREFIXES)
+                    .map(|index| format!("p{}/", index))
+                    .collect::<Vec<_>>();
+                return MockResponse::new(
+                    200,
+                    list_bucket_result(&[], &prefixes),
+                );
+            }
+            let index = prefix[1..prefix.len() - 1].parse::<u64>().unwrap();
+            let delay = Duration::from_millis((PREFIXES as u64 - index) * 30);
+            let keys = [format!("{}file", prefix)];
+            MockResponse::new(200, list_bucket_result(&keys, &[]))
+                .with_delay(delay)
+        })
+        .await;
+
+        let mut config = mock_s3_config(&endpoint);
+        config.set("S3_LIST_CONCURRENCY".to_string(), PREFIXES.to_string());
+        let names = list_names(config, true, None).await;
+
+        let mut expected = (0..PREFIXES)
+            .map(|index| format!("p{}/", index))
+            .collect::<Vec<_>>();
+        expected.extend((0..PREFIXES).map(|index| format!("p{}/file", index)));
+        assert_eq!(names, expected);
+        assert_eq!(
+            requests.load(std::sync::atomic::Ordering::SeqCst),
+            PREFIXES + 1
+        );
+    }
+
+    #[test]
+    fn test_parent_directories() {
+        let directories = |prefix, key| parent_directories(prefix, key);
+        assert_eq!(directories(None, "a/b/c.txt"), vec!["a/", "a/b/"]);
+        assert!(directories(None, "c.txt").is_empty());
+
+        // prefix with a trailing slash, its own directory is not included
+        assert_eq!(directories(Some("a/"), "a/b/c/d.txt"), ["a/b/", "a/b/c/"]);
+        assert!(directories(Some("a/"), "a/c.txt").is_empty());
+
+        // without a trailing slash the prefix matches partial names
+        assert_eq!(directories(Some("a/b"), "a/b/c.txt"), vec!["a/b/"]);
+        assert_eq!(directories(Some("a/b"), "a/bc/d.txt"), vec!["a/bc/"]);
+
+        // directory markers are directories themselves
+        assert_eq!(directories(Some("a/"), "a/b/c/"), vec!["a/b/", "a/b/c/"]);
+        assert!(directories(Some("a/"), "a/").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_flat_directories() {
+        let (endpoint, _) = mock_endpoint(|_| {
+            let keys = ["a/1", "a/b/", "a/b/2", "a/b/c/3", "a/d/4", "e/5", "f"]
+                .map(String::from);
+            MockResponse::new(200, list_bucket_result(&keys, &[]))
+        })
+        .await;
+        let mut config = mock_s3_config(&endpoint);
+        config.set("S3_LIST_FLAT".to_string(), "true".to_string());
+
+        // each directory is listed once, before the keys below it
+        let names = list_names(config, true, None).await;
+        assert_eq!(
+            names,
+            [
+                "a/", "a/1", "a/b/", "a/b/2", "a/b/c/", "a/b/c/3", "a/d/",
+                "a/d/4", "e/", "e/5", "f"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_all_pages() {
+        // 3 pages of a full page size, the last one truncated
+        const PAGE_SIZE: usize = AWS_MAX_LIST_OBJECTS as usize;
+        const PAGES: usize = 3;
+        const TOTAL: usize = 2 * PAGE_SIZE + 5;
+        let (endpoint, requests) = mock_endpoint(|request| {
+            let page = query_param(&request.path, "continuation-token")
+                .map_or(0, |token| token.parse::<usize>().unwrap());
+            let keys = (page * PAGE_SIZE..TOTAL.min((page + 1) * PAGE_SIZE))
+                .map(|index| format!("key{:05}", index))
+                .collect::<Vec<_>>();
+            let body = list_bucket_result(&keys, &[]);
+            let body = if page + 1 < PAGES {
+                body.replace(
+                    "</ListBucketResult>",
+                    &format!(
+                        "<IsTruncated>true</IsTruncated>\
+                         <NextContinuationToken>{}</NextContinuationToken>\
+                         </ListBucketResult>",
+                        page + 1
+                    ),
+                )
+            } else {
+                body
+            };
+            MockResponse::new(200, body)
+        })
+        .await;
+        let count = || requests.swap(0, std::sync::atomic::Ordering::SeqCst);
+        let expected = (0..TOTAL)
+            .map(|index| format!("key{:05}", index))
+            .collect::<Vec<_>>();
+
+        // without a limit, all pages are listed, both by walking the
+        // directories and by a flat listing
+        let config = mock_s3_config(&endpoint);
+        assert_eq!(list_names(config.clone(), false, None).await, expected);
+        assert_eq!(count(), PAGES);
+        assert_eq!(list_names(config.clone(), true, None).await, expected);
+        assert_eq!(count(), PAGES);
+
+        // a limit stops in the middle of the second page
+        let limit = PAGE_SIZE + PAGE_SIZE / 2;
+        for recursive in [false, true] {
+            let names =
+                list_names(config.clone(), recursive, Some(limit as u32)).await;
+            assert_eq!(names, expected[..limit]);
+            assert_eq!(count(), 2);
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use super::bucket::S3Bucket;
+use super::client::S3Client;
+use super::client_headers::Headers;
+use super::list::{create_s3_client, process_file_object};
+use super::parse_http_response::{
+    extract_continuation_token, parse_file_objects,
+};
+use super::request_handler::http_with_redirect_handling;
+use crate::{
+    FileObjectFilter, LakestreamError, ListPage, ListStart, ObjectStoreTrait,
+    AWS_MAX_LIST_OBJECTS,
+};
+
+// list a single page of objects. Recursive pages list all keys under the
+// prefix without (virtual) directory rows, so that the S3 continuation
+// token is all that is needed to resume
+pub async fn list_page(
+    s3_bucket: &S3Bucket,
+    prefix: Option<&str>,
+    recursive: bool,
+    max_keys: Option<u32>,
+    start: Option<&ListStart>,
+    filter: &Option<FileObjectFilter>,
+) -> Result<ListPage, LakestreamError> {
+    if let (Some(prefix), None) = (prefix, start) {
+        s3_bucket.check_prefix(prefix).await?;
+    }
+
+    let (continuation_token, start_after) = match start {
+        Some(ListStart::Token(token)) => (Some(token.as_str()), None),
+        Some(ListStart::After(key)) => (None, Some(key.as_str())),
+        None => (None, None),
+    };
+    let page_size = max_keys
+        .unwrap_or(AWS_MAX_LIST_OBJECTS)
+        .min(AWS_MAX_LIST_OBJECTS);
+    let delimiter = if recursive { None } else { Some("/") };
+
+    let s3_client =
+        create_s3_client(s3_bucket.config(), Some(s3_bucket.name()));
+    let (body_bytes, _updated_s3_client, _status_code, _response_headers) =
+        http_with_redirect_handling(
+            &s3_client,
+            |s3_client: &mut S3Client| {
+                s3_client.generate_list_objects_headers(
+                    prefix,
+                    Some(page_size),
+                    continuation_token,
+                    start_after,
+                    delimiter,
+                )
+            },
+            "GET",
+            None,
+        )
+        .await?;
+
+    let body = String::from_utf8_lossy(&body_bytes).to_string();
+    if body.is_empty() {
+        return Ok(ListPage::default());
+    }
+
+    let mut file_objects = Vec::new();
+    for file_object in parse_file_objects(&body).unwrap_or_default() {
+        // skip directory markers
+        if recursive && file_object.name().ends_with('/') {
+            continue;
+        }
+        process_file_object(
+            file_object,
+            false,
+            filter,
+            &mut Vec::new(),
+            &mut file_objects,
+        );
+    }
+    Ok(ListPage::new(
+        file_objects,
+        extract_continuation_token(&body),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::http::mock_endpoint::{
+        mock_endpoint, mock_s3_config, query_param, MockResponse,
+    };
+
+    const KEYS: [&str; 5] = ["k0", "k1", "k2", "k3", "k4"];
+
+    // the continuation-token and start-after of each request
+    type StartParams = Arc<Mutex<Vec<(Option<String>, Option<String>)>>>;
+
+    async fn mock_bucket() -> (S3Bucket, StartParams) {
+        let params = StartParams::default();
+        let recorded = params.clone();
+        let (endpoint, _) = mock_endpoint(move |request| {
+            let token = query_param(&request.path, "continuation-token");
+            let start_after = query_param(&request.path, "start-after");
+            let max_keys = query_param(&request.path, "max-keys")
+                .map_or(1000, |max_keys| max_keys.parse::<usize>().unwrap());
+            recorded
+                .lock()
+                .unwrap()
+                .push((token.clone(), start_after.clone()));
+
+            // tokens are opaque, "tok-<index>" of the next key here
+            let start = match (&token, &start_after) {
+                (Some(token), _) => {
+                    token.strip_prefix("tok-").unwrap().parse().unwrap()
+                }
+                (None, Some(key)) => {
+                    KEYS.iter().filter(|k| *k <= &key.as_str()).count()
+                }
+                (None, None) => 0,
+            };
+            let end = KEYS.len().min(start + max_keys);
+            let contents = KEYS[start..end]
+                .iter()
+                .map(|key| {
+                    format!(
+                        "<Contents><Key>{}</Key><LastModified>\
+                         2024-01-01T00:00:00.000Z</LastModified>\
+                         <Size>1</Size><ETag>\"etag\"</ETag></Contents>",
+                        key
+                    )
+                })
+                .collect::<String>();
+            let next = if end < KEYS.len() {
+                format!(
+                    "<IsTruncated>true</IsTruncated>\
+                     <NextContinuationToken>tok-{}</NextContinuationToken>",
+                    end
+                )
+            } else {
+                "<IsTruncated>false</IsTruncated>".to_string()
+            };
+            MockResponse::new(
+                200,
+                format!(
+                    "<ListBucketResult>{}{}</ListBucketResult>",
+                    contents, next
+                ),
+            )
+        })
+        .await;
+        let bucket =
+            S3Bucket::new("bucket", mock_s3_config(&endpoint)).unwrap();
+        (bucket, params)
+    }
+
+    async fn page(
+        bucket: &S3Bucket,
+        max_keys: u32,
+        start: Option<ListStart>,
+    ) -> (Vec<String>, Option<String>) {
+        let page = list_page(
+            bucket,
+            None,
+            true,
+            Some(max_keys),
+            start.as_ref(),
+            &None,
+        )
+        .await
+        .unwrap();
+        let (file_objects, next_token) = page.into_parts();
+        let names = file_objects
+            .iter()
+            .map(|file_object| file_object.name().to_string())
+            .collect();
+        (names, next_token)
+    }
+
+    fn last_params(params: &StartParams) -> (Option<String>, Option<String>) {
+        params.lock().unwrap().last().cloned().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_list_page_token() {
+        let (bucket, params) = mock_bucket().await;
+
+        let (names, next_token) = page(&bucket, 2, None).await;
+        assert_eq!(names, ["k0", "k1"]);
+        assert_eq!(next_token.as_deref(), Some("tok-2"));
+        assert_eq!(last_params(&params), (None, None));
+
+        // the token of a truncated page resumes the listing
+        let start = ListStart::Token(next_token.unwrap());
+        let (names, next_token) = page(&bucket, 2, Some(start)).await;
+        assert_eq!(names, ["k2", "k3"]);
+        assert_eq!(next_token.as_deref(), Some("tok-4"));
+        assert_eq!(last_params(&params), (Some("tok-2".to_string()), None));
+
+        // the final page has no next token
+        let start = ListStart::Token(next_token.unwrap());
+        let (names, next_token) = page(&bucket, 2, Some(start)).await;
+        assert_eq!(names, ["k4"]);
+        assert_eq!(next_token, None);
+        assert_eq!(last_params(&params), (Some("tok-4".to_string()), None));
+    }
+
+    #[tokio::test]
+    async fn test_list_page_after() {
+        let (bucket, params) = mock_bucket().await;
+
+        let start = ListStart::After("k0".to_string());
+        let (names, next_token) = page(&bucket, 2, Some(start)).await;
+        assert_eq!(names, ["k1", "k2"]);
+        assert_eq!(next_token.as_deref(), Some("tok-3"));
+        assert_eq!(last_params(&params), (None, Some("k0".to_string())));
+
+        let start = ListStart::After("k2".to_string());
+        let (names, next_token) = page(&bucket, 2, Some(start)).await;
+        assert_eq!(names, ["k3", "k4"]);
+        assert_eq!(next_token, None);
+        assert_eq!(last_params(&params), (None, Some("k2".to_string())));
+    }
+}
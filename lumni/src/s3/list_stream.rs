@@ -0,0 +1,349 @@
+use std::collections::VecDeque;
+
+use futures::stream::{self, StreamExt, TryStreamExt};
+
+use super::bucket::S3Bucket;
+use super::client::S3Client;
+use super::client_headers::Headers;
+use super::list::{
+    create_s3_client, get_effective_max_keys, is_flat_listing,
+    parent_directories, process_file_object,
+};
+use super::parse_http_response::{
+    extract_continuation_token, parse_file_objects,
+};
+use super::request_handler::http_with_redirect_handling;
+use crate::{
+    FileObject, FileObjectFilter, FileObjectStream, LakestreamError,
+    ObjectStoreTrait,
+};
+
+struct ListStreamState {
+    s3_bucket: S3Bucket,
+    s3_client: S3Client,
+    prefix: Option<String>, // prefix that is currently listed
+    pending_prefixes: VecDeque<Option<String>>,
+    continuation_token: Option<String>,
+    listing_prefix: bool,
+    started: bool,
+    delimiter: Option<&'static str>,
+    recursive: bool,
+    page_size: u32,
+    filter: Option<FileObjectFilter>,
+    // directories of the previous key, used in flat mode to derive
+    // directory rows without keeping track of all directories
+    last_directories: Vec<String>,
+}
+
+pub fn list_stream(
+    s3_bucket: &S3Bucket,
+    prefix: Option<&str>,
+    recursive: bool,
+    max_keys: Option<u32>,
+    filter: &Option<FileObjectFilter>,
+) -> FileObjectStream<'static> {
+    let flat = is_flat_listing(s3_bucket.config(), recursive);
+    let state = ListStreamState {
+        s3_bucket: s3_bucket.clone(),
+        s3_client: create_s3_client(s3_bucket.config(), Some(s3_bucket.name())),
+        prefix: None,
+        pending_prefixes: VecDeque::from([prefix.map(|p| p.to_owned())]),
+        continuation_token: None,
+        listing_prefix: false,
+        started: false,
+        delimiter: if flat { None } else { Some("/") },
+        recursive,
+        page_size: get_effective_max_keys(filter, max_keys),
+        filter: filter.clone(),
+        last_directories: Vec::new(),
+    };
+
+    let file_objects = stream::try_unfold(state, next_page)
+        .map_ok(|page| stream::iter(page.into_iter().map(Ok)))
+        .try_flatten();
+
+    match max_keys {
+        Some(max_keys) => Box::pin(file_objects.take(max_keys as usize)),
+        None => Box::pin(file_objects),
+    }
+}
+
+// fetch pages until there is one with objects, or all prefixes are listed
+async fn next_page(
+    mut state: ListStreamState,
+) -> Result<Option<(Vec<FileObject>, ListStreamState)>, LakestreamError> {
+    if !state.started {
+        state.started = true;
+        if let Some(Some(prefix)) = state.pending_prefixes.front() {
+            state.s3_bucket.check_prefix(prefix).await?;
+        }
+    }
+
+    loop {
+        if !state.listing_prefix {
+            match state.pending_prefixes.pop_front() {
+                Some(prefix) => {
+                    state.prefix = prefix;
+                    state.listing_prefix = true;
+                }
+                None => return Ok(None),
+            }
+        }
+
+        let (body_bytes, updated_s3_client, _status_code, _response_headers) =
+            http_with_redirect_handling(
+                &state.s3_client,
+                |s3_client: &mut S3Client| {
+                    s3_client.generate_list_objects_headers(
+                        state.prefix.as_deref(),
+                        Some(state.page_size),
+                        state.continuation_token.as_deref(),
+                        None,
+                        state.delimiter,
+                    )
+                },
+                "GET",
+                None,
+            )
+            .await?;
+
+        if let Some(new_s3_client) = updated_s3_client {
+            state.s3_client = new_s3_client;
+        }
+
+        let body = String::from_utf8_lossy(&body_bytes).to_string();
+        state.continuation_token = extract_continuation_token(&body);
+        if state.continuation_token.is_none() {
+            state.listing_prefix = false;
+        }
+
+        let file_objects = if body.is_empty() {
+            Vec::new()
+        } else {
+            parse_file_objects(&body).unwrap_or_default()
+        };
+        let page = if state.delimiter.is_none() {
+            flat_page(&mut state, file_objects)
+        } else {
+            prefix_page(&mut state, file_objects)
+        };
+
+        if !page.is_empty() {
+            return Ok(Some((page, state)));
+        }
+    }
+}
+
+fn flat_page(
+    state: &mut ListStreamState,
+    file_objects: Vec<FileObject>,
+) -> Vec<FileObject> {
+    let mut page = Vec::new();
+    for file_object in file_objects {
+        if state.filter.is_none() {
+            // keys are sorted, so all keys of a directory are listed
+            // consecutively
+            let directories =
+                parent_directories(state.prefix.as_deref(), file_object.name());
+            for directory in &directories {
+                if !state.last_directories.contains(directory) {
+                    page.push(FileObject::new(
+                        directory.clone(),
+                        0,
+                        None,
+                        None,
+                    ));
+                }
+            }
+            state.last_directories = directories;
+        }
+        // directory markers are covered by the derived directory rows
+        if !file_object.name().ends_with('/') {
+            process_file_object(
+                file_object,
+                false,
+                &state.filter,
+                &mut Vec::new(),
+                &mut page,
+            );
+        }
+    }
+    page
+}
+
+fn prefix_page(
+    state: &mut ListStreamState,
+    file_objects: Vec<FileObject>,
+) -> Vec<FileObject> {
+    let mut page = Vec::new();
+    let mut virtual_directories = Vec::new();
+    for file_object in file_objects {
+        process_file_object(
+            file_object,
+            state.recursive,
+            &state.filter,
+            &mut virtual_directories,
+            &mut page,
+        );
+    }
+    state
+        .pending_prefixes
+        .extend(virtual_directories.into_iter().map(Some));
+    page
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::http::mock_endpoint::{
+        mock_endpoint, mock_s3_config, query_param, MockResponse,
+    };
+    use crate::s3::list::list_files;
+    use crate::{
+        EnvironmentConfig, FileObjectTable, Table, TableCallback,
+        TableColumnValue, TableRow,
+    };
+
+    const KEYS: [&str; 9] = [
+        "a/1", "a/2", "a/b/3", "a/b/c/4", "a/b/c/5", "a/d/6", "e/7", "f", "g",
+    ];
+    // small pages, so that every listing spans several pages
+    const PAGE_SIZE: usize = 2;
+
+    // lists KEYS with and without a delimiter, in pages of PAGE_SIZE
+    fn list_response(path: &str) -> MockResponse {
+        let prefix = query_param(path, "prefix").unwrap_or_default();
+        let delimiter = query_param(path, "delimiter");
+        let start = query_param(path, "continuation-token")
+            .map_or(0, |token| token.parse::<usize>().unwrap());
+
+        let mut entries: Vec<(String, bool)> = Vec::new();
+        for key in KEYS.iter().filter(|key| key.starts_with(&prefix)) {
+            let rest = &key[prefix.len()..];
+            let entry = match (&delimiter, rest.find('/')) {
+                (Some(_), Some(index)) => {
+                    (format!("{}{}", prefix, &rest[..=index]), true)
+                }
+                _ => (key.to_string(), false),
+            };
+            if !entries.contains(&entry) {
+                entries.push(entry);
+            }
+        }
+        let end = entries.len().min(start + PAGE_SIZE);
+        let body = entries[start..end]
+            .iter()
+            .map(|(name, is_prefix)| match is_prefix {
+                true => format!(
+                    "<CommonPrefixes><Prefix>{}</Prefix></CommonPrefixes>",
+                    name
+                ),
+                false => format!(
+                    "<Contents><Key>{}</Key><LastModified>\
+                     2024-01-01T00:00:00.000Z</LastModified><Size>1</Size>\
+                     <ETag>\"etag\"</ETag></Contents>",
+                    name
+                ),
+            })
+            .collect::<String>();
+        let next = if end < entries.len() {
+            format!(
+                "<IsTruncated>true</IsTruncated>\
+                 <NextContinuationToken>{}</NextContinuationToken>",
+                end
+            )
+        } else {
+            String::new()
+        };
+        MockResponse::new(
+            200,
+            format!("<ListBucketResult>{}{}</ListBucketResult>", body, next),
+        )
+    }
+
+    struct CollectNames(Mutex<Vec<String>>);
+
+    impl TableCallback for CollectNames {
+        fn on_row_add(&self, row: &mut TableRow) {
+            for (column, value) in row.data() {
+                if let ("name", TableColumnValue::StringColumn(name)) =
+                    (column.as_str(), value)
+                {
+                    self.0.lock().unwrap().push(name.clone());
+                }
+            }
+        }
+    }
+
+    async fn buffered_names(
+        bucket: &S3Bucket,
+        recursive: bool,
+        max_keys: Option<u32>,
+    ) -> Vec<String> {
+        let names = Arc::new(CollectNames(Mutex::new(Vec::new())));
+        let mut table = FileObjectTable::new(&None);
+        table.set_callback(names.clone());
+        list_files(bucket, None, &None, recursive, max_keys, &None, &mut table)
+            .await
+            .unwrap();
+        let names = names.0.lock().unwrap().clone();
+        names
+    }
+
+    async fn streamed_names(
+        bucket: &S3Bucket,
+        recursive: bool,
+        max_keys: Option<u32>,
+    ) -> Vec<String> {
+        list_stream(bucket, None, recursive, max_keys, &None)
+            .map_ok(|file_object| file_object.name().to_string())
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_stream_matches_buffered_listing() {
+        let (endpoint, _) =
+            mock_endpoint(|request| list_response(&request.path)).await;
+        let config = |settings: &[(&str, &str)]| {
+            let mut config: EnvironmentConfig = mock_s3_config(&endpoint);
+            for (key, value) in settings {
+                config.set(key.to_string(), value.to_string());
+            }
+            config
+        };
+
+        // flat (default), and walking the directories as selected by
+        // S3_LIST_CONCURRENCY or S3_LIST_FLAT
+        for settings in [
+            &[][..],
+            &[("S3_LIST_CONCURRENCY", "4")][..],
+            &[("S3_LIST_FLAT", "false")][..],
+            &[("S3_LIST_FLAT", "true"), ("S3_LIST_CONCURRENCY", "4")][..],
+        ] {
+            let bucket = S3Bucket::new("bucket", config(settings)).unwrap();
+            for recursive in [false, true] {
+                for max_keys in [None, Some(5)] {
+                    let streamed =
+                        streamed_names(&bucket, recursive, max_keys).await;
+                    let buffered =
+                        buffered_names(&bucket, recursive, max_keys).await;
+                    assert_eq!(
+                        streamed, buffered,
+                        "{:?} recursive {} max_keys {:?}",
+                        settings, recursive, max_keys
+                    );
+                    if recursive && max_keys.is_none() {
+                        // every key is listed
+                        for key in KEYS {
+                            assert!(streamed.contains(&key.to_string()));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,12 +1,26 @@
 pub mod backend;
 mod bucket;
+mod checksum;
 mod client;
 mod client_config;
 mod client_headers;
 mod config;
+#[cfg(not(target_arch = "wasm32"))]
+mod credential_providers;
+mod credentials;
+mod delete;
 mod get;
 mod head;
 mod list;
+mod list_page;
+mod list_stream;
+mod multipart;
 mod parse_http_response;
+mod presign;
+mod profile;
+mod provider;
+mod put;
 mod request_builder;
 mod request_handler;
+#[cfg(not(target_arch = "wasm32"))]
+mod sts;
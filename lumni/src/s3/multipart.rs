@@ -0,0 +1,452 @@
+use std::collections::HashMap;
+
+use bytes::Bytes;
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use log::{info, warn};
+use sha2::{Digest, Sha256};
+
+use super::bucket::S3Bucket;
+use super::checksum::{Checksum, ChecksumAlgorithm};
+use super::client_headers::Headers;
+use super::config::{checksum_algorithm, parse_setting};
+use super::list::create_s3_client;
+use super::parse_http_response::{parse_error_response, parse_upload_id};
+use super::request_handler::http_with_redirect_handling;
+use crate::{
+    ByteStream, EnvironmentConfig, LakestreamError, ObjectStoreTrait,
+    AWS_MAX_PARTS, AWS_MIN_PART_SIZE, S3_MULTIPART_CONCURRENCY,
+    S3_MULTIPART_PART_SIZE, S3_MULTIPART_THRESHOLD,
+};
+
+#[derive(Debug, Clone)]
+pub struct MultipartSettings {
+    pub threshold: u64,
+    part_size: u64,
+    concurrency: usize,
+    checksum_algorithm: Option<ChecksumAlgorithm>,
+}
+
+// A part as listed when completing the upload
+struct CompletedPart {
+    part_number: u32,
+    etag: String,
+    checksum: Option<Checksum>,
+}
+
+impl MultipartSettings {
+    pub fn from_config(
+        config: &EnvironmentConfig,
+    ) -> Result<MultipartSettings, LakestreamError> {
+        let settings = MultipartSettings {
+            threshold: parse_setting(
+                config,
+                "S3_MULTIPART_THRESHOLD",
+                S3_MULTIPART_THRESHOLD,
+            )?,
+            part_size: parse_setting(
+                config,
+                "S3_MULTIPART_PART_SIZE",
+                S3_MULTIPART_PART_SIZE,
+            )?,
+            concurrency: parse_setting(
+                config,
+                "S3_MULTIPART_CONCURRENCY",
+                S3_MULTIPART_CONCURRENCY,
+            )?,
+            checksum_algorithm: checksum_algorithm(config)?,
+        };
+        if settings.concurrency == 0 {
+            return Err(LakestreamError::ConfigError(
+                "S3_MULTIPART_CONCURRENCY must be at least 1".to_string(),
+            ));
+        }
+        Ok(settings)
+    }
+
+    // the configured part size is raised if needed, parts must be at least
+    // 5 MiB and an upload can have at most 10000 parts
+    pub fn part_size(&self, object_size: u64) -> u64 {
+        let min_part_size = object_size.div_ceil(AWS_MAX_PARTS);
+        self.part_size.max(min_part_size).max(AWS_MIN_PART_SIZE)
+    }
+}
+
+// parts are read from the stream as they are uploaded, at most
+// S3_MULTIPART_CONCURRENCY parts are held in memory
+pub async fn multipart_upload<S>(
+    s3_bucket: &S3Bucket,
+    object_key: &str,
+    parts: S,
+    metadata: Option<&HashMap<String, String>>,
+    settings: &MultipartSettings,
+) -> Result<(), LakestreamError>
+where
+    S: Stream<Item = Result<Bytes, LakestreamError>>,
+{
+    let upload_id = create_multipart_upload(
+        s3_bucket,
+        object_key,
+        metadata,
+        settings.checksum_algorithm,
+    )
+    .await?;
+
+    let result =
+        upload_parts(s3_bucket, object_key, &upload_id, parts, settings).await;
+
+    if let Err(err) = result {
+        // abort, so the uploaded parts are not kept (and billed) by S3
+        if let Err(abort_err) =
+            abort_multipart_upload(s3_bucket, object_key, &upload_id).await
+        {
+            warn!(
+                "Failed to abort multipart upload {} for {}: {}",
+                upload_id, object_key, abort_err
+            );
+        }
+        return Err(err);
+    }
+    Ok(())
+}
+
+// split data in memory into parts
+pub fn data_parts(
+    data: &[u8],
+    part_size: u64,
+) -> impl Stream<Item = Result<Bytes, LakestreamError>> + '_ {
+    stream::iter(data.chunks(part_size as usize))
+        .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+}
+
+// regroup the chunks of a stream into parts of part_size bytes, the last
+// part can be smaller
+pub fn stream_parts(
+    data: ByteStream<'_>,
+    part_size: u64,
+) -> impl Stream<Item = Result<Bytes, LakestreamError>> + '_ {
+    let part_size = part_size as usize;
+    stream::try_unfold(
+        (data, Vec::with_capacity(part_size), false),
+        move |(mut data, mut buffer, mut done)| async move {
+            while !done && buffer.len() < part_size {
+                match data.try_next().await? {
+                    Some(chunk) => buffer.extend_from_slice(&chunk),
+                    None => done = true,
+                }
+            }
+            if buffer.is_empty() {
+                return Ok(None);
+            }
+            let rest = buffer.split_off(buffer.len().min(part_size));
+            let part = Bytes::from(std::mem::replace(&mut buffer, rest));
+            Ok(Some((part, (data, buffer, done))))
+        },
+    )
+}
+
+async fn upload_parts<S>(
+    s3_bucket: &S3Bucket,
+    object_key: &str,
+    upload_id: &str,
+    parts: S,
+    settings: &MultipartSettings,
+) -> Result<(), LakestreamError>
+where
+    S: Stream<Item = Result<Bytes, LakestreamError>>,
+{
+    info!("Multipart upload of {}", object_key);
+
+    // a failed part is retried by the request handler, as any request
+    let mut parts = parts
+        .enumerate()
+        .map(|(index, part)| async move {
+            let part_number = index as u32 + 1;
+            if u64::from(part_number) > AWS_MAX_PARTS {
+                return Err(LakestreamError::String(format!(
+                    "Upload of {} exceeds {} parts, increase \
+                     S3_MULTIPART_PART_SIZE",
+                    object_key, AWS_MAX_PARTS
+                )));
+            }
+            let part = part?;
+            let checksum = settings
+                .checksum_algorithm
+                .map(|algorithm| Checksum::new(algorithm, &part));
+            let etag = upload_part(
+                s3_bucket,
+                object_key,
+                upload_id,
+                part_number,
+                part,
+                checksum.as_ref(),
+            )
+            .await?;
+            Ok::<_, LakestreamError>(CompletedPart {
+                part_number,
+                etag,
+                checksum,
+            })
+        })
+        .buffer_unordered(settings.concurrency)
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    // S3 rejects completing an upload without parts
+    if parts.is_empty() {
+        return Err(LakestreamError::String(format!(
+            "No parts to upload for {}",
+            object_key
+        )));
+    }
+    // parts complete in any order, but must be listed in order
+    parts.sort_by_key(|part| part.part_number);
+    complete_multipart_upload(s3_bucket, object_key, upload_id, &parts).await
+}
+
+async fn create_multipart_upload(
+    s3_bucket: &S3Bucket,
+    object_key: &str,
+    metadata: Option<&HashMap<String, String>>,
+    checksum_algorithm: Option<ChecksumAlgorithm>,
+) -> Result<String, LakestreamError> {
+    let s3_client =
+        create_s3_client(s3_bucket.config(), Some(s3_bucket.name()));
+
+    let (body_bytes, _updated_s3_client, status_code, _response_headers) =
+        http_with_redirect_handling(
+            &s3_client,
+            |s3_client| {
+                s3_client.generate_create_multipart_upload_headers(
+                    object_key,
+                    metadata,
+                    checksum_algorithm,
+                )
+            },
+            "POST",
+            None,
+        )
+        .await?;
+
+    if !(200..300).contains(&status_code) {
+        return Err(LakestreamError::String(format!(
+            "Failed to create multipart upload for {}: status code {}",
+            object_key, status_code
+        )));
+    }
+    parse_upload_id(&String::from_utf8_lossy(&body_bytes))
+        .map_err(LakestreamError::from)
+}
+
+async fn upload_part(
+    s3_bucket: &S3Bucket,
+    object_key: &str,
+    upload_id: &str,
+    part_number: u32,
+    body: Bytes,
+    checksum: Option<&Checksum>,
+) -> Result<String, LakestreamError> {
+    let s3_client =
+        create_s3_client(s3_bucket.config(), Some(s3_bucket.name()));
+
+    let payload_hash = hex::encode(Sha256::digest(&body));
+
+    let (body_bytes, _updated_s3_client, status_code, response_headers) =
+        http_with_redirect_handling(
+            &s3_client,
+            |s3_client| {
+                s3_client.generate_upload_part_headers(
+                    object_key,
+                    upload_id,
+                    part_number,
+                    &payload_hash,
+                    checksum,
+                )
+            },
+            "PUT",
+            Some(body),
+        )
+        .await?;
+
+    if let Some(error_response) =
+        parse_error_response(status_code, &body_bytes, &response_headers)
+    {
+        return Err(error_response.into());
+    }
+    if !(200..300).contains(&status_code) {
+        return Err(LakestreamError::String(format!(
+            "Failed to upload part {} of {}: status code {}",
+            part_number, object_key, status_code
+        )));
+    }
+    response_headers.get("etag").cloned().ok_or_else(|| {
+        LakestreamError::String(format!(
+            "No ETag returned for part {} of {}",
+            part_number, object_key
+        ))
+    })
+}
+
+async fn complete_multipart_upload(
+    s3_bucket: &S3Bucket,
+    object_key: &str,
+    upload_id: &str,
+    parts: &[CompletedPart],
+) -> Result<(), LakestreamError> {
+    let s3_client =
+        create_s3_client(s3_bucket.config(), Some(s3_bucket.name()));
+
+    let body = complete_multipart_upload_body(parts);
+    let payload_hash = hex::encode(Sha256::digest(body.as_bytes()));
+
+    let (body_bytes, _updated_s3_client, status_code, response_headers) =
+        http_with_redirect_handling(
+            &s3_client,
+            |s3_client| {
+                s3_client.generate_complete_multipart_upload_headers(
+                    object_key,
+                    upload_id,
+                    &payload_hash,
+                )
+            },
+            "POST",
+            Some(Bytes::from(body.clone())),
+        )
+        .await?;
+
+    // S3 can return an error in the body of a 200 response
+    if let Some(error_response) =
+        parse_error_response(status_code, &body_bytes, &response_headers)
+    {
+        return Err(error_response.into());
+    }
+    if !(200..300).contains(&status_code) {
+        return Err(LakestreamError::String(format!(
+            "Failed to complete multipart upload for {}: status code {}",
+            object_key, status_code
+        )));
+    }
+    Ok(())
+}
+
+async fn abort_multipart_upload(
+    s3_bucket: &S3Bucket,
+    object_key: &str,
+    upload_id: &str,
+) -> Result<(), LakestreamError> {
+    let s3_client =
+        create_s3_client(s3_bucket.config(), Some(s3_bucket.name()));
+
+    let (_body_bytes, _updated_s3_client, status_code, _response_headers) =
+        http_with_redirect_handling(
+            &s3_client,
+            |s3_client| {
+                s3_client.generate_abort_multipart_upload_headers(
+                    object_key, upload_id,
+                )
+            },
+            "DELETE",
+            None,
+        )
+        .await?;
+
+    if !(200..300).contains(&status_code) {
+        return Err(LakestreamError::String(format!(
+            "Failed to abort multipart upload for {}: status code {}",
+            object_key, status_code
+        )));
+    }
+    Ok(())
+}
+
+fn complete_multipart_upload_body(parts: &[CompletedPart]) -> String {
+    let parts = parts
+        .iter()
+        .map(|part| {
+            // additional checksums of the parts are listed as well
+            let checksum = part
+                .checksum
+                .as_ref()
+                .and_then(|checksum| {
+                    let name = checksum.algorithm().additional_checksum()?;
+                    Some(format!(
+                        "<Checksum{}>{}</Checksum{}>",
+                        name,
+                        checksum.value(),
+                        name
+                    ))
+                })
+                .unwrap_or_default();
+            format!(
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag>{}</Part>",
+                part.part_number, part.etag, checksum
+            )
+        })
+        .collect::<String>();
+    format!(
+        "<CompleteMultipartUpload>{}</CompleteMultipartUpload>",
+        parts
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_size() {
+        let settings =
+            MultipartSettings::from_config(&EnvironmentConfig::default())
+                .unwrap();
+        assert_eq!(settings.part_size(100), S3_MULTIPART_PART_SIZE);
+
+        // large objects need larger parts to stay within 10000 parts
+        let object_size = 200 * 1024 * 1024 * 1024;
+        let part_size = settings.part_size(object_size);
+        assert!(part_size > S3_MULTIPART_PART_SIZE);
+        assert!(object_size.div_ceil(part_size) <= AWS_MAX_PARTS);
+
+        // parts can not be smaller than 5 MiB
+        let config = EnvironmentConfig::with_setting(
+            "S3_MULTIPART_PART_SIZE".to_string(),
+            "1024".to_string(),
+        );
+        let settings = MultipartSettings::from_config(&config).unwrap();
+        assert_eq!(settings.part_size(100), AWS_MIN_PART_SIZE);
+
+        let config = EnvironmentConfig::with_setting(
+            "S3_MULTIPART_CONCURRENCY".to_string(),
+            "0".to_string(),
+        );
+        assert!(MultipartSettings::from_config(&config).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stream_parts() {
+        let data = (0..25u8).collect::<Vec<u8>>();
+        // uneven chunks are regrouped into parts of 10 bytes
+        let chunks = data
+            .chunks(7)
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+            .collect::<Vec<_>>();
+        let parts = stream_parts(Box::pin(stream::iter(chunks)), 10)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let sizes = parts.iter().map(Bytes::len).collect::<Vec<_>>();
+        assert_eq!(sizes, vec![10, 10, 5]);
+        assert_eq!(parts.concat(), data);
+
+        let parts = data_parts(&data, 25).try_collect::<Vec<_>>().await;
+        assert_eq!(parts.unwrap().len(), 1);
+
+        // an error of the stream ends the parts
+        let chunks = vec![
+            Ok(Bytes::from_static(b"abc")),
+            Err(LakestreamError::String("read failed".to_string())),
+        ];
+        let result = stream_parts(Box::pin(stream::iter(chunks)), 10)
+            .try_collect::<Vec<_>>()
+            .await;
+        assert!(result.is_err());
+    }
+}
@@ -1,9 +1,12 @@
 use std::collections::HashMap;
 
+use percent_encoding::percent_decode_str;
 use serde::Deserialize;
 
 use crate::utils::time::rfc3339_to_epoch;
-use crate::{EnvironmentConfig, FileObject, ObjectStore};
+use crate::{
+    DeleteResult, EnvironmentConfig, FileObject, ObjectStore, S3ErrorResponse,
+};
 
 // allow non snake case for the XML response
 #[allow(non_snake_case)]
@@ -81,7 +84,7 @@ pub fn parse_file_objects(
         .iter()
         .map(|content| {
             FileObject::new(
-                content.Key.clone(),
+                decode_key(&content.Key),
                 content.Size,
                 Some(rfc3339_to_epoch(content.LastModified.as_str()).unwrap()),
                 Some(
@@ -100,7 +103,7 @@ pub fn parse_file_objects(
         .CommonPrefixes
         .unwrap_or_default()
         .iter()
-        .map(|common_prefix| common_prefix.Prefix.clone())
+        .map(|common_prefix| decode_key(&common_prefix.Prefix))
         .collect();
     let common_prefix_file_objects: Vec<FileObject> = common_prefixes
         .iter()
@@ -118,6 +121,13 @@ pub fn parse_file_objects(
     Ok(all_file_objects)
 }
 
+// keys are listed with encoding-type=url, which encodes a space as '+'
+fn decode_key(key: &str) -> String {
+    percent_decode_str(&key.replace('+', " "))
+        .decode_utf8_lossy()
+        .to_string()
+}
+
 pub fn extract_continuation_token(body: &str) -> Option<String> {
     let list_bucket_result: Result<ListBucketResult, _> =
         serde_xml_rs::from_str(body);
@@ -127,3 +137,158 @@ pub fn extract_continuation_token(body: &str) -> Option<String> {
         Err(_) => None,
     }
 }
+
+// allow non snake case for the XML response
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize)]
+struct DeleteResponse {
+    // Deleted and Error elements can be interleaved
+    #[serde(rename = "$value", default)]
+    Entries: Vec<DeleteEntry>,
+}
+
+// allow non snake case for the XML response
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize)]
+enum DeleteEntry {
+    Deleted {
+        Key: String,
+    },
+    Error {
+        Key: String,
+        Code: String,
+        Message: Option<String>,
+    },
+}
+
+// allow non snake case for the XML response
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize)]
+struct InitiateMultipartUploadResult {
+    UploadId: String,
+}
+
+pub fn parse_upload_id(
+    body: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let result: InitiateMultipartUploadResult = serde_xml_rs::from_str(body)?;
+    Ok(result.UploadId)
+}
+
+// allow non snake case for the XML response
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize)]
+struct ErrorDocument {
+    Code: String,
+    Message: Option<String>,
+    RequestId: Option<String>,
+    Resource: Option<String>,
+}
+
+// parse the <Error> document of a failed request, the body is empty for
+// HEAD requests, in which case there is nothing to parse
+pub fn parse_error_response(
+    status: u16,
+    body: &[u8],
+    headers: &HashMap<String, String>,
+) -> Option<S3ErrorResponse> {
+    let body = std::str::from_utf8(body).ok()?;
+    if !body.contains("<Error>") {
+        return None;
+    }
+    let document: ErrorDocument = serde_xml_rs::from_str(body).ok()?;
+    Some(S3ErrorResponse::new(
+        status,
+        document.Code,
+        document.Message,
+        document
+            .RequestId
+            .or_else(|| headers.get("x-amz-request-id").cloned()),
+        document.Resource,
+    ))
+}
+
+pub fn parse_delete_result(
+    body: &str,
+) -> Result<Vec<DeleteResult>, Box<dyn std::error::Error>> {
+    let delete_response: DeleteResponse = serde_xml_rs::from_str(body)?;
+    let delete_results = delete_response
+        .Entries
+        .into_iter()
+        .map(|entry| match entry {
+            DeleteEntry::Deleted { Key } => DeleteResult::deleted(&Key),
+            DeleteEntry::Error { Key, Code, Message } => {
+                let error = match Message {
+                    Some(message) => format!("{}: {}", Code, message),
+                    None => Code,
+                };
+                DeleteResult::failed(&Key, &error)
+            }
+        })
+        .collect();
+    Ok(delete_results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_delete_result() {
+        let body = r#"<?xml version="1.0" encoding="UTF-8"?>
+<DeleteResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <Deleted><Key>a.txt</Key></Deleted>
+  <Error>
+    <Key>b.txt</Key>
+    <Code>AccessDenied</Code>
+    <Message>Access Denied</Message>
+  </Error>
+  <Deleted><Key>c.txt</Key></Deleted>
+</DeleteResult>"#;
+        let results = parse_delete_result(body).unwrap();
+        assert_eq!(
+            results,
+            vec![
+                DeleteResult::deleted("a.txt"),
+                DeleteResult::failed("b.txt", "AccessDenied: Access Denied"),
+                DeleteResult::deleted("c.txt"),
+            ]
+        );
+
+        let empty = r#"<DeleteResult></DeleteResult>"#;
+        assert!(parse_delete_result(empty).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_error_response() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?>
+<Error>
+  <Code>NoSuchKey</Code>
+  <Message>The specified key does not exist.</Message>
+  <Key>missing.txt</Key>
+  <Resource>/bucket/missing.txt</Resource>
+  <RequestId>4442587FB7D0A2F9</RequestId>
+</Error>"#;
+        let error = parse_error_response(404, body, &HashMap::new()).unwrap();
+        assert_eq!(error.code(), "NoSuchKey");
+        assert_eq!(error.message(), Some("The specified key does not exist."));
+        assert_eq!(error.resource(), Some("/bucket/missing.txt"));
+        assert_eq!(error.request_id(), Some("4442587FB7D0A2F9"));
+        assert!(matches!(
+            crate::LakestreamError::from(error),
+            crate::LakestreamError::NoSuchKey(_)
+        ));
+
+        // request id falls back to the response header
+        let body = b"<Error><Code>SlowDown</Code></Error>";
+        let headers = HashMap::from([(
+            "x-amz-request-id".to_string(),
+            "ABC".to_string(),
+        )]);
+        let error = parse_error_response(503, body, &headers).unwrap();
+        assert_eq!(error.request_id(), Some("ABC"));
+        assert!(error.is_retryable());
+
+        assert!(parse_error_response(404, b"", &HashMap::new()).is_none());
+    }
+}
@@ -0,0 +1,35 @@
+use std::time::Duration;
+
+use super::bucket::S3Bucket;
+use super::list::create_s3_client;
+use crate::utils::time::UtcTimeNow;
+use crate::{LakestreamError, ObjectStoreTrait};
+
+// url to GET or PUT an object without credentials until it expires. The
+// url is signed for the configured region, the bucket is not contacted
+pub async fn presign_url(
+    s3_bucket: &S3Bucket,
+    object_key: &str,
+    method: &str,
+    expires_in: Duration,
+) -> Result<String, LakestreamError> {
+    let method = method.to_ascii_uppercase();
+    if !matches!(method.as_str(), "GET" | "PUT" | "HEAD" | "DELETE") {
+        return Err(LakestreamError::String(format!(
+            "Unsupported method for a presigned url: {}",
+            method
+        )));
+    }
+
+    let mut s3_client =
+        create_s3_client(s3_bucket.config(), Some(s3_bucket.name()));
+    s3_client.config.resolve_credentials().await?;
+
+    s3_client.request_builder.presign_url(
+        s3_client.config(),
+        &method,
+        Some(object_key),
+        expires_in.as_secs(),
+        &UtcTimeNow::new(),
+    )
+}
@@ -0,0 +1,451 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::{EnvironmentConfig, LakestreamError};
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+type Sections = HashMap<String, HashMap<String, String>>;
+
+// Profiles from the shared AWS config (~/.aws/config) and credentials
+// (~/.aws/credentials) files
+#[derive(Debug, Clone, Default)]
+pub struct AwsProfileFiles {
+    config: Sections,
+    credentials: Sections,
+}
+
+impl AwsProfileFiles {
+    // read the files, paths can be overridden with AWS_CONFIG_FILE and
+    // AWS_SHARED_CREDENTIALS_FILE. Missing files are treated as empty
+    pub fn load(config: &EnvironmentConfig) -> Self {
+        let read = |key: &str, file_name: &str| {
+            config
+                .get(key)
+                .map(PathBuf::from)
+                .or_else(|| aws_dir().map(|dir| dir.join(file_name)))
+                .and_then(|path| fs::read_to_string(path).ok())
+                .unwrap_or_default()
+        };
+        AwsProfileFiles::from_strings(
+            &read("AWS_CONFIG_FILE", "config"),
+            &read("AWS_SHARED_CREDENTIALS_FILE", "credentials"),
+        )
+    }
+
+    pub fn from_strings(config: &str, credentials: &str) -> Self {
+        AwsProfileFiles {
+            config: parse_ini(config, true),
+            credentials: parse_ini(credentials, false),
+        }
+    }
+
+    // settings of a profile, the credentials file takes precedence
+    // over the config file
+    pub fn profile(&self, name: &str) -> Option<AwsProfile> {
+        let config = self.config.get(name);
+        let credentials = self.credentials.get(name);
+        if config.is_none() && credentials.is_none() {
+            return None;
+        }
+        let mut settings = HashMap::new();
+        for section in [config, credentials].into_iter().flatten() {
+            settings.extend(section.clone());
+        }
+        Some(AwsProfile {
+            name: name.to_string(),
+            settings,
+        })
+    }
+}
+
+impl AwsProfileFiles {
+    // follow the source_profile chain of a profile, None if the profile
+    // does not assume a role. A profile that is its own source_profile
+    // assumes the role with its own keys
+    pub fn role_chain(
+        &self,
+        name: &str,
+    ) -> Result<Option<RoleChain>, LakestreamError> {
+        let mut visited: Vec<String> = Vec::new();
+        let mut roles = Vec::new();
+        let mut name = name.to_string();
+        let source = loop {
+            if visited.contains(&name) {
+                visited.push(name);
+                return Err(LakestreamError::ConfigError(format!(
+                    "Cycle in the source_profile chain: {}",
+                    visited.join(" -> ")
+                )));
+            }
+            let profile = self.profile(&name).ok_or_else(|| {
+                LakestreamError::ConfigError(format!(
+                    "Profile {} not found in the AWS config or credentials \
+                     file",
+                    name
+                ))
+            })?;
+            visited.push(name.clone());
+
+            let role_arn = match profile.role_arn() {
+                Some(role_arn) => role_arn,
+                None if roles.is_empty() => return Ok(None),
+                None => {
+                    break profile.static_credentials().ok_or_else(|| {
+                        LakestreamError::ConfigError(format!(
+                            "Source profile {} has no credentials",
+                            name
+                        ))
+                    })?;
+                }
+            };
+            roles.push(profile.assume_role(role_arn)?);
+
+            if profile.get("credential_source").is_some() {
+                return Err(LakestreamError::ConfigError(format!(
+                    "Profile {} uses credential_source, only source_profile \
+                     is supported",
+                    name
+                )));
+            }
+            let source_profile =
+                profile.get("source_profile").ok_or_else(|| {
+                    LakestreamError::ConfigError(format!(
+                        "Profile {} has a role_arn without source_profile",
+                        name
+                    ))
+                })?;
+            if source_profile == name {
+                break profile.static_credentials().ok_or_else(|| {
+                    LakestreamError::ConfigError(format!(
+                        "Profile {} is its own source_profile, but has no \
+                         credentials",
+                        name
+                    ))
+                })?;
+            }
+            name = source_profile.to_string();
+        };
+        roles.reverse();
+        Ok(Some(RoleChain { source, roles }))
+    }
+}
+
+// A named profile, with keys as found in the files (lowercase). Keys in
+// a nested section, e.g. "s3 =" followed by indented lines, are stored
+// as "s3.<key>"
+#[derive(Debug, Clone)]
+pub struct AwsProfile {
+    name: String,
+    settings: HashMap<String, String>,
+}
+
+impl AwsProfile {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.settings
+            .get(key)
+            .map(String::as_str)
+            .filter(|value| !value.is_empty())
+    }
+
+    pub fn region(&self) -> Option<&str> {
+        self.get("region")
+    }
+
+    // the S3 specific endpoint takes precedence over the global one
+    pub fn endpoint_url(&self) -> Option<&str> {
+        self.get("s3.endpoint_url")
+            .or_else(|| self.get("endpoint_url"))
+    }
+
+    // role assumed by the profile, with the credentials of its
+    // source_profile
+    pub fn role_arn(&self) -> Option<&str> {
+        self.get("role_arn")
+    }
+
+    // static credentials of the profile, None if it has no keys or assumes
+    // a role (see AwsProfileFiles::role_chain)
+    pub fn credentials(&self) -> Option<ProfileCredentials> {
+        if self.role_arn().is_some() {
+            return None;
+        }
+        self.static_credentials()
+    }
+
+    fn static_credentials(&self) -> Option<ProfileCredentials> {
+        let access_key = self.get("aws_access_key_id")?;
+        let secret_key = self.get("aws_secret_access_key")?;
+        Some(ProfileCredentials {
+            access_key: access_key.to_string(),
+            secret_key: secret_key.to_string(),
+            session_token: self.get("aws_session_token").map(String::from),
+        })
+    }
+
+    fn assume_role(
+        &self,
+        role_arn: &str,
+    ) -> Result<AssumeRole, LakestreamError> {
+        let duration_seconds = self
+            .get("duration_seconds")
+            .map(|value| {
+                value.parse::<u32>().map_err(|_| {
+                    LakestreamError::ConfigError(format!(
+                        "Invalid duration_seconds in profile {}: {}",
+                        self.name, value
+                    ))
+                })
+            })
+            .transpose()?;
+        Ok(AssumeRole {
+            role_arn: role_arn.to_string(),
+            role_session_name: self.get("role_session_name").map(String::from),
+            external_id: self.get("external_id").map(String::from),
+            duration_seconds,
+        })
+    }
+}
+
+// A role to assume via STS AssumeRole
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssumeRole {
+    pub role_arn: String,
+    pub role_session_name: Option<String>,
+    pub external_id: Option<String>,
+    pub duration_seconds: Option<u32>,
+}
+
+// The source_profile chain of a role profile: the static credentials at
+// the end of the chain, and the roles to assume with these, in order.
+// Each role is assumed with the credentials of the previous one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoleChain {
+    pub source: ProfileCredentials,
+    pub roles: Vec<AssumeRole>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileCredentials {
+    pub access_key: String,
+    pub secret_key: String,
+    pub session_token: Option<String>,
+}
+
+fn aws_dir() -> Option<PathBuf> {
+    env::var("HOME")
+        .or_else(|_| env::var("USERPROFILE"))
+        .ok()
+        .map(|home| PathBuf::from(home).join(".aws"))
+}
+
+// parse an AWS style ini file. In the config file, profiles other than
+// "default" are named "[profile <name>]", other sections are skipped
+fn parse_ini(content: &str, is_config_file: bool) -> Sections {
+    let mut sections = Sections::new();
+    let mut current: Option<String> = None;
+    let mut parent_key: Option<String> = None;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with(['#', ';']) {
+            continue;
+        }
+
+        if let Some(header) = trimmed
+            .strip_prefix('[')
+            .and_then(|header| header.strip_suffix(']'))
+        {
+            let header = header.trim();
+            current = if !is_config_file || header == DEFAULT_PROFILE {
+                Some(header.to_string())
+            } else {
+                header
+                    .strip_prefix("profile ")
+                    .map(|name| name.trim().to_string())
+            };
+            parent_key = None;
+            continue;
+        }
+
+        let (section, (key, value)) = match (&current, trimmed.split_once('='))
+        {
+            (Some(section), Some(key_value)) => (section, key_value),
+            _ => continue,
+        };
+        let key = key.trim().to_ascii_lowercase();
+        let value = value.trim().to_string();
+
+        let is_nested = line.starts_with([' ', '\t']);
+        let key = match &parent_key {
+            Some(parent_key) if is_nested => format!("{}.{}", parent_key, key),
+            _ => {
+                // "key =" without a value opens a nested section
+                parent_key = value.is_empty().then(|| key.clone());
+                key
+            }
+        };
+        sections
+            .entry(section.clone())
+            .or_default()
+            .insert(key, value);
+    }
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "
+# comment
+[default]
+region = eu-west-1
+
+[profile dev]
+region = us-west-2
+s3 =
+  endpoint_url = http://localhost:9000
+  addressing_style = path
+
+[profile base]
+region = eu-central-1
+endpoint_url = https://s3.example.com
+
+[profile admin]
+role_arn = arn:aws:iam::123456789012:role/admin
+source_profile = base
+
+[sso-session corp]
+sso_region = us-east-1
+";
+
+    const CREDENTIALS: &str = "
+[default]
+aws_access_key_id = AKIDDEFAULT
+aws_secret_access_key = default-secret
+
+[base]
+aws_access_key_id = AKIDBASE
+aws_secret_access_key = base-secret
+aws_session_token = base-token
+";
+
+    #[test]
+    fn test_profile_settings() {
+        let files = AwsProfileFiles::from_strings(CONFIG, CREDENTIALS);
+
+        let default = files.profile(DEFAULT_PROFILE).unwrap();
+        assert_eq!(default.region(), Some("eu-west-1"));
+        assert_eq!(default.get("aws_access_key_id"), Some("AKIDDEFAULT"));
+
+        let dev = files.profile("dev").unwrap();
+        assert_eq!(dev.region(), Some("us-west-2"));
+        assert_eq!(dev.endpoint_url(), Some("http://localhost:9000"));
+        assert_eq!(dev.get("s3.addressing_style"), Some("path"));
+
+        let base = files.profile("base").unwrap();
+        assert_eq!(base.endpoint_url(), Some("https://s3.example.com"));
+
+        assert!(files.profile("missing").is_none());
+        assert!(files.profile("corp").is_none());
+    }
+
+    #[test]
+    fn test_profile_credentials() {
+        let files = AwsProfileFiles::from_strings(CONFIG, CREDENTIALS);
+        let credentials =
+            |name: &str| files.profile(name).unwrap().credentials();
+
+        let default = credentials(DEFAULT_PROFILE).unwrap();
+        assert_eq!(default.access_key, "AKIDDEFAULT");
+        assert_eq!(default.session_token, None);
+
+        let base = credentials("base").unwrap();
+        assert_eq!(base.access_key, "AKIDBASE");
+        assert_eq!(base.session_token.as_deref(), Some("base-token"));
+
+        // no keys of its own, other providers are tried
+        assert_eq!(credentials("dev"), None);
+        // keys are obtained by assuming the role
+        assert_eq!(credentials("admin"), None);
+    }
+
+    #[test]
+    fn test_role_chain() {
+        let config = "
+[profile base]
+aws_access_key_id = AKIDBASE
+aws_secret_access_key = base-secret
+
+[profile admin]
+role_arn = arn:aws:iam::123456789012:role/admin
+source_profile = base
+external_id = ext
+
+[profile audit]
+role_arn = arn:aws:iam::210987654321:role/audit
+source_profile = admin
+role_session_name = auditor
+duration_seconds = 900
+
+[profile self]
+role_arn = arn:aws:iam::123456789012:role/self
+source_profile = self
+aws_access_key_id = AKIDSELF
+aws_secret_access_key = self-secret
+
+[profile loop-a]
+role_arn = arn:aws:iam::123456789012:role/a
+source_profile = loop-b
+
+[profile loop-b]
+role_arn = arn:aws:iam::123456789012:role/b
+source_profile = loop-a
+
+[profile orphan]
+role_arn = arn:aws:iam::123456789012:role/orphan
+source_profile = missing
+";
+        let files = AwsProfileFiles::from_strings(config, "");
+        let chain = |name: &str| files.role_chain(name);
+
+        assert_eq!(chain("base").unwrap(), None);
+
+        // roles are assumed starting at the end of the chain
+        let audit = chain("audit").unwrap().unwrap();
+        assert_eq!(audit.source.access_key, "AKIDBASE");
+        assert_eq!(
+            audit.roles,
+            vec![
+                AssumeRole {
+                    role_arn: "arn:aws:iam::123456789012:role/admin".into(),
+                    role_session_name: None,
+                    external_id: Some("ext".into()),
+                    duration_seconds: None,
+                },
+                AssumeRole {
+                    role_arn: "arn:aws:iam::210987654321:role/audit".into(),
+                    role_session_name: Some("auditor".into()),
+                    external_id: None,
+                    duration_seconds: Some(900),
+                },
+            ]
+        );
+
+        let own = chain("self").unwrap().unwrap();
+        assert_eq!(own.source.access_key, "AKIDSELF");
+        assert_eq!(own.roles.len(), 1);
+
+        let err = chain("loop-a").unwrap_err().to_string();
+        assert!(err.contains("loop-a -> loop-b -> loop-a"));
+        let err = chain("orphan").unwrap_err().to_string();
+        assert!(err.contains("Profile missing not found"));
+    }
+}
@@ -0,0 +1,285 @@
+use std::fmt;
+use std::str::FromStr;
+
+// How the bucket is addressed: as part of the host name (virtual hosted
+// style) or as the first path segment (path style)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AddressingStyle {
+    // virtual hosted style for AWS, path style for custom endpoints
+    #[default]
+    Auto,
+    Path,
+    Virtual,
+}
+
+impl AddressingStyle {
+    // resolve Auto for a bucket, bucket names with dots can not be used
+    // as host name with TLS as these do not match the wildcard certificate
+    pub fn resolve(
+        &self,
+        endpoint_url: Option<&str>,
+        bucket_name: &str,
+    ) -> AddressingStyle {
+        match self {
+            AddressingStyle::Auto
+                if endpoint_url.is_some() || bucket_name.contains('.') =>
+            {
+                AddressingStyle::Path
+            }
+            AddressingStyle::Auto => AddressingStyle::Virtual,
+            style => *style,
+        }
+    }
+}
+
+impl FromStr for AddressingStyle {
+    type Err = String;
+
+    fn from_str(style: &str) -> Result<Self, Self::Err> {
+        match style.trim().to_ascii_lowercase().as_str() {
+            "auto" => Ok(AddressingStyle::Auto),
+            "path" => Ok(AddressingStyle::Path),
+            "virtual" => Ok(AddressingStyle::Virtual),
+            _ => Err(format!(
+                "Invalid addressing style: {}, expected auto, path or virtual",
+                style
+            )),
+        }
+    }
+}
+
+impl fmt::Display for AddressingStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let style = match self {
+            AddressingStyle::Auto => "auto",
+            AddressingStyle::Path => "path",
+            AddressingStyle::Virtual => "virtual",
+        };
+        write!(f, "{}", style)
+    }
+}
+
+// Presets for S3 compatible services. A preset only provides defaults for
+// the endpoint, region and addressing style, requests are signed the same
+// (SigV4 with a signed payload hash) for every provider
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum S3Provider {
+    #[default]
+    Aws,
+    Minio,
+    CephRgw,
+    CloudflareR2,
+    BackblazeB2,
+    Wasabi,
+}
+
+impl S3Provider {
+    // endpoint of the service, None for AWS (endpoints are derived from
+    // the region) or when the service is self-hosted
+    pub fn endpoint_url(
+        &self,
+        region: &str,
+        account_id: Option<&str>,
+    ) -> Result<Option<String>, String> {
+        let endpoint_url = match self {
+            S3Provider::Aws => None,
+            S3Provider::Minio | S3Provider::CephRgw => {
+                return Err(format!(
+                    "S3_ENDPOINT_URL is required for {}, as it is self-hosted",
+                    self
+                ))
+            }
+            S3Provider::CloudflareR2 => {
+                let account_id = account_id.ok_or_else(|| {
+                    format!(
+                        "S3_ACCOUNT_ID or S3_ENDPOINT_URL is required for {}",
+                        self
+                    )
+                })?;
+                Some(format!("https://{}.r2.cloudflarestorage.com", account_id))
+            }
+            S3Provider::BackblazeB2 => {
+                Some(format!("https://s3.{}.backblazeb2.com", region))
+            }
+            S3Provider::Wasabi => {
+                Some(format!("https://s3.{}.wasabisys.com", region))
+            }
+        };
+        Ok(endpoint_url)
+    }
+
+    // region used when none is configured. R2 signs with region "auto",
+    // self-hosted services accept the AWS default. Backblaze B2 regions
+    // are part of the endpoint and have no default
+    pub fn default_region(&self) -> Option<&'static str> {
+        match self {
+            S3Provider::CloudflareR2 => Some("auto"),
+            S3Provider::BackblazeB2 => None,
+            _ => Some("us-east-1"),
+        }
+    }
+
+    // self-hosted services are typically addressed without DNS wildcard
+    pub fn addressing_style(&self) -> AddressingStyle {
+        match self {
+            S3Provider::Minio
+            | S3Provider::CephRgw
+            | S3Provider::CloudflareR2 => AddressingStyle::Path,
+            _ => AddressingStyle::Auto,
+        }
+    }
+}
+
+impl FromStr for S3Provider {
+    type Err = String;
+
+    fn from_str(provider: &str) -> Result<Self, Self::Err> {
+        match provider.trim().to_ascii_lowercase().as_str() {
+            "aws" => Ok(S3Provider::Aws),
+            "minio" => Ok(S3Provider::Minio),
+            "ceph" | "ceph-rgw" | "rgw" => Ok(S3Provider::CephRgw),
+            "r2" | "cloudflare-r2" => Ok(S3Provider::CloudflareR2),
+            "b2" | "backblaze-b2" => Ok(S3Provider::BackblazeB2),
+            "wasabi" => Ok(S3Provider::Wasabi),
+            _ => Err(format!(
+                "Unknown S3 provider: {}, expected aws, minio, ceph-rgw, \
+                 r2, b2 or wasabi",
+                provider
+            )),
+        }
+    }
+}
+
+impl fmt::Display for S3Provider {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let provider = match self {
+            S3Provider::Aws => "aws",
+            S3Provider::Minio => "minio",
+            S3Provider::CephRgw => "ceph-rgw",
+            S3Provider::CloudflareR2 => "r2",
+            S3Provider::BackblazeB2 => "b2",
+            S3Provider::Wasabi => "wasabi",
+        };
+        write!(f, "{}", provider)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::http::mock_endpoint::{
+        mock_endpoint, mock_s3_config, MockResponse,
+    };
+    use crate::s3::bucket::{configure_bucket_url, S3Bucket};
+    use crate::s3::head::head_object;
+
+    #[test]
+    fn test_bucket_url() {
+        let url = |endpoint_url, bucket_name, style| {
+            configure_bucket_url("eu-west-1", endpoint_url, bucket_name, style)
+        };
+        let minio = Some("http://localhost:9000/");
+
+        assert_eq!(
+            url(None, Some("bucket"), AddressingStyle::Auto),
+            "https://bucket.s3.eu-west-1.amazonaws.com"
+        );
+        // dots in the name do not match the wildcard certificate
+        assert_eq!(
+            url(None, Some("my.bucket"), AddressingStyle::Auto),
+            "https://s3.eu-west-1.amazonaws.com/my.bucket"
+        );
+        assert_eq!(
+            url(None, Some("bucket"), AddressingStyle::Path),
+            "https://s3.eu-west-1.amazonaws.com/bucket"
+        );
+        assert_eq!(
+            url(minio, Some("bucket"), AddressingStyle::Auto),
+            "http://localhost:9000/bucket"
+        );
+        assert_eq!(
+            url(minio, Some("bucket"), AddressingStyle::Virtual),
+            "http://bucket.localhost:9000"
+        );
+        assert_eq!(
+            url(minio, None, AddressingStyle::Virtual),
+            "http://localhost:9000"
+        );
+        assert_eq!(
+            url(None, None, AddressingStyle::Auto),
+            "https://s3.eu-west-1.amazonaws.com"
+        );
+    }
+
+    #[test]
+    fn test_provider_presets() {
+        let provider = |name: &str| name.parse::<S3Provider>().unwrap();
+
+        let r2 = provider("r2");
+        assert_eq!(r2.default_region(), Some("auto"));
+        assert_eq!(
+            r2.endpoint_url("auto", Some("abc123")).unwrap().as_deref(),
+            Some("https://abc123.r2.cloudflarestorage.com")
+        );
+        assert!(r2.endpoint_url("auto", None).is_err());
+
+        let b2 = provider("backblaze-b2");
+        assert_eq!(b2.default_region(), None);
+        assert_eq!(
+            b2.endpoint_url("us-west-004", None).unwrap().as_deref(),
+            Some("https://s3.us-west-004.backblazeb2.com")
+        );
+
+        assert_eq!(
+            provider("wasabi")
+                .endpoint_url("eu-central-1", None)
+                .unwrap(),
+            Some("https://s3.eu-central-1.wasabisys.com".to_string())
+        );
+        assert!(provider("minio").endpoint_url("us-east-1", None).is_err());
+        assert_eq!(provider("rgw"), S3Provider::CephRgw);
+        assert_eq!(provider("ceph").addressing_style(), AddressingStyle::Path);
+        assert_eq!(provider("aws").endpoint_url("us-east-1", None), Ok(None));
+        assert!("gcs".parse::<S3Provider>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_provider_requests() {
+        // (path, authorization, x-amz-content-sha256) of each request
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let captured = requests.clone();
+        let (endpoint, _) = mock_endpoint(move |request| {
+            let header = |name: &str| {
+                request.headers.get(name).cloned().unwrap_or_default()
+            };
+            captured.lock().unwrap().push((
+                request.path.clone(),
+                header("authorization"),
+                header("x-amz-content-sha256"),
+            ));
+            MockResponse::new(200, "data")
+        })
+        .await;
+
+        for provider in ["aws", "minio", "ceph-rgw", "r2", "b2", "wasabi"] {
+            let mut config = mock_s3_config(&endpoint);
+            config.set("S3_PROVIDER".to_string(), provider.to_string());
+            let s3_bucket = S3Bucket::new("testbucket", config).unwrap();
+            head_object(&s3_bucket, "key").await.unwrap();
+        }
+
+        let requests = requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 6);
+        assert!(!requests[0].2.is_empty());
+        for (path, authorization, content_sha256) in &requests {
+            // path style for the custom endpoint, signed as for AWS
+            assert_eq!(path, "/testbucket/key");
+            assert!(authorization
+                .starts_with("AWS4-HMAC-SHA256 Credential=AKIDMOCK/"));
+            assert!(authorization.contains("/us-east-1/s3/aws4_request"));
+            assert_eq!(content_sha256, &requests[0].2);
+        }
+    }
+}
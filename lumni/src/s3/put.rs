@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+
+use bytes::Bytes;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use log::info;
+use sha2::{Digest, Sha256};
+
+use super::bucket::S3Bucket;
+use super::checksum::Checksum;
+use super::client_headers::Headers;
+use super::config::checksum_algorithm;
+use super::list::create_s3_client;
+use super::multipart::{
+    data_parts, multipart_upload, stream_parts, MultipartSettings,
+};
+use super::parse_http_response::parse_error_response;
+use super::request_handler::http_with_redirect_handling;
+use crate::{ByteStream, LakestreamError, ObjectStoreTrait};
+
+// objects up to the multipart threshold are collected and uploaded with a
+// single PUT, larger objects or objects of unknown size are uploaded in
+// parts as these are read from the stream
+pub async fn put_object_stream(
+    s3_bucket: &S3Bucket,
+    object_key: &str,
+    data: ByteStream<'_>,
+    size: Option<u64>,
+    metadata: Option<&HashMap<String, String>>,
+) -> Result<(), LakestreamError> {
+    let settings = MultipartSettings::from_config(s3_bucket.config())?;
+    match size {
+        Some(size) if size <= settings.threshold => {
+            let data = data
+                .try_fold(Vec::new(), |mut buffer, chunk| async move {
+                    buffer.extend_from_slice(&chunk);
+                    Ok(buffer)
+                })
+                .await?;
+            put_object(s3_bucket, object_key, &data, metadata).await
+        }
+        _ => {
+            // without a size the configured part size is used, which
+            // limits the size of the object to 10000 parts
+            let part_size = settings.part_size(size.unwrap_or(0));
+            let mut parts = Box::pin(stream_parts(data, part_size));
+
+            // a stream that ends before the first part is full (including
+            // an empty stream) is uploaded with a single PUT, a multipart
+            // upload needs at least one part
+            let first_part = match parts.try_next().await? {
+                Some(part) if part.len() as u64 >= part_size => part,
+                Some(part) => {
+                    return put_object(s3_bucket, object_key, &part, metadata)
+                        .await
+                }
+                None => {
+                    return put_object(s3_bucket, object_key, &[], metadata)
+                        .await
+                }
+            };
+            let parts = stream::once(async { Ok(first_part) }).chain(parts);
+            multipart_upload(s3_bucket, object_key, parts, metadata, &settings)
+                .await
+        }
+    }
+}
+
+pub async fn put_object(
+    s3_bucket: &S3Bucket,
+    object_key: &str,
+    data: &[u8],
+    metadata: Option<&HashMap<String, String>>,
+) -> Result<(), LakestreamError> {
+    // large objects are uploaded in parts, a single PUT is limited to 5 GB
+    let settings = MultipartSettings::from_config(s3_bucket.config())?;
+    if data.len() as u64 > settings.threshold {
+        let parts = data_parts(data, settings.part_size(data.len() as u64));
+        return multipart_upload(
+            s3_bucket, object_key, parts, metadata, &settings,
+        )
+        .await;
+    }
+
+    let s3_client =
+        create_s3_client(s3_bucket.config(), Some(s3_bucket.name()));
+
+    // sign the actual payload instead of sending it as UNSIGNED-PAYLOAD
+    let payload_hash = hex::encode(Sha256::digest(data));
+    let checksum = checksum_algorithm(s3_bucket.config())?
+        .map(|algorithm| Checksum::new(algorithm, data));
+    let body = Bytes::copy_from_slice(data);
+
+    info!(
+        "Putting object: {} of size {} bytes",
+        object_key,
+        data.len()
+    );
+    let (body_bytes, _updated_s3_client, status_code, response_headers) =
+        http_with_redirect_handling(
+            &s3_client,
+            |s3_client| {
+                s3_client.generate_put_object_headers(
+                    object_key,
+                    &payload_hash,
+                    metadata,
+                    checksum.as_ref(),
+                )
+            },
+            "PUT",
+            Some(body),
+        )
+        .await?;
+
+    // e.g. BadDigest when the checksum does not match
+    if let Some(error_response) =
+        parse_error_response(status_code, &body_bytes, &response_headers)
+    {
+        return Err(error_response.into());
+    }
+    if !(200..300).contains(&status_code) {
+        return Err(LakestreamError::String(format!(
+            "Failed to put object {}: status code {}",
+            object_key, status_code
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::http::mock_endpoint::{
+        mock_endpoint, mock_s3_config, MockResponse,
+    };
+    use crate::AWS_MIN_PART_SIZE;
+
+    // the method, path and body size of each request
+    type Requests = Arc<Mutex<Vec<(String, String, usize)>>>;
+
+    async fn mock_bucket() -> (S3Bucket, Requests) {
+        let requests = Requests::default();
+        let recorded = requests.clone();
+        let (endpoint, _) = mock_endpoint(move |request| {
+            let path = request.path.clone();
+            recorded.lock().unwrap().push((
+                request.method.clone(),
+                path.clone(),
+                request.body.len(),
+            ));
+            match request.method.as_str() {
+                "POST" if path.ends_with("?uploads") => MockResponse::new(
+                    200,
+                    "<InitiateMultipartUploadResult><UploadId>upload-1\
+                     </UploadId></InitiateMultipartUploadResult>",
+                ),
+                "PUT" => {
+                    MockResponse::new(200, "").with_header("ETag", "\"etag\"")
+                }
+                "DELETE" => MockResponse::new(204, ""),
+                _ => MockResponse::new(400, ""),
+            }
+        })
+        .await;
+        let mut config = mock_s3_config(&endpoint);
+        config.set(
+            "S3_MULTIPART_PART_SIZE".to_string(),
+            AWS_MIN_PART_SIZE.to_string(),
+        );
+        let bucket = S3Bucket::new("bucket", config).unwrap();
+        (bucket, requests)
+    }
+
+    fn byte_stream(
+        chunks: Vec<Result<Bytes, LakestreamError>>,
+    ) -> ByteStream<'static> {
+        Box::pin(stream::iter(chunks))
+    }
+
+    fn methods(requests: &Requests) -> Vec<String> {
+        let requests = requests.lock().unwrap();
+        requests.iter().map(|(method, ..)| method.clone()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_put_stream_of_unknown_size() {
+        // an empty stream is uploaded as an empty object
+        let (bucket, requests) = mock_bucket().await;
+        put_object_stream(&bucket, "empty", byte_stream(vec![]), None, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [("PUT".to_string(), "/bucket/empty".to_string(), 0)]
+        );
+
+        // less than a part, in several chunks
+        let (bucket, requests) = mock_bucket().await;
+        let chunks = vec![Ok(Bytes::from("ab")), Ok(Bytes::from("cde"))];
+        put_object_stream(&bucket, "small", byte_stream(chunks), None, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [("PUT".to_string(), "/bucket/small".to_string(), 5)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_put_stream_aborts_on_error() {
+        // the first part is full, the stream fails while reading the next
+        let (bucket, requests) = mock_bucket().await;
+        let part = Bytes::from(vec![0u8; AWS_MIN_PART_SIZE as usize]);
+        let chunks = vec![
+            Ok(part),
+            Err(LakestreamError::String("read failed".to_string())),
+        ];
+        let err = put_object_stream(
+            &bucket,
+            "large",
+            byte_stream(chunks),
+            None,
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("read failed"));
+        // the upload of the first part may be cancelled by the error
+        let methods = methods(&requests);
+        assert_eq!(methods.first().map(String::as_str), Some("POST"));
+        assert_eq!(methods.last().map(String::as_str), Some("DELETE"));
+        let requests = requests.lock().unwrap();
+        assert!(requests.last().unwrap().1.contains("uploadId=upload-1"));
+    }
+}
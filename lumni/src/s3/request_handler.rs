@@ -1,27 +1,86 @@
 use std::collections::HashMap;
 
 use bytes::Bytes;
+use log::warn;
 
-use crate::http::requests::http_request_with_headers;
+use crate::http::requests::{
+    http_request_with_callback, http_request_with_headers,
+};
+use crate::http::retry::{is_retryable_status, is_transient_error, Backoff};
+use crate::http::ResponseHeadersHook;
 use crate::s3::client::S3Client;
 use crate::s3::client_config::S3ClientConfig;
-use crate::LakestreamError;
+use crate::s3::parse_http_response::parse_error_response;
+use crate::{BinaryCallbackWrapper, LakestreamError};
 
 async fn handle_redirect(s3_client: &S3Client, new_region: &str) -> S3Client {
     let config = s3_client.config();
     let bucket_name = config.bucket_name();
-    let credentials = config.credentials().clone();
     let endpoint_url = config.endpoint_url();
 
-    let s3_client_config =
-        S3ClientConfig::new(credentials, bucket_name, endpoint_url, new_region);
+    let s3_client_config = S3ClientConfig::new(
+        config.credential_provider().cloned(),
+        bucket_name,
+        endpoint_url,
+        new_region,
+        config.addressing_style(),
+        config.retry_policy().clone(),
+        config.http_client_config().clone(),
+    );
     S3Client::new(s3_client_config)
 }
 
+// client for the region named by a 301 response. The region is switched
+// once per request, an endpoint that keeps redirecting is an error
+async fn follow_redirect(
+    s3_client: &S3Client,
+    response_headers: &HashMap<String, String>,
+    redirected: &mut bool,
+) -> Result<S3Client, LakestreamError> {
+    let new_region =
+        response_headers.get("x-amz-bucket-region").ok_or_else(|| {
+            LakestreamError::from(
+                "Error: Redirect without x-amz-bucket-region header",
+            )
+        })?;
+    if *redirected {
+        return Err(LakestreamError::String(format!(
+            "Redirected again to region {} after switching to region {}: {}",
+            new_region,
+            s3_client.region(),
+            s3_client.url()
+        )));
+    }
+    *redirected = true;
+    Ok(handle_redirect(s3_client, new_region).await)
+}
+
+fn retries_exhausted(
+    s3_client: &S3Client,
+    status_code: u16,
+    attempts: u32,
+) -> LakestreamError {
+    LakestreamError::String(format!(
+        "Request failed with status {} after {} attempt(s): {}",
+        status_code,
+        attempts,
+        s3_client.url()
+    ))
+}
+
+// a forwarding proxy rejected the (missing) proxy credentials
+fn proxy_auth_required(s3_client: &S3Client) -> LakestreamError {
+    LakestreamError::String(format!(
+        "Proxy authentication required (status 407): {}",
+        s3_client.url()
+    ))
+}
+
 pub async fn http_with_redirect_handling<F>(
     s3_client: &S3Client,
     generate_headers: F,
     method: &str,
+    body: Option<Bytes>,
 ) -> Result<
     (Bytes, Option<S3Client>, u16, HashMap<String, String>),
     LakestreamError,
@@ -30,29 +89,67 @@ where
     F: Fn(&mut S3Client) -> Result<HashMap<String, String>, LakestreamError>,
 {
     let mut current_s3_client = s3_client.clone();
+    let retry_policy = s3_client.config().retry_policy().clone();
+    let mut backoff = Backoff::new(&retry_policy);
+    let mut redirected = false;
     loop {
+        // headers are generated for each attempt, signatures are time-bound
+        current_s3_client.config.resolve_credentials().await?;
         let headers = generate_headers(&mut current_s3_client)?;
         let result = http_request_with_headers(
             &current_s3_client.url(),
             &headers,
             method,
+            body.clone(),
+            current_s3_client.config().http_client_config(),
         )
-        .await;
+        .await
+        .map_err(LakestreamError::from);
 
         match result {
             Ok((body_bytes, status_code, response_headers)) => {
                 if status_code == 301 {
-                    if let Some(new_region) =
-                        response_headers.get("x-amz-bucket-region")
-                    {
-                        current_s3_client =
-                            handle_redirect(&current_s3_client, new_region)
-                                .await;
-                    } else {
-                        let error = "Error: Redirect without \
-                                     x-amz-bucket-region header";
-                        return Err(LakestreamError::from(error));
+                    current_s3_client = follow_redirect(
+                        &current_s3_client,
+                        &response_headers,
+                        &mut redirected,
+                    )
+                    .await?;
+                    continue;
+                }
+
+                let error_response = if status_code >= 300 {
+                    parse_error_response(
+                        status_code,
+                        &body_bytes,
+                        &response_headers,
+                    )
+                } else {
+                    None
+                };
+                if is_retryable_status(status_code)
+                    || error_response.as_ref().is_some_and(|e| e.is_retryable())
+                {
+                    warn!(
+                        "Status {} from {} (attempt {})",
+                        status_code,
+                        current_s3_client.url(),
+                        backoff.attempt()
+                    );
+                    if !backoff.wait().await {
+                        return Err(error_response.map_or_else(
+                            || {
+                                retries_exhausted(
+                                    &current_s3_client,
+                                    status_code,
+                                    backoff.attempt(),
+                                )
+                            },
+                            LakestreamError::from,
+                        ));
                     }
+                } else if let Some(error_response) = error_response {
+                    return Err(error_response.into());
                 } else {
                     if status_code == 403 {
                         let url = current_s3_client.url();
@@ -60,6 +157,9 @@ where
                             url.to_string(),
                         ));
                     }
+                    if status_code == 407 {
+                        return Err(proxy_auth_required(&current_s3_client));
+                    }
 
                     // TODO: Handle non-200 status codes
                     // TODO: return response_headers to accomodate HEAD requests
@@ -75,7 +175,226 @@ where
                     ));
                 }
             }
-            Err(e) => return Err(LakestreamError::from(e)),
+            Err(err) if is_transient_error(&err) => {
+                warn!(
+                    "Request to {} failed (attempt {}): {}",
+                    current_s3_client.url(),
+                    backoff.attempt(),
+                    err
+                );
+                if !backoff.wait().await {
+                    return Err(err);
+                }
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+// same as http_with_redirect_handling, but forwards the response body to the
+// callback in chunks instead of returning it. Only error responses are
+// retried, as a failure halfway the body may have been partially forwarded
+pub async fn http_stream_with_redirect_handling<F>(
+    s3_client: &S3Client,
+    generate_headers: F,
+    method: &str,
+    callback: &BinaryCallbackWrapper,
+    on_headers: Option<&ResponseHeadersHook>,
+) -> Result<(u16, HashMap<String, String>), LakestreamError>
+where
+    F: Fn(&mut S3Client) -> Result<HashMap<String, String>, LakestreamError>,
+{
+    let mut current_s3_client = s3_client.clone();
+    let retry_policy = s3_client.config().retry_policy().clone();
+    let mut backoff = Backoff::new(&retry_policy);
+    let mut redirected = false;
+    loop {
+        current_s3_client.config.resolve_credentials().await?;
+        let headers = generate_headers(&mut current_s3_client)?;
+        let (error_body, status_code, response_headers) =
+            http_request_with_callback(
+                &current_s3_client.url(),
+                &headers,
+                method,
+                callback,
+                on_headers,
+                current_s3_client.config().http_client_config(),
+            )
+            .await?;
+
+        if status_code == 301 {
+            current_s3_client = follow_redirect(
+                &current_s3_client,
+                &response_headers,
+                &mut redirected,
+            )
+            .await?;
+            continue;
+        }
+
+        let error_response =
+            parse_error_response(status_code, &error_body, &response_headers);
+        if is_retryable_status(status_code)
+            || error_response.as_ref().is_some_and(|e| e.is_retryable())
+        {
+            warn!(
+                "Status {} from {} (attempt {})",
+                status_code,
+                current_s3_client.url(),
+                backoff.attempt()
+            );
+            if !backoff.wait().await {
+                return Err(error_response.map_or_else(
+                    || {
+                        retries_exhausted(
+                            &current_s3_client,
+                            status_code,
+                            backoff.attempt(),
+                        )
+                    },
+                    LakestreamError::from,
+                ));
+            }
+        } else if let Some(error_response) = error_response {
+            return Err(error_response.into());
+        } else if status_code == 403 {
+            let url = current_s3_client.url();
+            return Err(LakestreamError::AccessDenied(url.to_string()));
+        } else if status_code == 407 {
+            return Err(proxy_auth_required(&current_s3_client));
+        } else {
+            return Ok((status_code, response_headers));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::http::mock_endpoint::{
+        mock_endpoint, mock_s3_config, MockResponse,
+    };
+    use crate::s3::list::create_s3_client;
+
+    const MAX_ATTEMPTS: usize = 3;
+
+    // a client for the mock endpoint, with short retry delays
+    async fn mock_client<F>(handler: F) -> (S3Client, Arc<AtomicUsize>)
+    where
+        F: Fn(usize) -> MockResponse + Send + Sync + 'static,
+    {
+        // the handler gets the number of the request, starting at 0
+        let calls = AtomicUsize::new(0);
+        let (endpoint, requests) = mock_endpoint(move |_| {
+            handler(calls.fetch_add(1, Ordering::SeqCst))
+        })
+        .await;
+        let mut config = mock_s3_config(&endpoint);
+        config.set("AWS_MAX_ATTEMPTS".to_string(), MAX_ATTEMPTS.to_string());
+        config.set("S3_RETRY_BASE_DELAY_MS".to_string(), "1".to_string());
+        (create_s3_client(&config, Some("bucket")), requests)
+    }
+
+    // result of both the buffered and the streamed request, with the
+    // number of requests each made
+    async fn request_both<F>(
+        handler: F,
+    ) -> Vec<(Result<u16, LakestreamError>, usize)>
+    where
+        F: Fn(usize) -> MockResponse + Send + Sync + Clone + 'static,
+    {
+        let no_headers = |_: &mut S3Client| Ok(HashMap::new());
+        let (s3_client, requests) = mock_client(handler.clone()).await;
+        let buffered =
+            http_with_redirect_handling(&s3_client, no_headers, "GET", None)
+                .await
+                .map(|(_, _, status, _)| status);
+        let buffered_requests = requests.load(Ordering::SeqCst);
+
+        let (s3_client, requests) = mock_client(handler).await;
+        let callback = BinaryCallbackWrapper::create_async(|_| async {});
+        let streamed = http_stream_with_redirect_handling(
+            &s3_client, no_headers, "GET", &callback, None,
+        )
+        .await
+        .map(|(status, _)| status);
+        vec![
+            (buffered, buffered_requests),
+            (streamed, requests.load(Ordering::SeqCst)),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_retry_slow_down() {
+        let results = request_both(|call| match call {
+            0 => MockResponse::new(
+                503,
+                "<Error><Code>SlowDown</Code>\
+                 <Message>Please reduce your request rate.</Message></Error>",
+            ),
+            _ => MockResponse::new(200, "data"),
+        })
+        .await;
+        for (result, requests) in results {
+            assert_eq!(result.unwrap(), 200);
+            assert_eq!(requests, 2);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_exhausted() {
+        let results =
+            request_both(|_| MockResponse::new(500, "internal error")).await;
+        for (result, requests) in results {
+            let err = result.unwrap_err().to_string();
+            assert!(err.contains("status 500 after 3 attempt(s)"), "{}", err);
+            assert_eq!(requests, MAX_ATTEMPTS);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_no_retry_not_found() {
+        let results = request_both(|_| {
+            MockResponse::new(
+                404,
+                "<Error><Code>NoSuchKey</Code>\
+                 <Message>The specified key does not exist.</Message></Error>",
+            )
+        })
+        .await;
+        for (result, requests) in results {
+            let err = result.unwrap_err().to_string();
+            assert!(err.contains("NoSuchKey"), "{}", err);
+            assert_eq!(requests, 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_redirect_once() {
+        let redirect = |region: &str| {
+            MockResponse::new(301, "")
+                .with_header("x-amz-bucket-region", region)
+        };
+        // the region is switched once, the request is then repeated
+        let results = request_both(move |call| match call {
+            0 => redirect("eu-west-1"),
+            _ => MockResponse::new(200, "data"),
+        })
+        .await;
+        for (result, requests) in results {
+            assert_eq!(result.unwrap(), 200);
+            assert_eq!(requests, 2);
+        }
+
+        // an endpoint that keeps redirecting is not followed forever
+        let results = request_both(move |_| redirect("eu-west-1")).await;
+        for (result, requests) in results {
+            let err = result.unwrap_err().to_string();
+            assert!(err.contains("Redirected again"), "{}", err);
+            assert_eq!(requests, 2);
         }
     }
 }
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use super::bucket::S3Credentials;
+use super::client_config::S3ClientConfig;
+use super::credentials::StaticProvider;
+use super::profile::AssumeRole;
+use super::provider::AddressingStyle;
+use super::request_builder::{query_encode, RequestBuilder};
+use crate::http::client_config::HttpClientConfig;
+use crate::http::requests::http_request_with_headers;
+use crate::http::retry::RetryPolicy;
+use crate::utils::time::{rfc3339_to_epoch, system_time_in_seconds};
+use crate::LakestreamError;
+
+const STS_API_VERSION: &str = "2011-06-15";
+
+// regional STS endpoint, used unless AWS_ENDPOINT_URL_STS is set
+pub fn sts_endpoint_url(region: &str) -> String {
+    format!("https://sts.{}.amazonaws.com", region)
+}
+
+// temporary credentials of a role, via the STS AssumeRole action signed
+// with the given credentials
+pub async fn assume_role(
+    endpoint_url: &str,
+    region: &str,
+    credentials: &S3Credentials,
+    role: &AssumeRole,
+    client_config: &HttpClientConfig,
+) -> Result<S3Credentials, LakestreamError> {
+    let session_name = role
+        .role_session_name
+        .clone()
+        .unwrap_or_else(|| format!("lumni-{}", system_time_in_seconds()));
+    let mut params = vec![
+        ("Action", "AssumeRole".to_string()),
+        ("Version", STS_API_VERSION.to_string()),
+        ("RoleArn", role.role_arn.clone()),
+        ("RoleSessionName", session_name),
+    ];
+    if let Some(external_id) = &role.external_id {
+        params.push(("ExternalId", external_id.clone()));
+    }
+    if let Some(duration_seconds) = role.duration_seconds {
+        params.push(("DurationSeconds", duration_seconds.to_string()));
+    }
+    let body = params
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, query_encode(value)))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    let mut signing_config = S3ClientConfig::new(
+        Some(Arc::new(StaticProvider::new(credentials.clone()))),
+        None,
+        Some(endpoint_url),
+        region,
+        AddressingStyle::Path,
+        RetryPolicy::default(),
+        client_config.clone(),
+    );
+    signing_config.resolve_credentials().await?;
+    let payload_hash = format!("{:x}", Sha256::digest(body.as_bytes()));
+    let content_type = HashMap::from([(
+        "content-type".to_string(),
+        "application/x-www-form-urlencoded; charset=utf-8".to_string(),
+    )]);
+    let headers = RequestBuilder::new(endpoint_url)
+        .with_service("sts")
+        .generate_headers(
+            &signing_config,
+            "POST",
+            None,
+            None,
+            Some(&payload_hash),
+            Some(&content_type),
+        )?;
+
+    let (response, status, _) = http_request_with_headers(
+        endpoint_url,
+        &headers,
+        "POST",
+        Some(Bytes::from(body)),
+        client_config,
+    )
+    .await
+    .map_err(|err| assume_role_error(role, err.to_string()))?;
+    let response = String::from_utf8_lossy(&response);
+    if status != 200 {
+        return Err(assume_role_error(
+            role,
+            format!("status {}: {}", status, response.trim()),
+        ));
+    }
+    parse_assume_role_result(&response)
+        .map_err(|err| assume_role_error(role, err.to_string()))
+}
+
+fn assume_role_error(role: &AssumeRole, message: String) -> LakestreamError {
+    LakestreamError::ConfigError(format!(
+        "Failed to assume role {}: {}",
+        role.role_arn, message
+    ))
+}
+
+// allow non snake case for the XML response
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize)]
+struct AssumeRoleResponse {
+    AssumeRoleResult: AssumeRoleResult,
+}
+
+// allow non snake case for the XML response
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize)]
+struct AssumeRoleResult {
+    Credentials: RoleCredentials,
+}
+
+// allow non snake case for the XML response
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize)]
+struct RoleCredentials {
+    AccessKeyId: String,
+    SecretAccessKey: String,
+    SessionToken: String,
+    Expiration: String,
+}
+
+fn parse_assume_role_result(
+    body: &str,
+) -> Result<S3Credentials, Box<dyn std::error::Error>> {
+    let response: AssumeRoleResponse = serde_xml_rs::from_str(body)?;
+    let credentials = response.AssumeRoleResult.Credentials;
+    let expiration = rfc3339_to_epoch(&credentials.Expiration)?;
+    Ok(S3Credentials::new(
+        credentials.AccessKeyId,
+        credentials.SecretAccessKey,
+        Some(credentials.SessionToken),
+    )
+    .with_expiration(Some(expiration)))
+}
@@ -4,7 +4,8 @@ use std::sync::Arc;
 
 use crate::formatters::{bytes_human_readable, time_human_readable};
 use crate::table::{
-    OptionalUint64Column, StringColumn, TableRow, Uint64Column,
+    OptionalStringColumn, OptionalUint64Column, StringColumn, TableRow,
+    Uint64Column,
 };
 use crate::{FileObject, Table, TableCallback, TableColumn, TableColumnValue};
 
@@ -36,6 +37,12 @@ impl FileObjectTable {
                         "modified",
                         Box::new(OptionalUint64Column(Vec::new())),
                     ),
+                    // only added when selected, as computing these for
+                    // local files requires reading the file
+                    "etag" | "md5" | "sha256" => table.add_column(
+                        column,
+                        Box::new(OptionalStringColumn(Vec::new())),
+                    ),
                     _ => panic!("Invalid column name: {}", column),
                 }
             }
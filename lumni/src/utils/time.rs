@@ -1,6 +1,8 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
-pub use super::time_parse_ext::{datetime_utc, rfc3339_to_epoch};
+pub use super::time_parse_ext::{
+    datetime_utc, http_date_to_epoch, rfc3339_to_epoch,
+};
 
 impl UtcTimeNow {
     pub fn new() -> UtcTimeNow {
@@ -14,6 +16,26 @@ impl UtcTimeNow {
             second,
         }
     }
+
+    // fixed time, e.g. to check signatures against known values
+    #[cfg(test)]
+    pub fn from_parts(
+        year: u32,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+    ) -> UtcTimeNow {
+        UtcTimeNow {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        }
+    }
 }
 
 pub struct UtcTimeNow {
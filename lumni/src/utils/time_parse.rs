@@ -1,5 +1,13 @@
+use std::time::Duration;
+
 use regex::Regex;
 
+// a duration as accepted by calculate_time_offset_seconds, e.g. "15m",
+// "1D8h" or "900" (seconds), shared by all duration arguments
+pub fn parse_duration(duration: &str) -> Result<Duration, String> {
+    calculate_time_offset_seconds(duration).map(Duration::from_secs)
+}
+
 pub fn calculate_time_offset_seconds(
     time_offset_str: &str,
 ) -> Result<u64, String> {
@@ -7,12 +15,28 @@ pub fn calculate_time_offset_seconds(
     const MAX_OFFSET_SECONDS: i64 = 10000 * 366 * 86400;
     const MIN_OFFSET_SECONDS: i64 = -MAX_OFFSET_SECONDS;
 
-    let re = Regex::new(r"^(?:(?P<value>\d+)(?P<unit>[YMWDhms]))+$").unwrap();
+    // a number without unit is in seconds
+    let with_unit;
+    let mut remaining_str = if !time_offset_str.is_empty()
+        && time_offset_str.chars().all(|c| c.is_ascii_digit())
+    {
+        with_unit = format!("{}s", time_offset_str);
+        &with_unit
+    } else {
+        time_offset_str
+    };
+    // one value and unit at a time, the string must consist of these
+    let re = Regex::new(r"^(?P<value>\d+)(?P<unit>[YMWDhms])").unwrap();
+    let out_of_range = || {
+        format!(
+            "Invalid time offset string: {} (offset exceeds valid range)",
+            time_offset_str
+        )
+    };
 
     let mut total_offset_seconds = 0i64;
-    let mut remaining_str = time_offset_str;
     while let Some(caps) = re.captures(remaining_str) {
-        let value: u64 = caps["value"].parse().expect("Invalid numeric value");
+        let value: u64 = caps["value"].parse().map_err(|_| out_of_range())?;
         let unit = &caps["unit"];
         let seconds_multiplier = TIME_UNITS
             .iter()
@@ -25,10 +49,7 @@ pub fn calculate_time_offset_seconds(
         if !(MIN_OFFSET_SECONDS..=MAX_OFFSET_SECONDS)
             .contains(&total_offset_seconds)
         {
-            return Err(format!(
-                "Invalid time offset string: {} (offset exceeds valid range)",
-                time_offset_str
-            ));
+            return Err(out_of_range());
         }
 
         remaining_str = &remaining_str[caps.get(0).unwrap().end()..];
@@ -6,6 +6,12 @@ pub fn rfc3339_to_epoch(timestamp: &str) -> Result<u64, JsValue> {
     Ok((date.get_time() / 1000.0) as u64)
 }
 
+// parse a HTTP-date, e.g. as used in a Last-Modified header
+pub fn http_date_to_epoch(timestamp: &str) -> Result<u64, JsValue> {
+    let date = Date::new(&JsValue::from_str(timestamp));
+    Ok((date.get_time() / 1000.0) as u64)
+}
+
 pub fn epoch_to_rfc3339(timestamp: u64) -> Result<String, JsValue> {
     let date = Date::new(&JsValue::from_f64(timestamp as f64 * 1000.0));
     let date_string = date.to_iso_string().as_string().unwrap();
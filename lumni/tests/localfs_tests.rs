@@ -48,3 +48,416 @@
 //    assert!(filenames.contains(&file_path2_str));
 //}
 //
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use futures::TryStreamExt;
+use lumni::{
+    BinaryCallbackWrapper, ByteRange, DeleteResult, EnvironmentConfig,
+    FileObjectFilter, LakestreamError, ListStart, ObjectStoreHandler,
+    TableCallback, TableColumnValue, TableRow,
+};
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn test_put_object_creates_parent_directories() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let temp_dir_path = temp_dir.path().to_path_buf();
+
+    let config = EnvironmentConfig::new(HashMap::new());
+    let handler = ObjectStoreHandler::new(None);
+    let uri =
+        format!("localfs://{}/nested/dir/file.txt", temp_dir_path.display());
+
+    handler
+        .put_object(&uri, &config, b"hello world", None)
+        .await
+        .unwrap();
+
+    let file_path = temp_dir_path.join("nested/dir/file.txt");
+    assert_eq!(std::fs::read(&file_path).unwrap(), b"hello world");
+
+    let data = handler.get_object(&uri, &config, None, None).await.unwrap();
+    assert_eq!(data, Some(b"hello world".to_vec()));
+}
+
+#[tokio::test]
+async fn test_get_object_streams_chunks_to_callback() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let file_path = temp_dir.path().join("large.bin");
+    let content: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+    std::fs::write(&file_path, &content).unwrap();
+
+    let chunks = Arc::new(Mutex::new(Vec::<Vec<u8>>::new()));
+    let chunks_clone = chunks.clone();
+    let callback = BinaryCallbackWrapper::create_async(move |data: Vec<u8>| {
+        chunks_clone.lock().unwrap().push(data);
+        async {}
+    });
+
+    let config = EnvironmentConfig::new(HashMap::new());
+    let handler = ObjectStoreHandler::new(None);
+    let uri = format!("localfs://{}", file_path.display());
+    let result = handler
+        .get_object(&uri, &config, None, Some(callback))
+        .await;
+    assert!(matches!(result, Ok(None)));
+
+    let chunks = chunks.lock().unwrap();
+    assert!(chunks.len() > 1);
+    assert_eq!(chunks.concat(), content);
+}
+
+#[tokio::test]
+async fn test_get_object_byte_range() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let file_path = temp_dir.path().join("data.txt");
+    std::fs::write(&file_path, b"0123456789").unwrap();
+
+    let config = EnvironmentConfig::new(HashMap::new());
+    let handler = ObjectStoreHandler::new(None);
+    let uri = format!("localfs://{}", file_path.display());
+
+    let range = ByteRange::new(2, Some(3)).unwrap();
+    let data = handler.get_object(&uri, &config, Some(&range), None).await;
+    assert_eq!(data.unwrap(), Some(b"234".to_vec()));
+
+    let range = ByteRange::suffix(4).unwrap();
+    let data = handler.get_object(&uri, &config, Some(&range), None).await;
+    assert_eq!(data.unwrap(), Some(b"6789".to_vec()));
+
+    let range = ByteRange::new(20, None).unwrap();
+    let data = handler.get_object(&uri, &config, Some(&range), None).await;
+    assert!(data.is_err());
+}
+
+#[tokio::test]
+async fn test_head_object_metadata() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let temp_dir_path = temp_dir.path().to_path_buf();
+    let file_path = temp_dir_path.join("nested/file.txt");
+    std::fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+    std::fs::write(&file_path, b"hello world").unwrap();
+
+    let config = EnvironmentConfig::new(HashMap::new());
+    let handler = ObjectStoreHandler::new(None);
+
+    let uri = format!("localfs://{}/nested/file.txt", temp_dir_path.display());
+    let metadata = handler.head_object(&uri, &config).await.unwrap();
+    assert_eq!(metadata.size(), 11);
+    assert!(metadata.last_modified().is_some());
+    assert_eq!(metadata.content_type(), Some("text/plain"));
+    let etag = metadata.etag().unwrap().to_string();
+    assert_eq!(
+        handler.head_object(&uri, &config).await.unwrap().etag(),
+        Some(etag.as_str())
+    );
+
+    // the ETag changes with the file
+    std::fs::write(&file_path, b"hello world!").unwrap();
+    let metadata = handler.head_object(&uri, &config).await.unwrap();
+    assert_ne!(metadata.etag(), Some(etag.as_str()));
+
+    let data_path = temp_dir_path.join("nested/data");
+    std::fs::write(&data_path, b"\x00\x01").unwrap();
+    let uri = format!("localfs://{}", data_path.display());
+    let metadata = handler.head_object(&uri, &config).await.unwrap();
+    assert_eq!(metadata.content_type(), Some("application/octet-stream"));
+
+    // directories and missing files are not objects
+    for key in ["nested", "nested/missing.txt"] {
+        let uri = format!("localfs://{}/{}", temp_dir_path.display(), key);
+        let result = handler.head_object(&uri, &config).await;
+        assert!(matches!(result, Err(LakestreamError::NotFound(_))));
+    }
+}
+
+#[tokio::test]
+async fn test_delete_objects_removes_empty_directories() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let temp_dir_path = temp_dir.path().to_path_buf();
+    for key in ["a/b/file1.txt", "a/file2.txt"] {
+        let file_path = temp_dir_path.join(key);
+        std::fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+        std::fs::write(&file_path, b"data").unwrap();
+    }
+
+    let config = EnvironmentConfig::with_setting(
+        "LOCALFS_REMOVE_EMPTY_DIRS".to_string(),
+        "true".to_string(),
+    );
+    let handler = ObjectStoreHandler::new(None);
+    let bucket_uri = format!("localfs://{}", temp_dir_path.display());
+
+    let keys = vec!["a/b/file1.txt".to_string(), "a/missing.txt".to_string()];
+    let results = handler
+        .delete_objects(&bucket_uri, &config, &keys)
+        .await
+        .unwrap();
+    assert_eq!(
+        results,
+        vec![
+            DeleteResult::deleted("a/b/file1.txt"),
+            DeleteResult::deleted("a/missing.txt"),
+        ]
+    );
+    assert!(!temp_dir_path.join("a/b").exists());
+    // "a" is not empty, so it is kept
+    assert!(temp_dir_path.join("a/file2.txt").exists());
+
+    let keys = vec!["a/file2.txt".to_string()];
+    handler
+        .delete_objects(&bucket_uri, &config, &keys)
+        .await
+        .unwrap();
+    assert!(!temp_dir_path.join("a").exists());
+    // directories are never removed beyond the given URI
+    assert!(temp_dir_path.exists());
+}
+
+#[tokio::test]
+async fn test_delete_object_removes_empty_directories() {
+    // nothing else in the temp dir, so every directory up to it (and its
+    // ancestors) would become empty
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let temp_dir_path = temp_dir.path().to_path_buf();
+    let handler = ObjectStoreHandler::new(None);
+    let uri = format!("localfs://{}/a/b/file.txt", temp_dir_path.display());
+
+    // by default, only the object is removed
+    let config = EnvironmentConfig::new(HashMap::new());
+    handler
+        .put_object(&uri, &config, b"data", None)
+        .await
+        .unwrap();
+    handler.delete_object(&uri, &config).await.unwrap();
+    assert!(!temp_dir_path.join("a/b/file.txt").exists());
+    assert!(temp_dir_path.join("a/b").exists());
+
+    // when asked for, only the parent directory of the object is removed
+    let config = EnvironmentConfig::with_setting(
+        "LOCALFS_REMOVE_EMPTY_DIRS".to_string(),
+        "true".to_string(),
+    );
+    handler
+        .put_object(&uri, &config, b"data", None)
+        .await
+        .unwrap();
+    handler.delete_object(&uri, &config).await.unwrap();
+    assert!(!temp_dir_path.join("a/b").exists());
+    assert!(temp_dir_path.join("a").exists());
+    assert!(temp_dir_path.exists());
+}
+
+// list names relative to the given base path, sorted
+async fn list_stream_names(
+    handler: &ObjectStoreHandler,
+    base_path: &str,
+    recursive: bool,
+    max_files: Option<u32>,
+    filter: Option<FileObjectFilter>,
+) -> Vec<String> {
+    let config = EnvironmentConfig::new(HashMap::new());
+    let uri = format!("localfs://{}", base_path);
+    let mut names = handler
+        .list_stream(&uri, &config, recursive, max_files, &filter)
+        .unwrap()
+        .map_ok(|file_object| {
+            file_object.name().trim_start_matches(base_path).to_string()
+        })
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    names.sort();
+    names
+}
+
+#[tokio::test]
+async fn test_list_stream() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let temp_dir_path = temp_dir.path().to_path_buf();
+    for key in ["file1.txt", "a/file2.txt", "a/b/file3.csv"] {
+        let file_path = temp_dir_path.join(key);
+        std::fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+        std::fs::write(&file_path, b"data").unwrap();
+    }
+
+    let handler = ObjectStoreHandler::new(None);
+    let base_path = temp_dir_path.to_string_lossy().to_string();
+
+    assert_eq!(
+        list_stream_names(&handler, &base_path, false, None, None).await,
+        vec!["/a/", "/file1.txt"]
+    );
+    assert_eq!(
+        list_stream_names(&handler, &base_path, true, None, None).await,
+        vec![
+            "/a/",
+            "/a/b/",
+            "/a/b/file3.csv",
+            "/a/file2.txt",
+            "/file1.txt"
+        ]
+    );
+    assert_eq!(
+        list_stream_names(&handler, &base_path, true, Some(2), None)
+            .await
+            .len(),
+        2
+    );
+
+    let filter = FileObjectFilter::new(Some(r"\.csv$"), None, None).unwrap();
+    assert_eq!(
+        list_stream_names(&handler, &base_path, true, None, Some(filter)).await,
+        vec!["/a/b/file3.csv"]
+    );
+}
+
+#[tokio::test]
+async fn test_list_page_resumes_from_token() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let temp_dir_path = temp_dir.path().to_path_buf();
+    let keys = ["a/b/file2.txt", "a/file1.txt", "a/file3.txt", "file4.txt"];
+    for key in keys {
+        let file_path = temp_dir_path.join(key);
+        std::fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+        std::fs::write(&file_path, b"data").unwrap();
+    }
+
+    let config = EnvironmentConfig::new(HashMap::new());
+    let handler = ObjectStoreHandler::new(None);
+    let uri = format!("localfs://{}", temp_dir_path.display());
+    let key_of = |name: &str| {
+        name.trim_start_matches(&*temp_dir_path.to_string_lossy())
+            .trim_start_matches('/')
+            .to_string()
+    };
+
+    let mut listed = Vec::new();
+    let mut start = None;
+    loop {
+        let page = handler
+            .list_page(&uri, &config, true, Some(3), start.as_ref(), &None)
+            .await
+            .unwrap();
+        assert!(page.file_objects().len() <= 3);
+        let (file_objects, next_token) = page.into_parts();
+        listed.extend(file_objects.iter().map(|fo| key_of(fo.name())));
+        match next_token {
+            Some(token) => start = Some(ListStart::Token(token)),
+            None => break,
+        }
+    }
+    assert_eq!(listed, keys);
+
+    let start = ListStart::After("a/file1.txt".to_string());
+    let page = handler
+        .list_page(&uri, &config, true, None, Some(&start), &None)
+        .await
+        .unwrap();
+    let names = page
+        .file_objects()
+        .iter()
+        .map(|fo| key_of(fo.name()))
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["a/file3.txt", "file4.txt"]);
+    assert!(page.next_token().is_none());
+}
+
+type DigestRow = (Option<String>, Option<String>);
+
+// collects the md5 and sha256 columns of the listed rows, by name
+struct DigestCallback {
+    rows: Arc<Mutex<HashMap<String, DigestRow>>>,
+}
+
+impl TableCallback for DigestCallback {
+    fn on_row_add(&self, row: &mut TableRow) {
+        let mut name = String::new();
+        let mut digests = (None, None);
+        for (column, value) in row.data() {
+            match (column.as_str(), value) {
+                ("name", TableColumnValue::StringColumn(val)) => {
+                    name = val.clone()
+                }
+                ("md5", TableColumnValue::OptionalStringColumn(val)) => {
+                    digests.0 = val.clone()
+                }
+                ("sha256", TableColumnValue::OptionalStringColumn(val)) => {
+                    digests.1 = val.clone()
+                }
+                _ => {}
+            }
+        }
+        self.rows.lock().unwrap().insert(name, digests);
+    }
+}
+
+async fn list_digests(
+    uri: &str,
+    selected_columns: Vec<&str>,
+) -> HashMap<String, DigestRow> {
+    let rows = Arc::new(Mutex::new(HashMap::new()));
+    let callback = Arc::new(DigestCallback { rows: rows.clone() });
+    let config = EnvironmentConfig::new(HashMap::new());
+    ObjectStoreHandler::new(None)
+        .list_objects(
+            uri,
+            &config,
+            Some(selected_columns),
+            true,
+            None,
+            &None,
+            Some(callback),
+        )
+        .await
+        .unwrap();
+    let rows = rows.lock().unwrap().clone();
+    rows
+}
+
+#[tokio::test]
+async fn test_list_digest_columns() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let temp_dir_path = temp_dir.path().to_path_buf();
+    std::fs::create_dir(temp_dir_path.join("dir")).unwrap();
+    std::fs::write(temp_dir_path.join("dir/hello.txt"), b"hello world")
+        .unwrap();
+    std::fs::write(temp_dir_path.join("empty.txt"), b"").unwrap();
+
+    let uri = format!("localfs://{}", temp_dir_path.display());
+    let rows = list_digests(&uri, vec!["name", "md5", "sha256"]).await;
+    let digests = |key: &str| {
+        let name = temp_dir_path.join(key).to_string_lossy().to_string();
+        rows.get(&name).cloned().unwrap()
+    };
+
+    assert_eq!(
+        digests("dir/hello.txt"),
+        (
+            Some("5eb63bbbe01eeed093cb22bb8f5acdc3".to_string()),
+            Some(
+                "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+                    .to_string()
+            )
+        )
+    );
+    assert_eq!(
+        digests("empty.txt"),
+        (
+            Some("d41d8cd98f00b204e9800998ecf8427e".to_string()),
+            Some(
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+                    .to_string()
+            )
+        )
+    );
+    // directories (listed by their relative name) have no digests
+    assert_eq!(rows.get("dir"), Some(&(None, None)));
+
+    // files are only read when a digest column is selected
+    let rows = list_digests(&uri, vec!["name", "size"]).await;
+    assert_eq!(rows.len(), 3);
+    assert!(rows.values().all(|digests| *digests == (None, None)));
+}